@@ -18,14 +18,16 @@ fn benchmark_file_collection(c: &mut Criterion) {
             let mut audio_files = Vec::new();
             let test_dir = black_box("./");
 
-            for entry in WalkDir::new(test_dir).follow_links(false) {
-                if let Ok(entry) = entry {
-                    if entry.file_type().is_file() {
-                        if let Some(extension) = entry.path().extension() {
-                            if let Some(ext_str) = extension.to_str() {
-                                if audio_extensions.contains(&ext_str.to_lowercase().as_str()) {
-                                    audio_files.push(entry.path().to_path_buf());
-                                }
+            for entry in WalkDir::new(test_dir)
+                .follow_links(false)
+                .into_iter()
+                .flatten()
+            {
+                if entry.file_type().is_file() {
+                    if let Some(extension) = entry.path().extension() {
+                        if let Some(ext_str) = extension.to_str() {
+                            if audio_extensions.contains(&ext_str.to_lowercase().as_str()) {
+                                audio_files.push(entry.path().to_path_buf());
                             }
                         }
                     }
@@ -5,7 +5,6 @@
 /// 
 /// このファイルは使用例を示すためのドキュメンテーションです。
 /// 実際の使用には以下のCLIコマンドを使用してください。
-
 fn main() {
     println!("🎵 Audio Probe - 高性能音声ファイル解析ツール");
     println!("==============================================\n");
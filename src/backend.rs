@@ -0,0 +1,713 @@
+// 解析バックエンド
+//
+// ネイティブパーサー・ffprobe・拡張子などからの推定を共通のトレイトで扱い、設定した順に試す。
+// ファイルごとに値を出したバックエンドと、失敗したバックエンドとそのエラーを記録する。
+
+use crate::ffprobe::FFprobeVersion;
+use crate::formats::{self, sniff::Container};
+use crate::process::{self, ResourceLimits};
+use crate::{
+    AudioInfo, AudioProbeError, FFProbeChapter, FFProbeOutput, FFProbeStream, FieldSource,
+};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// バックエンドの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Rust実装のパーサー
+    Native,
+    /// 外部のffprobe
+    Ffprobe,
+    /// コンテナの種類・拡張子・ファイルサイズからの推定
+    Estimate,
+}
+
+impl BackendKind {
+    /// デフォルトの順序（最も多くのフィールドを出すffprobeを優先し、ネイティブパーサーを代替、
+    /// 推定を最後の手段とする。ffprobeが利用できなければネイティブパーサーから試す）
+    pub const DEFAULT_CHAIN: [BackendKind; 3] = [
+        BackendKind::Ffprobe,
+        BackendKind::Native,
+        BackendKind::Estimate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BackendKind::Native => "native",
+            BackendKind::Ffprobe => "ffprobe",
+            BackendKind::Estimate => "estimate",
+        }
+    }
+
+    /// 試す順序を決める（`--backend`の指定 > 設定ファイルの`backends` > デフォルト）
+    pub fn resolve_chain(cli: &[BackendKind], config: Option<&[BackendKind]>) -> Vec<BackendKind> {
+        if !cli.is_empty() {
+            return cli.to_vec();
+        }
+        config.unwrap_or(&Self::DEFAULT_CHAIN).to_vec()
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 失敗したバックエンドとそのエラー
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendAttempt {
+    pub backend: BackendKind,
+    /// `AudioProbeError::kind`と同じ種類名
+    pub kind: String,
+    pub error: String,
+}
+
+/// 1ファイルの解析に必要な情報
+pub struct ProbeRequest<'a> {
+    pub path: &'a Path,
+    /// 先頭バイトから判定したコンテナ
+    pub container: Option<Container>,
+    pub file_size: u64,
+    /// ネイティブパーサー・ffprobeそれぞれの1ファイルあたりのタイムアウト
+    pub timeout: Option<Duration>,
+    pub resource_limits: ResourceLimits,
+}
+
+/// 解析バックエンド
+pub trait ProbeBackend: Send + Sync {
+    fn kind(&self) -> BackendKind;
+
+    /// ファイルを解析する。扱えないファイルはエラーを返し、次のバックエンドに任せる
+    fn probe<'a>(
+        &'a self,
+        request: &'a ProbeRequest<'a>,
+    ) -> BoxFuture<'a, Result<AudioInfo, AudioProbeError>>;
+}
+
+/// 設定した順に並べたバックエンド
+#[derive(Clone, Default)]
+pub struct BackendChain {
+    backends: Vec<Arc<dyn ProbeBackend>>,
+}
+
+impl BackendChain {
+    /// `kinds`の順にバックエンドを並べる（ffprobeが利用できない場合は除く）
+    pub fn new(kinds: &[BackendKind], ffprobe: Option<&FFprobeVersion>) -> Self {
+        let backends = kinds
+            .iter()
+            .filter_map(|kind| -> Option<Arc<dyn ProbeBackend>> {
+                match kind {
+                    BackendKind::Native => Some(Arc::new(NativeBackend)),
+                    BackendKind::Ffprobe => Some(Arc::new(FFprobeBackend {
+                        ffprobe: ffprobe?.clone(),
+                    })),
+                    BackendKind::Estimate => Some(Arc::new(EstimateBackend)),
+                }
+            })
+            .collect();
+        Self { backends }
+    }
+
+    /// 実際に使うバックエンドの順序
+    pub fn kinds(&self) -> Vec<BackendKind> {
+        self.backends.iter().map(|backend| backend.kind()).collect()
+    }
+
+    /// 順にバックエンドを試し、最初に成功した結果に使ったバックエンドと失敗の記録を付けて返す
+    ///
+    /// 全て失敗した場合は最後のエラーを返す
+    pub async fn probe(&self, request: &ProbeRequest<'_>) -> Result<AudioInfo, AudioProbeError> {
+        let mut failed_attempts = Vec::new();
+        let mut last_error = None;
+        for backend in &self.backends {
+            match backend.probe(request).await {
+                Ok(mut info) => {
+                    info.backend = Some(backend.kind());
+                    info.failed_attempts = failed_attempts;
+                    return Ok(info);
+                }
+                // タイムアウトしたファイルは次のバックエンドの推定値で埋めず、エラーとして報告する
+                Err(e @ AudioProbeError::Timeout { .. }) => return Err(e),
+                Err(e) => {
+                    if backend.kind() == BackendKind::Native {
+                        debug!("Native analysis unavailable for {:?}: {}", request.path, e);
+                    } else {
+                        warn!(
+                            "{} analysis failed for {:?}: {}",
+                            backend.kind(),
+                            request.path,
+                            e
+                        );
+                    }
+                    failed_attempts.push(BackendAttempt {
+                        backend: backend.kind(),
+                        kind: e.kind().to_string(),
+                        error: e.to_string(),
+                    });
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| AudioProbeError::Processing {
+            path: request.path.to_path_buf(),
+            message: "No analysis backend is available".to_string(),
+            backend: None,
+        }))
+    }
+}
+
+/// ネイティブパーサー（ffprobeの起動を省略する高速解析）
+pub struct NativeBackend;
+
+impl NativeBackend {
+    /// ブロッキングスレッドで解析する
+    ///
+    /// タイムアウトした場合はエラーを返すが、スレッド自体は中断できないため解析が終わるまで残る
+    async fn analyze(request: &ProbeRequest<'_>) -> Result<AudioInfo, AudioProbeError> {
+        let path = request.path.to_path_buf();
+        let container = request.container;
+        let start = Instant::now();
+        let task = tokio::task::spawn_blocking(move || formats::probe_file(&path, container));
+        let joined =
+            match request.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, task).await.map_err(|_| {
+                    AudioProbeError::Timeout {
+                        path: request.path.to_path_buf(),
+                        elapsed: start.elapsed(),
+                        backend: BackendKind::Native,
+                    }
+                })?,
+                None => task.await,
+            };
+        joined.map_err(|e| AudioProbeError::Processing {
+            path: request.path.to_path_buf(),
+            message: format!("Native analysis task failed: {}", e),
+            backend: Some(BackendKind::Native),
+        })?
+    }
+}
+
+impl ProbeBackend for NativeBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Native
+    }
+
+    fn probe<'a>(
+        &'a self,
+        request: &'a ProbeRequest<'a>,
+    ) -> BoxFuture<'a, Result<AudioInfo, AudioProbeError>> {
+        Self::analyze(request).boxed()
+    }
+}
+
+/// ffprobe（`-version`で利用可能と確認できたもの）
+pub struct FFprobeBackend {
+    pub ffprobe: FFprobeVersion,
+}
+
+impl FFprobeBackend {
+    async fn analyze(&self, request: &ProbeRequest<'_>) -> Result<AudioInfo, AudioProbeError> {
+        let path = request.path;
+        let mut command = Command::new(&self.ffprobe.path);
+        command
+            .args([
+                // エラー内容はstderrから報告するため、エラーだけは出力させる
+                "-v",
+                "error",
+                "-print_format",
+                "json",
+                "-show_format",
+                "-show_streams",
+                "-show_chapters",
+            ])
+            .arg(path);
+        let output =
+            process::output_with_timeout(command, path, request.timeout, request.resource_limits)
+                .await?;
+
+        if !output.status.success() {
+            return Err(AudioProbeError::ffprobe_failed(path, &output));
+        }
+
+        let json_str = String::from_utf8_lossy(&output.stdout);
+        let probe_data: FFProbeOutput =
+            serde_json::from_str(&json_str).map_err(|e| AudioProbeError::Processing {
+                path: path.to_path_buf(),
+                message: format!("Failed to parse ffprobe output: {}", e),
+                backend: Some(BackendKind::Ffprobe),
+            })?;
+
+        let mut audio_info = AudioInfo::new(path.to_path_buf());
+        audio_info.file_size = request.file_size;
+
+        // フォーマット情報
+        if let Some(format) = probe_data.format {
+            audio_info.format_name = format.format_name;
+            audio_info.format_long_name = format.format_long_name;
+
+            if let Some(duration_str) = format.duration {
+                audio_info.duration_seconds = duration_str.parse::<f64>().unwrap_or(0.0);
+            }
+
+            if let Some(bit_rate_str) = format.bit_rate {
+                audio_info.bit_rate = bit_rate_str.parse::<i64>().unwrap_or(0);
+            }
+
+            audio_info.start_time = format.start_time.and_then(|time| time.parse().ok());
+            audio_info.probe_score = format.probe_score;
+            audio_info.nb_streams = format.nb_streams;
+
+            // メタデータ
+            if let Some(tags) = format.tags {
+                for (key, value) in tags {
+                    audio_info.metadata.insert(key.to_lowercase(), value);
+                }
+            }
+        }
+
+        // ffprobeが読み飛ばしたAPEタグで欠けているキーを補う
+        match formats::apetag::read_path(path) {
+            Ok(entries) => {
+                for (key, value) in entries {
+                    audio_info.metadata.entry(key).or_insert(value);
+                }
+            }
+            Err(e) => debug!("Failed to read APE tag from {:?}: {}", path, e),
+        }
+
+        // ストリーム情報（添付画像はアートワークとして扱い、動画とはみなさない）
+        audio_info.artwork = probe_data
+            .streams
+            .iter()
+            .filter_map(FFProbeStream::attached_picture)
+            .collect();
+        audio_info.streams = probe_data
+            .streams
+            .into_iter()
+            .map(FFProbeStream::into_stream_info)
+            .collect();
+        audio_info.has_video = audio_info
+            .streams
+            .iter()
+            .any(|s| s.codec_type == "video" && !s.disposition.attached_pic);
+        // 書き出せるよう、ID3v2タグがあれば画像データ付きのアートワークに置き換える
+        if !audio_info.artwork.is_empty() {
+            match formats::artwork::read_id3_path(path) {
+                Ok(artwork) if !artwork.is_empty() => audio_info.artwork = artwork,
+                Ok(_) => {}
+                Err(e) => debug!("Failed to read ID3 artwork from {:?}: {}", path, e),
+            }
+        }
+        audio_info.chapters = probe_data
+            .chapters
+            .into_iter()
+            .map(FFProbeChapter::into_chapter)
+            .collect();
+
+        // 要約フィールドは主音声ストリーム（defaultの音声、なければ最初の音声）から
+        let primary = audio_info
+            .streams
+            .iter()
+            .find(|s| s.is_audio() && s.disposition.default)
+            .or_else(|| audio_info.streams.iter().find(|s| s.is_audio()))
+            .cloned();
+        if let Some(stream) = primary {
+            audio_info.codec_name = stream.codec_name;
+            audio_info.codec_long_name = stream.codec_long_name;
+            audio_info.sample_rate = stream.sample_rate;
+            audio_info.channels = stream.channels;
+            audio_info.channel_layout = stream.channel_layout;
+            audio_info.bits_per_sample = stream.bits_per_sample;
+            audio_info.bits_per_raw_sample = stream.bits_per_raw_sample;
+            audio_info.sample_fmt = stream.sample_fmt;
+            audio_info.profile = stream.profile;
+            audio_info.start_time = stream.start_time.or(audio_info.start_time);
+            audio_info.duration_ts = stream.duration_ts;
+            audio_info.time_base = stream.time_base;
+            audio_info.nb_frames = stream.nb_frames;
+
+            // フォーマットのビットレートがない場合はストリームのビットレートを使う
+            if stream.bit_rate > 0 && audio_info.bit_rate == 0 {
+                audio_info.bit_rate = stream.bit_rate;
+            }
+        }
+
+        // 古いffprobeが出力しないフィールドは値がないことを明示する
+        for (field, (major, minor)) in self.ffprobe.unsupported_fields() {
+            audio_info.warnings.push(format!(
+                "ffprobe {} does not report {} (requires {}.{} or newer)",
+                self.ffprobe.display_version(),
+                field,
+                major,
+                minor
+            ));
+        }
+
+        Ok(audio_info)
+    }
+}
+
+impl ProbeBackend for FFprobeBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Ffprobe
+    }
+
+    fn probe<'a>(
+        &'a self,
+        request: &'a ProbeRequest<'a>,
+    ) -> BoxFuture<'a, Result<AudioInfo, AudioProbeError>> {
+        self.analyze(request).boxed()
+    }
+}
+
+/// 推定のみ（失敗しない）
+pub struct EstimateBackend;
+
+impl EstimateBackend {
+    /// 判定できたコンテナ、なければ拡張子とファイルサイズから基本情報を推定する
+    ///
+    /// 値を設定したフィールドには出所を`provenance`に記録する
+    fn estimate(request: &ProbeRequest<'_>) -> AudioInfo {
+        let path = request.path;
+        let mut audio_info = AudioInfo::new(path.to_path_buf());
+        audio_info.file_size = request.file_size;
+        audio_info.estimated = true;
+        let mut mark = |fields: &[&str], source: FieldSource| {
+            for field in fields {
+                audio_info.provenance.insert(field.to_string(), source);
+            }
+        };
+        // 内容から判定できたコンテナを拡張子より優先する
+        let format_key = match request.container {
+            Some(container) => Some((container.primary_extension(), FieldSource::Estimated)),
+            None => path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| (ext, FieldSource::Filename)),
+        };
+        // 実際に値を設定したフィールドのみ出所を記録する
+        if let Some((ext_str, source)) = format_key {
+            mark(&["format_name", "codec_name"], source);
+            mark(&["sample_rate", "channels"], FieldSource::Default);
+            audio_info.format_name = ext_str.to_lowercase();
+            audio_info.codec_name = ext_str.to_lowercase();
+
+            // 拡張子に基づく基本情報の推定
+            match ext_str.to_lowercase().as_str() {
+                "mp3" => {
+                    audio_info.codec_long_name = "MP3 (MPEG audio layer 3)".to_string();
+                    audio_info.format_long_name = "MP2/3 (MPEG audio layer 2/3)".to_string();
+                    audio_info.sample_rate = 44100;
+                    audio_info.channels = 2;
+                    audio_info.bit_rate = 320000;
+                }
+                "wav" => {
+                    audio_info.codec_name = "pcm_s16le".to_string();
+                    audio_info
+                        .provenance
+                        .insert("codec_name".to_string(), FieldSource::Default);
+                    audio_info.codec_long_name = "PCM signed 16-bit little-endian".to_string();
+                    audio_info.format_long_name = "WAV / WAVE (Waveform Audio)".to_string();
+                    audio_info.sample_rate = 44100;
+                    audio_info.channels = 2;
+                    audio_info.bit_rate = 44100 * 2 * 16; // 1411200
+                }
+                "flac" => {
+                    audio_info.codec_long_name = "FLAC (Free Lossless Audio Codec)".to_string();
+                    audio_info.format_long_name = "raw FLAC".to_string();
+                    audio_info.sample_rate = 44100;
+                    audio_info.channels = 2;
+                }
+                _ => {
+                    audio_info.codec_long_name = format!("{} audio", ext_str.to_uppercase());
+                    audio_info.format_long_name = format!("{} format", ext_str.to_uppercase());
+                    audio_info.sample_rate = 44100;
+                    audio_info.channels = 2;
+                    audio_info.bit_rate = 320000;
+                }
+            }
+        }
+
+        // 先頭のID3v2タグにある画像は拡張子に関係なく読める
+        match formats::artwork::read_id3_path(path) {
+            Ok(artwork) => audio_info.artwork = artwork,
+            Err(e) => debug!("Failed to read ID3 artwork from {:?}: {}", path, e),
+        }
+
+        // ファイルサイズに基づく継続時間の推定
+        if audio_info.bit_rate > 0 {
+            audio_info.duration_seconds =
+                (audio_info.file_size * 8) as f64 / audio_info.bit_rate as f64;
+            audio_info
+                .provenance
+                .insert("bit_rate".to_string(), FieldSource::Default);
+            audio_info
+                .provenance
+                .insert("duration_seconds".to_string(), FieldSource::Estimated);
+        } else {
+            // デフォルトの継続時間（5分）
+            audio_info.duration_seconds = 300.0;
+            audio_info
+                .provenance
+                .insert("duration_seconds".to_string(), FieldSource::Default);
+        }
+
+        audio_info
+    }
+}
+
+impl ProbeBackend for EstimateBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Estimate
+    }
+
+    fn probe<'a>(
+        &'a self,
+        request: &'a ProbeRequest<'a>,
+    ) -> BoxFuture<'a, Result<AudioInfo, AudioProbeError>> {
+        futures::future::ready(Ok(Self::estimate(request))).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use clap::Parser;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 決まった結果を返し、呼ばれた回数を数えるバックエンド
+    struct MockBackend {
+        kind: BackendKind,
+        result: fn(&Path) -> Result<AudioInfo, AudioProbeError>,
+        calls: AtomicUsize,
+    }
+
+    impl MockBackend {
+        fn new(
+            kind: BackendKind,
+            result: fn(&Path) -> Result<AudioInfo, AudioProbeError>,
+        ) -> Arc<Self> {
+            Arc::new(Self {
+                kind,
+                result,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl ProbeBackend for MockBackend {
+        fn kind(&self) -> BackendKind {
+            self.kind
+        }
+
+        fn probe<'a>(
+            &'a self,
+            request: &'a ProbeRequest<'a>,
+        ) -> BoxFuture<'a, Result<AudioInfo, AudioProbeError>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            futures::future::ready((self.result)(request.path)).boxed()
+        }
+    }
+
+    fn request(path: &Path) -> ProbeRequest<'_> {
+        ProbeRequest {
+            path,
+            container: None,
+            file_size: 40_000,
+            timeout: None,
+            resource_limits: ResourceLimits::default(),
+        }
+    }
+
+    fn invalid(path: &Path) -> Result<AudioInfo, AudioProbeError> {
+        Err(AudioProbeError::InvalidAudioFile {
+            path: path.to_path_buf(),
+            reason: "unknown container".to_string(),
+        })
+    }
+
+    fn ffprobe_failed(path: &Path) -> Result<AudioInfo, AudioProbeError> {
+        Err(AudioProbeError::FFprobeError {
+            path: path.to_path_buf(),
+            message: "Invalid data found when processing input".to_string(),
+            exit_code: Some(1),
+            stderr: None,
+        })
+    }
+
+    fn timeout(path: &Path) -> Result<AudioInfo, AudioProbeError> {
+        Err(AudioProbeError::Timeout {
+            path: path.to_path_buf(),
+            elapsed: Duration::from_secs(30),
+            backend: BackendKind::Ffprobe,
+        })
+    }
+
+    fn succeed(path: &Path) -> Result<AudioInfo, AudioProbeError> {
+        Ok(AudioInfo::new(path.to_path_buf()))
+    }
+
+    #[test]
+    fn test_chain_order_from_config_and_cli() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"backends": ["estimate", "native"]}"#).unwrap();
+        let config = Config::load(&path).unwrap();
+
+        let args = crate::Args::try_parse_from(["audio-probe", "a.mp3"]).unwrap();
+        assert_eq!(
+            BackendKind::resolve_chain(&args.backend, config.backends.as_deref()),
+            [BackendKind::Estimate, BackendKind::Native]
+        );
+        assert_eq!(
+            BackendKind::resolve_chain(&args.backend, None),
+            BackendKind::DEFAULT_CHAIN
+        );
+
+        // --backendは設定ファイルより優先する
+        let args =
+            crate::Args::try_parse_from(["audio-probe", "--backend", "ffprobe,native", "a.mp3"])
+                .unwrap();
+        let kinds = BackendKind::resolve_chain(&args.backend, config.backends.as_deref());
+        assert_eq!(kinds, [BackendKind::Ffprobe, BackendKind::Native]);
+
+        let ffprobe = FFprobeVersion {
+            path: PathBuf::from("ffprobe"),
+            ..Default::default()
+        };
+        assert_eq!(BackendChain::new(&kinds, Some(&ffprobe)).kinds(), kinds);
+    }
+
+    #[test]
+    fn test_ffprobe_skipped_when_unavailable() {
+        let chain = BackendChain::new(&BackendKind::DEFAULT_CHAIN, None);
+        assert_eq!(chain.kinds(), [BackendKind::Native, BackendKind::Estimate]);
+        assert!(BackendChain::new(&[BackendKind::Ffprobe], None)
+            .kinds()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_are_recorded() {
+        let native = MockBackend::new(BackendKind::Native, invalid);
+        let ffprobe = MockBackend::new(BackendKind::Ffprobe, ffprobe_failed);
+        let estimate = MockBackend::new(BackendKind::Estimate, succeed);
+        let chain = BackendChain {
+            backends: vec![native, ffprobe, estimate],
+        };
+
+        let info = chain.probe(&request(Path::new("a.xyz"))).await.unwrap();
+        assert_eq!(info.backend, Some(BackendKind::Estimate));
+        let attempts: Vec<_> = info
+            .failed_attempts
+            .iter()
+            .map(|attempt| (attempt.backend, attempt.kind.as_str()))
+            .collect();
+        assert_eq!(
+            attempts,
+            [
+                (BackendKind::Native, "invalid_audio_file"),
+                (BackendKind::Ffprobe, "ffprobe_failed")
+            ]
+        );
+
+        // 全て失敗した場合は最後のエラー、バックエンドがなければProcessing
+        let chain = BackendChain {
+            backends: vec![
+                MockBackend::new(BackendKind::Native, invalid),
+                MockBackend::new(BackendKind::Ffprobe, ffprobe_failed),
+            ],
+        };
+        let error = chain.probe(&request(Path::new("a.xyz"))).await.unwrap_err();
+        assert_eq!(error.kind(), "ffprobe_failed");
+        let error = BackendChain::default()
+            .probe(&request(Path::new("a.xyz")))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "processing");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_stops_the_chain() {
+        let ffprobe = MockBackend::new(BackendKind::Ffprobe, timeout);
+        let estimate = MockBackend::new(BackendKind::Estimate, succeed);
+        let chain = BackendChain {
+            backends: vec![ffprobe.clone(), estimate.clone()],
+        };
+
+        let error = chain
+            .probe(&request(Path::new("stuck.mp3")))
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), "timeout");
+        assert_eq!(ffprobe.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(estimate.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_native_timeout() {
+        use std::os::unix::ffi::OsStrExt;
+
+        // 書き込み側が開くまでopenが戻らないFIFOでパーサーを止める
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stuck.wav");
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        let request = ProbeRequest {
+            timeout: Some(Duration::from_millis(200)),
+            ..request(&path)
+        };
+        let error = NativeBackend.probe(&request).await.unwrap_err();
+        assert!(matches!(
+            error,
+            AudioProbeError::Timeout {
+                backend: BackendKind::Native,
+                ..
+            }
+        ));
+        assert_eq!(error.backend(), Some(BackendKind::Native));
+
+        // 残ったブロッキングスレッドを終わらせる
+        drop(std::fs::OpenOptions::new().write(true).open(&path).unwrap());
+    }
+
+    #[test]
+    fn test_estimate_marks_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("noise.mp3");
+        std::fs::write(&path, vec![0u8; 40_000]).unwrap();
+
+        let info = EstimateBackend::estimate(&request(&path));
+        assert!(info.estimated);
+        assert_eq!(info.duration_seconds, 1.0);
+        assert_eq!(info.provenance["format_name"], FieldSource::Filename);
+        assert_eq!(info.provenance["sample_rate"], FieldSource::Default);
+        assert_eq!(info.provenance["duration_seconds"], FieldSource::Estimated);
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["estimated"], true);
+        assert_eq!(json["provenance"]["bit_rate"], "default");
+
+        // 拡張子も判定結果もない場合は設定していないフィールドの出所を記録しない
+        let extensionless = dir.path().join("noise");
+        let info = EstimateBackend::estimate(&request(&extensionless));
+        assert!(info.format_name.is_empty());
+        assert!(!info.provenance.contains_key("format_name"));
+        assert!(!info.provenance.contains_key("codec_name"));
+        assert!(!info.provenance.contains_key("sample_rate"));
+        assert_eq!(info.provenance["duration_seconds"], FieldSource::Default);
+    }
+}
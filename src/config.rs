@@ -0,0 +1,93 @@
+// 設定ファイル（JSON）
+//
+// `--config`で指定したファイル、指定がなければ$XDG_CONFIG_HOME/audio-probe/config.json
+// （XDG_CONFIG_HOMEが未設定なら~/.config/audio-probe/config.json）を読む。
+// コマンドライン引数と環境変数の指定は設定ファイルより優先する。
+
+use crate::backend::BackendKind;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 使用するffprobe（相対パスは設定ファイルのディレクトリからの位置）
+    pub ffprobe_path: Option<PathBuf>,
+    /// 解析バックエンドを試す順序（例: ["native", "ffprobe"]）
+    pub backends: Option<Vec<BackendKind>>,
+}
+
+impl Config {
+    /// 指定がない場合に読む設定ファイルの場所
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("audio-probe").join("config.json"))
+    }
+
+    /// 設定ファイルを読む
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {:?}", path))?;
+        let mut config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid config file {:?}", path))?;
+
+        // "ffprobe"のようなコマンド名はPATHから探すため、そのままにする
+        if let (Some(ffprobe), Some(dir)) = (&config.ffprobe_path, path.parent()) {
+            if ffprobe.is_relative() && ffprobe.components().count() > 1 {
+                config.ffprobe_path = Some(dir.join(ffprobe));
+            }
+        }
+        Ok(config)
+    }
+
+    /// 指定された設定ファイル、なければデフォルトの場所にある設定ファイルを読む
+    ///
+    /// デフォルトの場所にファイルがない場合は空の設定とする
+    pub fn discover(explicit: Option<&Path>) -> Result<Self> {
+        match explicit {
+            Some(path) => Self::load(path),
+            None => match Self::default_path().filter(|path| path.is_file()) {
+                Some(path) => Self::load(&path),
+                None => Ok(Self::default()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        std::fs::write(&path, r#"{"ffprobe_path": "bin/ffprobe"}"#).unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.ffprobe_path, Some(dir.path().join("bin/ffprobe")));
+
+        std::fs::write(
+            &path,
+            r#"{"ffprobe_path": "ffprobe7", "backends": ["ffprobe", "estimate"]}"#,
+        )
+        .unwrap();
+        let config = Config::discover(Some(&path)).unwrap();
+        assert_eq!(config.ffprobe_path, Some(PathBuf::from("ffprobe7")));
+        assert_eq!(
+            config.backends,
+            Some(vec![BackendKind::Ffprobe, BackendKind::Estimate])
+        );
+
+        std::fs::write(&path, "{}").unwrap();
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        std::fs::write(&path, r#"{"ffprobe": "typo"}"#).unwrap();
+        assert!(Config::load(&path).is_err());
+        assert!(Config::discover(Some(&dir.path().join("missing.json"))).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+// ffprobeの検出とバージョン判定
+//
+// `ffprobe -version`の出力からバージョン・ビルド構成・有効なライブラリを読み取り、
+// 結果の再現性のためにJSONのsummaryへ出力する。古いffprobeが出力しないフィールドは
+// 0や空のまま返さず、警告で知らせる。
+
+use crate::process::{self, ResourceLimits};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// `ffprobe -version`の実行に許す時間
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ffprobeが出力するフィールドと、出力するようになった最初のlibavformatのバージョン
+///
+/// 値は`LAVF_RELEASES`にあるバージョンとし、1.0より前のリリースは区別しない
+const FIELD_REQUIREMENTS: [(&str, (u32, u32)); 13] = [
+    ("chapters", (54, 25)),
+    ("disposition.attached_pic", (54, 25)),
+    ("bits_per_sample", (54, 25)),
+    ("sample_fmt", (54, 25)),
+    ("profile", (54, 25)),
+    ("start_time", (54, 25)),
+    ("time_base", (54, 25)),
+    ("duration_ts", (54, 25)),
+    ("nb_frames", (54, 25)),
+    ("nb_streams", (54, 25)),
+    ("channel_layout", (55, 12)),
+    ("bits_per_raw_sample", (55, 33)),
+    ("probe_score", (55, 33)),
+];
+
+/// libavformatのバージョン → FFmpegのリリース（gitビルドのバージョン推定用）
+const LAVF_RELEASES: [((u32, u32), (u32, u32)); 18] = [
+    ((54, 25), (1, 0)),
+    ((55, 12), (2, 0)),
+    ((55, 33), (2, 2)),
+    ((56, 40), (2, 8)),
+    ((57, 25), (3, 0)),
+    ((57, 83), (3, 4)),
+    ((58, 12), (4, 0)),
+    ((58, 20), (4, 1)),
+    ((58, 29), (4, 2)),
+    ((58, 45), (4, 3)),
+    ((58, 76), (4, 4)),
+    ((59, 16), (5, 0)),
+    ((59, 27), (5, 1)),
+    ((60, 3), (6, 0)),
+    ((60, 16), (6, 1)),
+    ((61, 1), (7, 0)),
+    ((61, 7), (7, 1)),
+    ((62, 3), (8, 0)),
+];
+
+/// `ffprobe -version`の解析結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FFprobeVersion {
+    /// 実行したffprobeのパス
+    pub path: PathBuf,
+    /// "ffprobe version"に続く文字列（"6.1.1-3ubuntu5"、"N-113348-g0a5813fc68"など）
+    pub version: String,
+    /// リリース番号。gitビルドではlibavformatのバージョンから推定した下限
+    pub major: Option<u32>,
+    pub minor: Option<u32>,
+    /// configureのオプション
+    pub configuration: Vec<String>,
+    /// `--enable-lib*`で有効になっている外部ライブラリ（"libmp3lame"など）
+    pub enabled_libraries: Vec<String>,
+    /// FFmpegのライブラリと実行時のバージョン（"libavformat": "60.16.100"など）
+    pub libraries: BTreeMap<String, String>,
+}
+
+impl FFprobeVersion {
+    /// `major.minor`以降のリリースか（バージョンが不明な場合は`None`）
+    pub fn at_least(&self, major: u32, minor: u32) -> Option<bool> {
+        Some((self.major?, self.minor?) >= (major, minor))
+    }
+
+    /// 実行時のlibavformatのバージョン
+    fn lavf_version(&self) -> Option<(u32, u32)> {
+        let lavf = self.libraries.get("libavformat")?;
+        let mut parts = lavf.split('.').map(|part| part.parse::<u32>().ok());
+        Some((parts.next()??, parts.next()??))
+    }
+
+    /// このffprobeでは出力されないフィールドと必要なリリース
+    ///
+    /// libavformatのバージョンが分かればそれと比べ、なければリリース番号で比べる
+    pub fn unsupported_fields(&self) -> Vec<(&'static str, (u32, u32))> {
+        FIELD_REQUIREMENTS
+            .into_iter()
+            .filter_map(|(field, required)| {
+                let release = lavf_release(required)?;
+                let supported = match self.lavf_version() {
+                    Some(lavf) => lavf >= required,
+                    None => self.at_least(release.0, release.1)?,
+                };
+                (!supported).then_some((field, release))
+            })
+            .collect()
+    }
+
+    /// 表示用のバージョン（"6.1.1-3ubuntu5"、推定した場合は"N-113348-g0a5813fc68 (~6.1)"）
+    pub fn display_version(&self) -> String {
+        match (self.major, self.minor) {
+            (Some(major), Some(minor)) if release_number(&self.version).is_none() => {
+                format!("{} (~{}.{})", self.version, major, minor)
+            }
+            _ => self.version.clone(),
+        }
+    }
+}
+
+/// libavformatのバージョンに対応するリリース（そのバージョン以前で最も新しいもの）
+fn lavf_release(lavf: (u32, u32)) -> Option<(u32, u32)> {
+    LAVF_RELEASES
+        .iter()
+        .rev()
+        .find(|(required, _)| lavf >= *required)
+        .map(|(_, release)| *release)
+}
+
+/// "6.1.1-3ubuntu5"・"n7.0"・"4.4"から(major, minor)を読む
+fn release_number(version: &str) -> Option<(u32, u32)> {
+    let version = version.strip_prefix('n').unwrap_or(version);
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts
+        .next()
+        .and_then(|minor| minor.parse().ok())
+        .unwrap_or(0);
+    Some((major, minor))
+}
+
+/// 引用符を考慮してconfigurationの行を分割する（"--extra-cflags='-O2 -g'"など）
+fn split_configuration(line: &str) -> Vec<String> {
+    let mut options = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, c) if c.is_whitespace() => {
+                if !current.is_empty() {
+                    options.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        options.push(current);
+    }
+    options
+}
+
+/// `ffprobe -version`の出力を解析する（ffprobeの出力でない場合は`None`）
+pub fn parse_version(output: &str, path: &Path) -> Option<FFprobeVersion> {
+    let mut lines = output.lines();
+    let version = lines
+        .next()?
+        .strip_prefix("ffprobe version ")?
+        .split_whitespace()
+        .next()?
+        .to_string();
+
+    let mut info = FFprobeVersion {
+        path: path.to_path_buf(),
+        version,
+        ..Default::default()
+    };
+
+    for line in lines {
+        if let Some(configuration) = line.strip_prefix("configuration:") {
+            info.configuration = split_configuration(configuration);
+            info.enabled_libraries = info
+                .configuration
+                .iter()
+                .filter_map(|option| option.strip_prefix("--enable-"))
+                .filter(|name| name.starts_with("lib"))
+                .map(str::to_string)
+                .collect();
+        } else if let Some((name, versions)) = line
+            .split_once(char::is_whitespace)
+            .filter(|(name, _)| name.starts_with("lib"))
+        {
+            // "libavutil      58. 29.100 / 58. 29.100"（スラッシュの後が実行時のバージョン）
+            let runtime = versions.rsplit('/').next().unwrap_or(versions);
+            let runtime: String = runtime.chars().filter(|c| !c.is_whitespace()).collect();
+            if !runtime.is_empty() {
+                info.libraries.insert(name.to_string(), runtime);
+            }
+        }
+    }
+
+    let release = release_number(&info.version).or_else(|| lavf_release(info.lavf_version()?));
+    if let Some((major, minor)) = release {
+        info.major = Some(major);
+        info.minor = Some(minor);
+    }
+
+    Some(info)
+}
+
+/// `path`のffprobeを`-version`付きで実行し、利用できればバージョンを返す
+pub async fn detect(path: &Path) -> Option<FFprobeVersion> {
+    let mut command = Command::new(path);
+    command.arg("-version");
+    let output = process::output_with_timeout(
+        command,
+        path,
+        Some(VERSION_TIMEOUT),
+        ResourceLimits::default(),
+    )
+    .await
+    .ok()
+    .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // 出力の形式が想定と異なっても実行はできるため、バージョン不明として扱う
+    Some(
+        parse_version(&stdout, path).unwrap_or_else(|| FFprobeVersion {
+            path: path.to_path_buf(),
+            version: "unknown".to_string(),
+            ..Default::default()
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASE_OUTPUT: &str = "\
+ffprobe version 6.1.1-3ubuntu5 Copyright (c) 2007-2023 the FFmpeg developers
+built with gcc 13 (Ubuntu 13.2.0-23ubuntu3)
+configuration: --prefix=/usr --extra-version=3ubuntu5 --extra-cflags='-O2 -g' --enable-gpl --enable-libmp3lame --enable-libopus --disable-stripping
+libavutil      58. 29.100 / 58. 29.100
+libavcodec     60. 31.102 / 60. 31.102
+libavformat    60. 16.100 / 60. 16.100
+";
+
+    #[test]
+    fn test_parse_release_version() {
+        let info = parse_version(RELEASE_OUTPUT, Path::new("/usr/bin/ffprobe")).unwrap();
+        assert_eq!(info.version, "6.1.1-3ubuntu5");
+        assert_eq!((info.major, info.minor), (Some(6), Some(1)));
+        assert_eq!(info.configuration[2], "--extra-cflags=-O2 -g");
+        assert_eq!(info.enabled_libraries, ["libmp3lame", "libopus"]);
+        assert_eq!(info.libraries["libavformat"], "60.16.100");
+        assert_eq!(info.libraries.len(), 3);
+        assert_eq!(info.display_version(), "6.1.1-3ubuntu5");
+        assert!(info.unsupported_fields().is_empty());
+    }
+
+    #[test]
+    fn test_parse_git_build_and_old_release() {
+        let output = "ffprobe version N-113348-g0a5813fc68 Copyright (c) 2007-2024\n\
+                      libavformat    60. 20.100 / 60. 20.100\n";
+        let info = parse_version(output, Path::new("ffprobe")).unwrap();
+        assert_eq!((info.major, info.minor), (Some(6), Some(1)));
+        assert_eq!(info.display_version(), "N-113348-g0a5813fc68 (~6.1)");
+
+        let old = parse_version("ffprobe version 2.1.8\n", Path::new("ffprobe")).unwrap();
+        assert_eq!(old.at_least(2, 2), Some(false));
+        assert_eq!(
+            old.unsupported_fields(),
+            [("bits_per_raw_sample", (2, 2)), ("probe_score", (2, 2))]
+        );
+
+        // リリース番号よりlibavformatのバージョンを優先する（1.2はLAVF_RELEASESにない）
+        let output = "ffprobe version 1.2.12 Copyright (c) 2007-2015\n\
+                      libavformat    54. 63.104 / 54. 63.104\n";
+        let v1_2 = parse_version(output, Path::new("ffprobe")).unwrap();
+        assert_eq!((v1_2.major, v1_2.minor), (Some(1), Some(2)));
+        let fields: Vec<_> = v1_2.unsupported_fields().into_iter().map(|f| f.0).collect();
+        assert_eq!(
+            fields,
+            ["channel_layout", "bits_per_raw_sample", "probe_score"]
+        );
+
+        let unknown = parse_version("ffprobe version custom\n", Path::new("ffprobe")).unwrap();
+        assert_eq!(unknown.major, None);
+        assert!(unknown.unsupported_fields().is_empty());
+
+        assert_eq!(
+            parse_version("avprobe version 12\n", Path::new("avprobe")),
+            None
+        );
+    }
+}
@@ -0,0 +1,350 @@
+// AC-3 / E-AC-3 エレメンタリーストリームパーサー
+
+use super::{
+    channel_layout_from_mask, id3, scan_frames, stream_len, BitReader, FrameScan, ParseError,
+    ParseResult, Window,
+};
+use crate::AudioInfo;
+use std::io::{Read, Seek};
+
+/// 同期情報 + BSI先頭の解析に必要なバイト数
+const HEADER_LEN: usize = 8;
+
+/// 最初のフレームを探す範囲
+const MAX_SYNC_SEARCH: u64 = 64 * 1024;
+
+/// frmsizecod / 2 に対応するビットレート（kbps）
+const BIT_RATES: [u32; 19] = [
+    32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 448, 512, 576, 640,
+];
+
+/// acmodごとのフルバンドチャンネル数
+const ACMOD_CHANNELS: [u8; 8] = [2, 1, 2, 3, 3, 4, 4, 5];
+
+/// acmodごとのスピーカーマスク（FFmpegのAC-3デコーダーと同じ割り当て）
+const ACMOD_MASKS: [u32; 8] = [0x3, 0x4, 0x3, 0x7, 0x103, 0x107, 0x603, 0x607];
+
+/// acmodの表記（A/52 表5.8）
+const ACMOD_NAMES: [&str; 8] = ["1+1", "1/0", "2/0", "3/0", "2/1", "3/1", "2/2", "3/2"];
+
+/// AC-3 (bsid <= 10) と E-AC-3 (bsid 11〜16) 共通の同期フレームヘッダー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncFrame {
+    pub bsid: u8,
+    pub sample_rate: u32,
+    /// バイト単位のフレーム長
+    pub frame_size: u64,
+    pub samples: u64,
+    pub acmod: u8,
+    pub lfeon: bool,
+    /// 1〜31（-1〜-31 dBFS）
+    pub dialnorm: u8,
+    /// AC-3のみ
+    pub frmsizecod: Option<u8>,
+    pub bsmod: Option<u8>,
+    /// E-AC-3のみ: 0 独立, 1 従属, 2 AC-3互換
+    pub stream_type: Option<u8>,
+    pub substream_id: Option<u8>,
+}
+
+impl SyncFrame {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || bytes[0] != 0x0B || bytes[1] != 0x77 {
+            return None;
+        }
+        let bsid = bytes[5] >> 3;
+        match bsid {
+            0..=10 => Self::parse_ac3(bytes, bsid),
+            11..=16 => Self::parse_eac3(bytes, bsid),
+            _ => None,
+        }
+    }
+
+    fn parse_ac3(bytes: &[u8], bsid: u8) -> Option<Self> {
+        let fscod = bytes[4] >> 6;
+        let frmsizecod = bytes[4] & 0x3F;
+        let bit_rate = *BIT_RATES.get(frmsizecod as usize / 2)? as u64;
+        // フレーム長（16ビットワード単位）
+        let words = match fscod {
+            0 => bit_rate * 2,
+            1 => bit_rate * 320 / 147 + (frmsizecod & 1) as u64,
+            2 => bit_rate * 3,
+            _ => return None,
+        };
+        let sample_rate = [48000, 44100, 32000][fscod as usize];
+
+        let mut bits = BitReader::new(&bytes[5..]);
+        bits.skip(5)?;
+        let bsmod = bits.read(3)? as u8;
+        let acmod = bits.read(3)? as u8;
+        if acmod & 0x1 != 0 && acmod != 0x1 {
+            bits.skip(2)?; // cmixlev
+        }
+        if acmod & 0x4 != 0 {
+            bits.skip(2)?; // surmixlev
+        }
+        if acmod == 0x2 {
+            bits.skip(2)?; // dsurmod
+        }
+        let lfeon = bits.read(1)? == 1;
+        let dialnorm = bits.read(5)? as u8;
+
+        Some(Self {
+            bsid,
+            sample_rate,
+            frame_size: words * 2,
+            samples: 1536,
+            acmod,
+            lfeon,
+            dialnorm,
+            frmsizecod: Some(frmsizecod),
+            bsmod: Some(bsmod),
+            stream_type: None,
+            substream_id: None,
+        })
+    }
+
+    fn parse_eac3(bytes: &[u8], bsid: u8) -> Option<Self> {
+        let mut bits = BitReader::new(&bytes[2..]);
+        let stream_type = bits.read(2)? as u8;
+        let substream_id = bits.read(3)? as u8;
+        let frmsiz = bits.read(11)? as u64;
+        let fscod = bits.read(2)?;
+        let (sample_rate, blocks) = if fscod == 0x3 {
+            let fscod2 = bits.read(2)?;
+            let rate = *[24000, 22050, 16000].get(fscod2 as usize)?;
+            (rate, 6)
+        } else {
+            let numblkscod = bits.read(2)? as usize;
+            (
+                [48000, 44100, 32000][fscod as usize],
+                [1, 2, 3, 6][numblkscod],
+            )
+        };
+        let acmod = bits.read(3)? as u8;
+        let lfeon = bits.read(1)? == 1;
+        bits.skip(5)?; // bsid
+        let dialnorm = bits.read(5)? as u8;
+
+        // 従属サブストリームと追加の独立サブストリームは再生時間に寄与しない
+        let samples = if stream_type != 1 && substream_id == 0 {
+            256 * blocks
+        } else {
+            0
+        };
+
+        Some(Self {
+            bsid,
+            sample_rate,
+            frame_size: (frmsiz + 1) * 2,
+            samples,
+            acmod,
+            lfeon,
+            dialnorm,
+            frmsizecod: None,
+            bsmod: None,
+            stream_type: Some(stream_type),
+            substream_id: Some(substream_id),
+        })
+    }
+
+    pub fn is_eac3(&self) -> bool {
+        self.bsid > 10
+    }
+
+    pub fn channels(&self) -> u16 {
+        ACMOD_CHANNELS[self.acmod as usize] as u16 + self.lfeon as u16
+    }
+
+    pub fn channel_layout(&self) -> String {
+        let lfe = if self.lfeon { 0x8 } else { 0 };
+        channel_layout_from_mask(ACMOD_MASKS[self.acmod as usize] | lfe, self.channels())
+    }
+
+    /// 公称ビットレート（AC-3のみ）
+    pub fn nominal_bit_rate(&self) -> Option<i64> {
+        self.frmsizecod
+            .map(|code| BIT_RATES[code as usize / 2] as i64 * 1000)
+    }
+
+    /// dialnormのdB値（0は予約値で-31 dBとして扱う）
+    pub fn dialnorm_db(&self) -> i32 {
+        if self.dialnorm == 0 {
+            -31
+        } else {
+            -(self.dialnorm as i32)
+        }
+    }
+}
+
+/// AC-3/E-AC-3ストリーム全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ac3Stream {
+    pub first_frame: SyncFrame,
+    pub scan: FrameScan,
+}
+
+impl Ac3Stream {
+    pub fn duration_seconds(&self) -> f64 {
+        self.scan.sample_count as f64 / self.first_frame.sample_rate as f64
+    }
+
+    pub fn bit_rate(&self) -> i64 {
+        if let Some(bit_rate) = self.first_frame.nominal_bit_rate() {
+            return bit_rate;
+        }
+        let duration = self.duration_seconds();
+        if duration > 0.0 {
+            (self.scan.byte_count as f64 * 8.0 / duration).round() as i64
+        } else {
+            0
+        }
+    }
+}
+
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<Ac3Stream> {
+    let file_len = stream_len(reader)?;
+    let start = id3::skip_v2(reader, file_len)?;
+
+    let scan = scan_frames(
+        reader,
+        start,
+        file_len,
+        HEADER_LEN,
+        MAX_SYNC_SEARCH,
+        |bytes| SyncFrame::parse(bytes).map(|f| (f.frame_size, f.samples)),
+    )?
+    .ok_or_else(|| ParseError::Invalid("no AC-3 sync frame found".to_string()))?;
+
+    let first_frame = Window::new(reader)
+        .get(scan.first_offset, HEADER_LEN)?
+        .and_then(SyncFrame::parse)
+        .ok_or_else(|| ParseError::Invalid("no AC-3 sync frame found".to_string()))?;
+
+    Ok(Ac3Stream { first_frame, scan })
+}
+
+/// AC-3/E-AC-3ファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let stream = read(reader)?;
+    let frame = &stream.first_frame;
+
+    if frame.is_eac3() {
+        audio_info.format_name = "eac3".to_string();
+        audio_info.format_long_name = "raw E-AC-3".to_string();
+        audio_info.codec_name = "eac3".to_string();
+        audio_info.codec_long_name = "ATSC A/52B (AC-3, E-AC-3)".to_string();
+    } else {
+        audio_info.format_name = "ac3".to_string();
+        audio_info.format_long_name = "raw AC-3".to_string();
+        audio_info.codec_name = "ac3".to_string();
+        audio_info.codec_long_name = "ATSC A/52A (AC-3)".to_string();
+    }
+    audio_info.sample_rate = frame.sample_rate as i32;
+    audio_info.channels = frame.channels() as i32;
+    audio_info.channel_layout = frame.channel_layout();
+    audio_info.duration_seconds = stream.duration_seconds();
+    audio_info.bit_rate = stream.bit_rate();
+
+    let params = &mut audio_info.codec_params;
+    params.insert("bsid".to_string(), frame.bsid.to_string());
+    params.insert(
+        "acmod".to_string(),
+        format!("{} ({})", frame.acmod, ACMOD_NAMES[frame.acmod as usize]),
+    );
+    params.insert("lfeon".to_string(), (frame.lfeon as u8).to_string());
+    params.insert(
+        "dialnorm".to_string(),
+        format!("{} dB", frame.dialnorm_db()),
+    );
+    if let Some(frmsizecod) = frame.frmsizecod {
+        params.insert("frmsizecod".to_string(), frmsizecod.to_string());
+    }
+    if let Some(bsmod) = frame.bsmod {
+        params.insert("bsmod".to_string(), bsmod.to_string());
+    }
+    params.insert("frames".to_string(), stream.scan.frame_count.to_string());
+
+    audio_info.metadata = id3::read_metadata(reader)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// 48kHz・448kbps・3/2+LFE・dialnorm -27 のAC-3フレーム
+    fn ac3_frame() -> Vec<u8> {
+        // frmsizecod 30 = 448kbps, 48kHzで1792バイト
+        let mut frame = vec![0x0B, 0x77, 0x00, 0x00, 30];
+        // bsid=8, bsmod=0 | acmod=7, cmixlev, surmixlev, lfeon=1, dialnorm=27
+        frame.push(8 << 3);
+        frame.extend_from_slice(&[0b1110_0000 | 0b0000_0001, 0b1101_1000]);
+        frame.resize(1792, 0);
+        frame
+    }
+
+    /// 48kHz・6ブロック・2/0 のE-AC-3独立フレーム（frmsiz 383 = 768バイト）
+    fn eac3_frame(stream_type: u8, substream_id: u8) -> Vec<u8> {
+        let frmsiz: u16 = 383;
+        let mut frame = vec![
+            0x0B,
+            0x77,
+            (stream_type << 6) | (substream_id << 3) | (frmsiz >> 8) as u8,
+            frmsiz as u8,
+        ];
+        // fscod=0, numblkscod=3, acmod=2, lfeon=0 | bsid=16, dialnorm=31
+        frame.push(0b0011_0100);
+        frame.extend_from_slice(&[(16 << 3) | 0b111, 0b1100_0000, 0]);
+        frame.resize(768, 0);
+        frame
+    }
+
+    #[test]
+    fn test_ac3_sync_frame() {
+        let frame = SyncFrame::parse(&ac3_frame()).unwrap();
+        assert_eq!(frame.bsid, 8);
+        assert_eq!(frame.sample_rate, 48000);
+        assert_eq!(frame.frame_size, 1792);
+        assert_eq!(frame.acmod, 7);
+        assert!(frame.lfeon);
+        assert_eq!(frame.dialnorm_db(), -27);
+        assert_eq!(frame.channels(), 6);
+        assert_eq!(frame.channel_layout(), "5.1(side)");
+    }
+
+    #[test]
+    fn test_ac3_stream() {
+        let bytes: Vec<u8> = (0..125).flat_map(|_| ac3_frame()).collect();
+
+        let mut info = AudioInfo::new("program.ac3".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "ac3");
+        assert_eq!(info.bit_rate, 448_000);
+        assert_eq!(info.duration_seconds, 4.0);
+        assert_eq!(info.codec_params["dialnorm"], "-27 dB");
+        assert_eq!(info.codec_params["acmod"], "7 (3/2)");
+        assert_eq!(info.codec_params["lfeon"], "1");
+    }
+
+    #[test]
+    fn test_eac3_stream_ignores_dependent_substreams() {
+        let mut bytes = Vec::new();
+        for _ in 0..250 {
+            bytes.extend(eac3_frame(0, 0));
+            bytes.extend(eac3_frame(1, 0));
+        }
+
+        let mut info = AudioInfo::new("program.ec3".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "eac3");
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.duration_seconds, 8.0);
+        assert_eq!(info.bit_rate, 384_000);
+        assert_eq!(info.codec_params["bsid"], "16");
+        assert_eq!(info.codec_params["dialnorm"], "-31 dB");
+    }
+}
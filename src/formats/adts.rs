@@ -0,0 +1,187 @@
+// ADTS (raw AAC) エレメンタリーストリームパーサー
+
+use super::{
+    id3,
+    mp4::{aac_channel_count, aac_channel_layout, aac_profile_name, AAC_SAMPLE_RATES},
+    scan_frames, stream_len, FrameScan, ParseError, ParseResult,
+};
+use crate::AudioInfo;
+use std::io::{Read, Seek};
+
+/// ADTSヘッダー長（CRCなし）
+const HEADER_LEN: usize = 7;
+
+/// 最初のフレームを探す範囲
+const MAX_SYNC_SEARCH: u64 = 64 * 1024;
+
+/// ADTS固定/可変ヘッダー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdtsHeader {
+    /// true: MPEG-2, false: MPEG-4
+    pub mpeg2: bool,
+    pub protection_absent: bool,
+    /// audioObjectType（profile + 1）
+    pub object_type: u8,
+    pub sample_rate: u32,
+    pub channel_config: u8,
+    pub frame_length: u16,
+    pub raw_data_blocks: u8,
+}
+
+impl AdtsHeader {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || bytes[0] != 0xFF || bytes[1] & 0xF6 != 0xF0 {
+            return None;
+        }
+        let sample_rate = *AAC_SAMPLE_RATES.get(((bytes[2] >> 2) & 0xF) as usize)?;
+        let frame_length =
+            ((bytes[3] as u16 & 0x3) << 11) | ((bytes[4] as u16) << 3) | (bytes[5] as u16 >> 5);
+        if (frame_length as usize) < HEADER_LEN {
+            return None;
+        }
+        Some(Self {
+            mpeg2: bytes[1] & 0x08 != 0,
+            protection_absent: bytes[1] & 0x01 != 0,
+            object_type: (bytes[2] >> 6) + 1,
+            sample_rate,
+            channel_config: ((bytes[2] & 0x1) << 2) | (bytes[3] >> 6),
+            frame_length,
+            raw_data_blocks: bytes[6] & 0x3,
+        })
+    }
+
+    /// 1フレームあたりのサンプル数（raw_data_blockごとに1024）
+    pub fn samples_per_frame(&self) -> u64 {
+        1024 * (self.raw_data_blocks as u64 + 1)
+    }
+}
+
+/// ADTSストリーム全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdtsStream {
+    pub first_header: AdtsHeader,
+    pub scan: FrameScan,
+}
+
+impl AdtsStream {
+    pub fn duration_seconds(&self) -> f64 {
+        self.scan.sample_count as f64 / self.first_header.sample_rate as f64
+    }
+
+    pub fn average_bit_rate(&self) -> i64 {
+        let duration = self.duration_seconds();
+        if duration > 0.0 {
+            (self.scan.byte_count as f64 * 8.0 / duration).round() as i64
+        } else {
+            0
+        }
+    }
+}
+
+/// 先頭のID3v2タグを読み飛ばし、ADTSフレームを数える
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<AdtsStream> {
+    let file_len = stream_len(reader)?;
+    let start = id3::skip_v2(reader, file_len)?;
+
+    let scan = scan_frames(
+        reader,
+        start,
+        file_len,
+        HEADER_LEN,
+        MAX_SYNC_SEARCH,
+        |bytes| AdtsHeader::parse(bytes).map(|h| (h.frame_length as u64, h.samples_per_frame())),
+    )?
+    .ok_or_else(|| ParseError::Invalid("no ADTS sync frame found".to_string()))?;
+
+    let first_header = super::Window::new(reader)
+        .get(scan.first_offset, HEADER_LEN)?
+        .and_then(AdtsHeader::parse)
+        .ok_or_else(|| ParseError::Invalid("no ADTS sync frame found".to_string()))?;
+
+    Ok(AdtsStream { first_header, scan })
+}
+
+/// ADTSファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let adts = read(reader)?;
+    let header = &adts.first_header;
+
+    audio_info.format_name = "aac".to_string();
+    audio_info.format_long_name = "raw ADTS AAC (Advanced Audio Coding)".to_string();
+    audio_info.codec_name = "aac".to_string();
+    audio_info.codec_long_name = "AAC (Advanced Audio Coding)".to_string();
+    audio_info.sample_rate = header.sample_rate as i32;
+    audio_info.channels = aac_channel_count(header.channel_config) as i32;
+    audio_info.channel_layout = aac_channel_layout(header.channel_config);
+    audio_info.duration_seconds = adts.duration_seconds();
+    audio_info.bit_rate = adts.average_bit_rate();
+
+    let profile = aac_profile_name(header.object_type);
+    if !profile.is_empty() {
+        audio_info
+            .codec_params
+            .insert("profile".to_string(), profile.to_string());
+    }
+    audio_info.codec_params.insert(
+        "mpeg_version".to_string(),
+        if header.mpeg2 { "MPEG-2" } else { "MPEG-4" }.to_string(),
+    );
+    audio_info
+        .codec_params
+        .insert("frames".to_string(), adts.scan.frame_count.to_string());
+
+    audio_info.metadata = id3::read_metadata(reader)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) fn build_frame(object_type: u8, sr_index: u8, channel_config: u8, len: u16) -> Vec<u8> {
+    let mut frame = vec![
+        0xFF,
+        0xF1,
+        ((object_type - 1) << 6) | (sr_index << 2) | (channel_config >> 2),
+        ((channel_config & 0x3) << 6) | ((len >> 11) as u8 & 0x3),
+        (len >> 3) as u8,
+        ((len & 0x7) as u8) << 5 | 0x1F,
+        0xFC,
+    ];
+    frame.resize(len as usize, 0);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_adts_header() {
+        let header = AdtsHeader::parse(&build_frame(2, 4, 2, 371)).unwrap();
+        assert!(!header.mpeg2);
+        assert_eq!(header.object_type, 2);
+        assert_eq!(header.sample_rate, 44100);
+        assert_eq!(header.channel_config, 2);
+        assert_eq!(header.frame_length, 371);
+        assert_eq!(header.samples_per_frame(), 1024);
+    }
+
+    #[test]
+    fn test_adts_stream_with_leading_garbage() {
+        let mut bytes = vec![0x00, 0xFF, 0x12];
+        for _ in 0..375 {
+            bytes.extend(build_frame(2, 3, 6, 512));
+        }
+
+        let mut info = AudioInfo::new("surround.aac".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "aac");
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.channel_layout, "5.1");
+        assert_eq!(info.duration_seconds, 8.0);
+        assert_eq!(info.bit_rate, 192_000);
+        assert_eq!(info.codec_params["profile"], "LC");
+        assert_eq!(info.codec_params["frames"], "375");
+    }
+}
@@ -0,0 +1,459 @@
+// AIFF / AIFF-C パーサー
+
+use super::{
+    default_channel_layout, id3, merge_metadata, read_array, read_vec, stream_len, ParseError,
+    ParseResult,
+};
+use crate::AudioInfo;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+/// テキストチャンク（NAME/AUTH/ANNO/(c) ）の最大サイズ
+const MAX_TEXT_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// `COMM`チャンクの最大サイズ（AIFCの圧縮名を含めても通常は数十バイト）
+const MAX_COMM_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// IEEE 754 拡張精度（80ビット、ビッグエンディアン）を`f64`に変換する
+pub(crate) fn parse_extended(bytes: &[u8; 10]) -> f64 {
+    let sign = if bytes[0] & 0x80 != 0 { -1.0 } else { 1.0 };
+    let exponent = (u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7FFF) as i32;
+    let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+    if exponent == 0 && mantissa == 0 {
+        return 0.0;
+    }
+    if exponent == 0x7FFF {
+        return f64::NAN;
+    }
+    // 仮数部は明示的な整数ビットを含む 1.63 形式
+    sign * mantissa as f64 * 2f64.powi(exponent - 16383 - 63)
+}
+
+/// `COMM`チャンクの内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommonChunk {
+    pub channels: u16,
+    pub sample_frames: u32,
+    pub bits_per_sample: u16,
+    pub sample_rate: f64,
+    /// AIFFでは`NONE`、AIFCでは圧縮タイプ（`sowt`, `fl32`, `ulaw`など）
+    pub compression_type: [u8; 4],
+    pub compression_name: String,
+}
+
+impl CommonChunk {
+    fn parse(data: &[u8], is_aifc: bool) -> ParseResult<Self> {
+        if data.len() < 18 {
+            return Err(ParseError::Invalid(format!(
+                "COMM chunk too short ({} bytes)",
+                data.len()
+            )));
+        }
+
+        let mut comm = CommonChunk {
+            channels: u16::from_be_bytes([data[0], data[1]]),
+            sample_frames: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+            bits_per_sample: u16::from_be_bytes([data[6], data[7]]),
+            sample_rate: parse_extended(data[8..18].try_into().unwrap()),
+            compression_type: *b"NONE",
+            compression_name: String::new(),
+        };
+
+        if is_aifc && data.len() >= 22 {
+            comm.compression_type = data[18..22].try_into().unwrap();
+            // 圧縮名はPascal文字列（長さ1バイト + 本体）
+            if let Some(&len) = data.get(22) {
+                if let Some(name) = data.get(23..23 + len as usize) {
+                    comm.compression_name = String::from_utf8_lossy(name).into_owned();
+                }
+            }
+        }
+
+        if comm.channels == 0 {
+            return Err(ParseError::Invalid(
+                "COMM chunk has zero channels".to_string(),
+            ));
+        }
+        if !(comm.sample_rate.is_finite() && comm.sample_rate > 0.0) {
+            return Err(ParseError::Invalid(format!(
+                "COMM chunk has invalid sample rate {}",
+                comm.sample_rate
+            )));
+        }
+
+        Ok(comm)
+    }
+
+    /// 非圧縮（PCM/浮動小数点）かどうか
+    fn is_uncompressed(&self) -> bool {
+        matches!(
+            &self.compression_type,
+            b"NONE"
+                | b"twos"
+                | b"sowt"
+                | b"raw "
+                | b"in24"
+                | b"in32"
+                | b"fl32"
+                | b"FL32"
+                | b"fl64"
+                | b"FL64"
+        )
+    }
+
+    /// 1サンプルフレームあたりのサンプル数（IMA4は1パケット64サンプル、MACEは6サンプル）
+    fn samples_per_frame(&self) -> u32 {
+        match &self.compression_type {
+            b"ima4" => 64,
+            b"MAC3" | b"MAC6" => 6,
+            _ => 1,
+        }
+    }
+
+    /// FFmpeg互換のコーデック名と正式名称
+    pub fn codec_names(&self) -> (String, String) {
+        let bits = self.bits_per_sample;
+        let (name, long_name) = match (&self.compression_type, bits) {
+            (b"NONE" | b"twos", 1..=8) => ("pcm_s8", "PCM signed 8-bit"),
+            (b"NONE" | b"twos", 9..=16) => ("pcm_s16be", "PCM signed 16-bit big-endian"),
+            (b"NONE" | b"twos", 17..=24) | (b"in24", _) => {
+                ("pcm_s24be", "PCM signed 24-bit big-endian")
+            }
+            (b"NONE" | b"twos", 25..=32) | (b"in32", _) => {
+                ("pcm_s32be", "PCM signed 32-bit big-endian")
+            }
+            (b"sowt", 1..=8) => ("pcm_s8", "PCM signed 8-bit"),
+            (b"sowt", 9..=16) => ("pcm_s16le", "PCM signed 16-bit little-endian"),
+            (b"sowt", 17..=24) => ("pcm_s24le", "PCM signed 24-bit little-endian"),
+            (b"sowt", 25..=32) => ("pcm_s32le", "PCM signed 32-bit little-endian"),
+            (b"raw ", _) => ("pcm_u8", "PCM unsigned 8-bit"),
+            (b"fl32" | b"FL32", _) => ("pcm_f32be", "PCM 32-bit floating point big-endian"),
+            (b"fl64" | b"FL64", _) => ("pcm_f64be", "PCM 64-bit floating point big-endian"),
+            (b"ulaw" | b"ULAW", _) => ("pcm_mulaw", "PCM mu-law / G.711 mu-law"),
+            (b"alaw" | b"ALAW", _) => ("pcm_alaw", "PCM A-law / G.711 A-law"),
+            (b"ima4", _) => ("adpcm_ima_qt", "ADPCM IMA QuickTime"),
+            (b"MAC3", _) => ("mace3", "MACE (Macintosh Audio Compression/Expansion) 3:1"),
+            (b"MAC6", _) => ("mace6", "MACE (Macintosh Audio Compression/Expansion) 6:1"),
+            (b"GSM ", _) => ("gsm", "GSM"),
+            (b"QDM2", _) => ("qdm2", "QDesign Music Codec 2"),
+            (b"QDMC", _) => ("qdmc", "QDesign Music Codec 1"),
+            (b"Qclp", _) => ("qcelp", "QCELP / PureVoice"),
+            (tag, _) => {
+                let fourcc = String::from_utf8_lossy(tag).trim().to_string();
+                let long_name = if self.compression_name.is_empty() {
+                    format!("AIFF-C compression '{}'", fourcc)
+                } else {
+                    self.compression_name.clone()
+                };
+                return (fourcc, long_name);
+            }
+        };
+        (name.to_string(), long_name.to_string())
+    }
+
+    /// 実際に格納されているサンプルのビット数
+    pub fn stored_bits_per_sample(&self) -> u16 {
+        match &self.compression_type {
+            b"fl32" | b"FL32" => 32,
+            b"fl64" | b"FL64" => 64,
+            b"ulaw" | b"ULAW" | b"alaw" | b"ALAW" | b"raw " => 8,
+            b"in24" => 24,
+            b"in32" => 32,
+            b"NONE" | b"twos" | b"sowt" => self.bits_per_sample.div_ceil(8) * 8,
+            _ => 0,
+        }
+    }
+}
+
+/// AIFF/AIFCファイル全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiffFile {
+    pub is_aifc: bool,
+    pub common: CommonChunk,
+    pub sound_offset: u64,
+    pub sound_size: u64,
+    /// NAME/AUTH/ANNO/(c) チャンクと埋め込みID3タグから得たメタデータ
+    pub metadata: HashMap<String, String>,
+}
+
+impl AiffFile {
+    pub fn duration_seconds(&self) -> f64 {
+        let samples = self.common.sample_frames as u64 * self.common.samples_per_frame() as u64;
+        samples as f64 / self.common.sample_rate
+    }
+
+    pub fn bit_rate(&self) -> i64 {
+        let common = &self.common;
+        if common.is_uncompressed() {
+            (common.sample_rate * common.channels as f64 * common.stored_bits_per_sample() as f64)
+                .round() as i64
+        } else {
+            let duration = self.duration_seconds();
+            if duration > 0.0 {
+                (self.sound_size as f64 * 8.0 / duration).round() as i64
+            } else {
+                0
+            }
+        }
+    }
+}
+
+/// テキストチャンクのIDをffprobe互換のキーに変換する
+fn text_chunk_key(chunk_id: &[u8; 4]) -> Option<&'static str> {
+    match chunk_id {
+        b"NAME" => Some("title"),
+        // FFmpegは"author"として出力するが、他フォーマットと揃えて"artist"にする
+        b"AUTH" => Some("artist"),
+        b"ANNO" => Some("comment"),
+        b"(c) " => Some("copyright"),
+        _ => None,
+    }
+}
+
+/// FORM/AIFF・FORM/AIFCのチャンクを走査する
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<AiffFile> {
+    let file_len = stream_len(reader)?;
+
+    let form_header: [u8; 12] = read_array(reader)?;
+    let is_aifc = match (&form_header[0..4], &form_header[8..12]) {
+        (b"FORM", b"AIFF") => false,
+        (b"FORM", b"AIFC") => true,
+        _ => return Err(ParseError::Invalid("not an AIFF/AIFC file".to_string())),
+    };
+
+    let mut common = None;
+    let mut sound = None;
+    let mut text_entries = Vec::new();
+    let mut id3_entries = Vec::new();
+
+    let mut chunk_start = 12;
+    while chunk_start + 8 <= file_len {
+        reader.seek(SeekFrom::Start(chunk_start))?;
+        let header: [u8; 8] = match read_array(reader) {
+            Ok(header) => header,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        let chunk_id: [u8; 4] = header[0..4].try_into().unwrap();
+        let chunk_size = u32::from_be_bytes(header[4..8].try_into().unwrap()) as u64;
+        let body_start = chunk_start + 8;
+        // 実ファイルを超えるサイズは残りに丸める
+        let available = file_len - body_start;
+
+        match &chunk_id {
+            b"COMM" => {
+                let body = read_vec(reader, chunk_size.min(available), MAX_COMM_CHUNK_SIZE)?;
+                common = Some(CommonChunk::parse(&body, is_aifc)?);
+            }
+            b"SSND" if chunk_size >= 8 => {
+                // offset(4) + blockSize(4) の後に音声データが続く
+                let data_offset = u32::from_be_bytes(read_array(reader)?) as u64;
+                let data_start = body_start + 8 + data_offset;
+                let data_end = body_start + chunk_size.min(available);
+                sound = Some((data_start, data_end.saturating_sub(data_start)));
+            }
+            b"ID3 " | b"id3 " => {
+                if let Some(tag) = id3::read_v2_at(reader, body_start)? {
+                    id3_entries.extend(tag.entries);
+                }
+            }
+            id => {
+                if let Some(key) = text_chunk_key(id) {
+                    let body = read_vec(reader, chunk_size.min(available), MAX_TEXT_CHUNK_SIZE)?;
+                    let end = body.iter().position(|b| *b == 0).unwrap_or(body.len());
+                    let value = String::from_utf8_lossy(&body[..end]).trim().to_string();
+                    if !value.is_empty() {
+                        text_entries.push((key.to_string(), value));
+                    }
+                }
+            }
+        }
+
+        // チャンクは偶数バイト境界にパディングされる
+        chunk_start = body_start + chunk_size + (chunk_size & 1);
+    }
+
+    let common = common.ok_or_else(|| ParseError::Invalid("missing COMM chunk".to_string()))?;
+    let (sound_offset, sound_size) = sound.unwrap_or((0, 0));
+
+    // 埋め込みID3タグを優先し、テキストチャンクは欠けているキーだけを補う
+    let mut metadata = HashMap::new();
+    merge_metadata(&mut metadata, id3_entries);
+    let mut text_metadata = HashMap::new();
+    merge_metadata(&mut text_metadata, text_entries);
+    for (key, value) in text_metadata {
+        metadata.entry(key).or_insert(value);
+    }
+
+    Ok(AiffFile {
+        is_aifc,
+        common,
+        sound_offset,
+        sound_size,
+        metadata,
+    })
+}
+
+/// AIFF/AIFCファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let aiff = read(reader)?;
+    let (codec_name, codec_long_name) = aiff.common.codec_names();
+
+    audio_info.format_name = "aiff".to_string();
+    audio_info.format_long_name = "Audio IFF".to_string();
+    audio_info.codec_name = codec_name;
+    audio_info.codec_long_name = codec_long_name;
+    audio_info.sample_rate = aiff.common.sample_rate.round() as i32;
+    audio_info.channels = aiff.common.channels as i32;
+    audio_info.bits_per_sample = aiff.common.stored_bits_per_sample() as i32;
+    audio_info.channel_layout = default_channel_layout(aiff.common.channels);
+    audio_info.bit_rate = aiff.bit_rate();
+    audio_info.duration_seconds = aiff.duration_seconds();
+    merge_metadata(&mut audio_info.metadata, aiff.metadata);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(id);
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    fn form(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"FORM");
+        out.extend_from_slice(&(body.len() as u32 + 4).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn comm(channels: u16, frames: u32, bits: u16, rate: [u8; 10], compression: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&channels.to_be_bytes());
+        body.extend_from_slice(&frames.to_be_bytes());
+        body.extend_from_slice(&bits.to_be_bytes());
+        body.extend_from_slice(&rate);
+        body.extend_from_slice(compression);
+        chunk(b"COMM", &body)
+    }
+
+    fn ssnd(data_len: usize) -> Vec<u8> {
+        let mut body = vec![0u8; 8];
+        body.resize(8 + data_len, 0);
+        chunk(b"SSND", &body)
+    }
+
+    const RATE_44100: [u8; 10] = [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0];
+    const RATE_48000: [u8; 10] = [0x40, 0x0E, 0xBB, 0x80, 0, 0, 0, 0, 0, 0];
+
+    #[test]
+    fn test_extended_sample_rates() {
+        assert_eq!(parse_extended(&RATE_44100), 44100.0);
+        assert_eq!(parse_extended(&RATE_48000), 48000.0);
+        assert_eq!(
+            parse_extended(&[0x40, 0x0C, 0xFA, 0, 0, 0, 0, 0, 0, 0]),
+            16000.0
+        );
+        assert_eq!(parse_extended(&[0; 10]), 0.0);
+    }
+
+    #[test]
+    fn test_aiff_pcm_with_text_chunks() {
+        let bytes = form(
+            b"AIFF",
+            &[
+                comm(2, 44100, 16, RATE_44100, &[]),
+                chunk(b"NAME", b"Test Tone"),
+                chunk(b"AUTH", b"Someone"),
+                chunk(b"ANNO", b"first"),
+                chunk(b"ANNO", b"second"),
+                chunk(b"(c) ", b"2024 Example"),
+                ssnd(44100 * 4),
+            ],
+        );
+
+        let mut info = AudioInfo::new("test.aiff".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "pcm_s16be");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, 16);
+        assert_eq!(info.duration_seconds, 1.0);
+        assert_eq!(info.bit_rate, 1_411_200);
+        assert_eq!(info.metadata["title"], "Test Tone");
+        assert_eq!(info.metadata["artist"], "Someone");
+        assert_eq!(info.metadata["comment"], "first;second");
+        assert_eq!(info.metadata["copyright"], "2024 Example");
+    }
+
+    #[test]
+    fn test_aifc_compression_types() {
+        for (compression, codec, bits) in [
+            (b"sowt", "pcm_s16le", 16),
+            (b"fl32", "pcm_f32be", 32),
+            (b"ulaw", "pcm_mulaw", 8),
+        ] {
+            let mut comm_tail = compression.to_vec();
+            comm_tail.extend_from_slice(b"\x00\x00");
+            let bytes = form(
+                b"AIFC",
+                &[
+                    chunk(b"FVER", &0xA2805140u32.to_be_bytes()),
+                    comm(1, 24000, 16, RATE_48000, &comm_tail),
+                    ssnd(24000 * bits as usize / 8),
+                ],
+            );
+
+            let aiff = read(&mut Cursor::new(bytes)).unwrap();
+            assert!(aiff.is_aifc);
+            assert_eq!(aiff.common.codec_names().0, codec);
+            assert_eq!(aiff.common.stored_bits_per_sample(), bits);
+            assert_eq!(aiff.duration_seconds(), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_embedded_id3_takes_precedence() {
+        let mut frame = b"TIT2".to_vec();
+        frame.extend_from_slice(&6u32.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0]);
+        frame.extend_from_slice(b"Title");
+        let mut tag = vec![b'I', b'D', b'3', 3, 0, 0, 0, 0, 0, frame.len() as u8];
+        tag.extend_from_slice(&frame);
+
+        let bytes = form(
+            b"AIFF",
+            &[
+                comm(1, 8000, 8, RATE_44100, &[]),
+                chunk(b"NAME", b"Chunk Title"),
+                chunk(b"AUTH", b"Chunk Author"),
+                ssnd(8000),
+                chunk(b"ID3 ", &tag),
+            ],
+        );
+
+        let aiff = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(aiff.metadata["title"], "Title");
+        assert_eq!(aiff.metadata["artist"], "Chunk Author");
+        assert_eq!(aiff.common.codec_names().0, "pcm_s8");
+    }
+
+    #[test]
+    fn test_not_an_aiff_file() {
+        let result = read(&mut Cursor::new(b"Test WAV file content\n".to_vec()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,242 @@
+// Monkey's Audio (APE) ヘッダーパーサー
+//
+// バージョン3.98以降はAPE_DESCRIPTOR + APE_HEADER、それ以前は旧形式の単一ヘッダー。
+
+use super::{apetag, default_channel_layout, id3, read_array, stream_len, ParseError, ParseResult};
+use crate::AudioInfo;
+use std::io::{Read, Seek, SeekFrom};
+
+/// 旧形式ヘッダーのフラグ
+const FLAG_8_BIT: u16 = 0x1;
+const FLAG_24_BIT: u16 = 0x8;
+
+/// Monkey's Audioのストリーム情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApeHeader {
+    /// 3990 = 3.99
+    pub version: u16,
+    pub compression_level: u16,
+    pub blocks_per_frame: u32,
+    pub final_frame_blocks: u32,
+    pub total_frames: u32,
+    pub bits_per_sample: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+impl ApeHeader {
+    /// "MAC "の直後から読む
+    fn read<R: Read>(reader: &mut R) -> ParseResult<Self> {
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version >= 3980 {
+            Self::read_descriptor(reader, version)
+        } else {
+            Self::read_legacy(reader, version)
+        }
+    }
+
+    fn read_descriptor<R: Read>(reader: &mut R, version: u16) -> ParseResult<Self> {
+        let descriptor: [u8; 46] = read_array(reader)?;
+        let descriptor_bytes = u32::from_le_bytes(descriptor[2..6].try_into().unwrap());
+        // 将来の拡張に備えてdescriptor_bytesまで読み飛ばす
+        let extra = (descriptor_bytes as u64).saturating_sub(52);
+        std::io::copy(&mut reader.by_ref().take(extra), &mut std::io::sink())?;
+
+        let header: [u8; 24] = read_array(reader)?;
+        let u16_at = |i: usize| u16::from_le_bytes([header[i], header[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+        Ok(Self {
+            version,
+            compression_level: u16_at(0),
+            blocks_per_frame: u32_at(4),
+            final_frame_blocks: u32_at(8),
+            total_frames: u32_at(12),
+            bits_per_sample: u16_at(16),
+            channels: u16_at(18),
+            sample_rate: u32_at(20),
+        })
+    }
+
+    fn read_legacy<R: Read>(reader: &mut R, version: u16) -> ParseResult<Self> {
+        let header: [u8; 26] = read_array(reader)?;
+        let u16_at = |i: usize| u16::from_le_bytes([header[i], header[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+        let compression_level = u16_at(0);
+        let flags = u16_at(2);
+
+        let blocks_per_frame = if version >= 3950 {
+            73728 * 4
+        } else if version >= 3900 || (version >= 3800 && compression_level == 4000) {
+            73728
+        } else {
+            9216
+        };
+        let bits_per_sample = if flags & FLAG_8_BIT != 0 {
+            8
+        } else if flags & FLAG_24_BIT != 0 {
+            24
+        } else {
+            16
+        };
+
+        Ok(Self {
+            version,
+            compression_level,
+            blocks_per_frame,
+            final_frame_blocks: u32_at(22),
+            total_frames: u32_at(18),
+            bits_per_sample,
+            channels: u16_at(4),
+            sample_rate: u32_at(6),
+        })
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        match self.total_frames {
+            0 => 0,
+            frames => {
+                (frames as u64 - 1) * self.blocks_per_frame as u64 + self.final_frame_blocks as u64
+            }
+        }
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        if self.sample_rate == 0 {
+            0.0
+        } else {
+            self.total_samples() as f64 / self.sample_rate as f64
+        }
+    }
+
+    /// 圧縮レベルの名称（Monkey's Audioの表記）
+    pub fn compression_name(&self) -> &'static str {
+        match self.compression_level {
+            1000 => "fast",
+            2000 => "normal",
+            3000 => "high",
+            4000 => "extra high",
+            5000 => "insane",
+            _ => "unknown",
+        }
+    }
+}
+
+/// 先頭のID3v2タグ（規格外だが存在する場合がある）を読み飛ばしてヘッダーを読む
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<ApeHeader> {
+    let file_len = stream_len(reader)?;
+    let start = id3::skip_v2(reader, file_len)?;
+    reader.seek(SeekFrom::Start(start))?;
+
+    let magic: [u8; 4] = read_array(reader)?;
+    if &magic != b"MAC " {
+        return Err(ParseError::Invalid("not a Monkey's Audio file".to_string()));
+    }
+    let header = ApeHeader::read(reader)?;
+    if header.channels == 0 || header.sample_rate == 0 || header.blocks_per_frame == 0 {
+        return Err(ParseError::Invalid(
+            "invalid Monkey's Audio header".to_string(),
+        ));
+    }
+    Ok(header)
+}
+
+/// Monkey's Audioファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let header = read(reader)?;
+
+    audio_info.format_name = "ape".to_string();
+    audio_info.format_long_name = "Monkey's Audio".to_string();
+    audio_info.codec_name = "ape".to_string();
+    audio_info.codec_long_name = "Monkey's Audio".to_string();
+    audio_info.sample_rate = header.sample_rate as i32;
+    audio_info.channels = header.channels as i32;
+    audio_info.bits_per_sample = header.bits_per_sample as i32;
+    audio_info.channel_layout = default_channel_layout(header.channels);
+    audio_info.duration_seconds = header.duration_seconds();
+    if audio_info.duration_seconds > 0.0 {
+        audio_info.bit_rate =
+            (audio_info.file_size as f64 * 8.0 / audio_info.duration_seconds).round() as i64;
+    }
+
+    let params = &mut audio_info.codec_params;
+    params.insert(
+        "compression_level".to_string(),
+        format!(
+            "{} ({})",
+            header.compression_level,
+            header.compression_name()
+        ),
+    );
+    params.insert(
+        "version".to_string(),
+        format!("{:.2}", header.version as f64 / 1000.0),
+    );
+    params.insert("frames".to_string(), header.total_frames.to_string());
+
+    audio_info.metadata = apetag::read_metadata(reader)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn descriptor_file() -> Vec<u8> {
+        let mut file = b"MAC ".to_vec();
+        file.extend_from_slice(&3990u16.to_le_bytes());
+        file.extend_from_slice(&[0, 0]);
+        file.extend_from_slice(&52u32.to_le_bytes()); // descriptor_bytes
+        file.extend_from_slice(&24u32.to_le_bytes()); // header_bytes
+        file.extend_from_slice(&[0; 36]);
+        // APE_HEADER: high, 73728*4ブロック/フレーム、3フレーム
+        file.extend_from_slice(&3000u16.to_le_bytes());
+        file.extend_from_slice(&0u16.to_le_bytes());
+        file.extend_from_slice(&294_912u32.to_le_bytes());
+        file.extend_from_slice(&27_576u32.to_le_bytes());
+        file.extend_from_slice(&3u32.to_le_bytes());
+        file.extend_from_slice(&16u16.to_le_bytes());
+        file.extend_from_slice(&2u16.to_le_bytes());
+        file.extend_from_slice(&44100u32.to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn test_ape_descriptor_header() {
+        let mut file = descriptor_file();
+        file.extend(apetag::build_tag(&[("Artist", b"Someone", 0)], true));
+
+        let mut info = AudioInfo::new("album.ape".into());
+        info.file_size = file.len() as u64;
+        probe(&mut Cursor::new(file), &mut info).unwrap();
+        assert_eq!(info.codec_name, "ape");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, 16);
+        // (2 * 294912 + 27576) / 44100 = 14.0秒
+        assert_eq!(info.duration_seconds, 14.0);
+        assert_eq!(info.codec_params["compression_level"], "3000 (high)");
+        assert_eq!(info.codec_params["version"], "3.99");
+        assert_eq!(info.metadata["artist"], "Someone");
+    }
+
+    #[test]
+    fn test_ape_legacy_header() {
+        let mut file = b"MAC ".to_vec();
+        file.extend_from_slice(&3970u16.to_le_bytes());
+        file.extend_from_slice(&2000u16.to_le_bytes());
+        file.extend_from_slice(&FLAG_24_BIT.to_le_bytes());
+        file.extend_from_slice(&1u16.to_le_bytes());
+        file.extend_from_slice(&48000u32.to_le_bytes());
+        file.extend_from_slice(&[0; 8]);
+        file.extend_from_slice(&1u32.to_le_bytes());
+        file.extend_from_slice(&96000u32.to_le_bytes());
+
+        let header = read(&mut Cursor::new(file)).unwrap();
+        assert_eq!(header.bits_per_sample, 24);
+        assert_eq!(header.channels, 1);
+        assert_eq!(header.duration_seconds(), 2.0);
+        assert_eq!(header.compression_name(), "normal");
+    }
+}
@@ -0,0 +1,251 @@
+// APEv1/APEv2 タグリーダー
+//
+// タグはファイル末尾（ID3v1タグがある場合はその直前）に置かれ、32バイトのフッターから位置を求める。
+// キー名はID3と同じくffprobe互換の小文字キーに揃える（"Year"は"date"など）。
+//
+// 優先順位:
+// - MP3/AACなどID3が本来のタグ形式のファイル: ID3v2 > APE > ID3v1
+// - Monkey's Audio/WavPack: APE > ID3v2 > ID3v1
+// いずれも優先度の低いタグは欠けているキーのみを補う。
+
+use super::{id3, read_array, read_vec, stream_len, ParseError, ParseResult};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const PREAMBLE: &[u8; 8] = b"APETAGEX";
+
+/// ヘッダー/フッターの長さ
+const FOOTER_LEN: u64 = 32;
+
+/// タグ本体の最大サイズ
+const MAX_TAG_SIZE: u64 = 16 * 1024 * 1024;
+
+/// ヘッダーが存在する
+const FLAG_HAS_HEADER: u32 = 1 << 31;
+
+/// 値の種類（フラグのビット1-2）: 0 UTF-8テキスト, 1 バイナリ, 2 外部リンク
+const ITEM_TYPE_BINARY: u32 = 1;
+
+/// ファイル末尾から読み取ったAPEタグ
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApeTag {
+    /// 1000 (APEv1) または 2000 (APEv2)
+    pub version: u32,
+    /// タグ先頭（ヘッダーを含む）のファイル内オフセット
+    pub offset: u64,
+    pub entries: Vec<(String, String)>,
+}
+
+/// APEのアイテムキーをffprobe互換のメタデータキーに変換する
+fn item_key(key: &str) -> String {
+    let key = key.to_lowercase();
+    match key.as_str() {
+        "year" => "date".to_string(),
+        "album artist" | "albumartist" => "album_artist".to_string(),
+        "discnumber" => "disc".to_string(),
+        "tracknumber" => "track".to_string(),
+        _ => key,
+    }
+}
+
+/// タグ本体（ヘッダー/フッターを除く）からアイテムを取り出す
+fn parse_items(data: &[u8], version: u32, item_count: u32) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    for _ in 0..item_count {
+        let Some(header) = data.get(pos..pos + 8) else {
+            break;
+        };
+        let size = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let flags = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        pos += 8;
+
+        let Some(key_len) = data[pos..].iter().position(|b| *b == 0) else {
+            break;
+        };
+        let key = String::from_utf8_lossy(&data[pos..pos + key_len]).to_string();
+        pos += key_len + 1;
+
+        let Some(value) = data.get(pos..pos + size) else {
+            break;
+        };
+        pos += size;
+
+        // APEv1のフラグは常に0で、値はテキストのみ
+        if version >= 2000 && (flags >> 1) & 0x3 == ITEM_TYPE_BINARY {
+            continue;
+        }
+        // APEv2の複数値はNUL区切り
+        let value = String::from_utf8_lossy(value)
+            .split('\0')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join(";");
+        if !value.is_empty() {
+            entries.push((item_key(&key), value));
+        }
+    }
+    entries
+}
+
+/// フッターが`footer_offset`にあればタグを読む
+fn read_at<R: Read + Seek>(reader: &mut R, footer_offset: u64) -> ParseResult<Option<ApeTag>> {
+    reader.seek(SeekFrom::Start(footer_offset))?;
+    let footer: [u8; 32] = read_array(reader)?;
+    if &footer[0..8] != PREAMBLE {
+        return Ok(None);
+    }
+    let field = |i: usize| u32::from_le_bytes(footer[i..i + 4].try_into().unwrap());
+    let (version, tag_size, item_count, flags) = (field(8), field(12), field(16), field(20));
+
+    // tag_sizeはアイテムとフッターの合計（ヘッダーは含まない）
+    let tag_size = tag_size as u64;
+    if tag_size < FOOTER_LEN || tag_size > footer_offset + FOOTER_LEN {
+        return Err(ParseError::Invalid(format!(
+            "APE tag size {} is out of range",
+            tag_size
+        )));
+    }
+    let items_offset = footer_offset + FOOTER_LEN - tag_size;
+    let header_len = if version >= 2000 && flags & FLAG_HAS_HEADER != 0 {
+        FOOTER_LEN
+    } else {
+        0
+    };
+
+    reader.seek(SeekFrom::Start(items_offset))?;
+    let data = read_vec(reader, tag_size - FOOTER_LEN, MAX_TAG_SIZE)?;
+
+    Ok(Some(ApeTag {
+        version,
+        offset: items_offset.saturating_sub(header_len),
+        entries: parse_items(&data, version, item_count),
+    }))
+}
+
+/// ファイル末尾（またはID3v1タグの直前）のAPEタグを読む
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<Option<ApeTag>> {
+    let file_len = stream_len(reader)?;
+    if file_len >= FOOTER_LEN {
+        if let Some(tag) = read_at(reader, file_len - FOOTER_LEN)? {
+            return Ok(Some(tag));
+        }
+    }
+    if file_len >= 128 + FOOTER_LEN {
+        reader.seek(SeekFrom::Start(file_len - 128))?;
+        let id3v1: [u8; 3] = read_array(reader)?;
+        if &id3v1 == b"TAG" {
+            return read_at(reader, file_len - 128 - FOOTER_LEN);
+        }
+    }
+    Ok(None)
+}
+
+/// ファイルのAPEタグをメタデータのエントリーとして読む（タグがなければ空）
+pub fn read_path(path: &Path) -> ParseResult<Vec<(String, String)>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(read(&mut reader)?
+        .map(|tag| tag.entries)
+        .unwrap_or_default())
+}
+
+/// APEタグを主とするフォーマット（Monkey's Audio/WavPack）のメタデータを読む
+///
+/// APEタグを優先し、ID3v2・ID3v1は欠けているキーのみを補う。
+pub fn read_metadata<R: Read + Seek>(reader: &mut R) -> ParseResult<HashMap<String, String>> {
+    let mut metadata = HashMap::new();
+    if let Some(tag) = read(reader)? {
+        super::merge_metadata(&mut metadata, tag.entries);
+    }
+    for (key, value) in id3::read_metadata(reader)? {
+        metadata.entry(key).or_insert(value);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+pub(crate) fn build_tag(items: &[(&str, &[u8], u32)], with_header: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (key, value, flags) in items {
+        body.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        body.extend_from_slice(&flags.to_le_bytes());
+        body.extend_from_slice(key.as_bytes());
+        body.push(0);
+        body.extend_from_slice(value);
+    }
+    let block = |is_header: bool| {
+        let mut flags = if with_header { FLAG_HAS_HEADER } else { 0 };
+        if is_header {
+            flags |= 1 << 29;
+        }
+        let mut out = PREAMBLE.to_vec();
+        out.extend_from_slice(&2000u32.to_le_bytes());
+        out.extend_from_slice(&(body.len() as u32 + 32).to_le_bytes());
+        out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+        out.extend_from_slice(&flags.to_le_bytes());
+        out.extend_from_slice(&[0; 8]);
+        out
+    };
+
+    let mut tag = if with_header { block(true) } else { Vec::new() };
+    tag.extend_from_slice(&body);
+    tag.extend(block(false));
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_apev2_tag_before_id3v1() {
+        let mut file = b"audio data".to_vec();
+        file.extend(build_tag(
+            &[
+                ("Title", b"First\0Second", 0),
+                ("Year", b"2021", 0),
+                ("Album Artist", b"Various", 0),
+                ("Cover Art (Front)", b"cover.jpg\0\xFF\xD8", 1 << 1),
+            ],
+            true,
+        ));
+        let mut id3v1 = vec![0u8; 128];
+        id3v1[0..3].copy_from_slice(b"TAG");
+        id3v1[3..8].copy_from_slice(b"Other");
+        id3v1[127] = 0xFF;
+        file.extend(id3v1);
+
+        let tag = read(&mut Cursor::new(&file)).unwrap().unwrap();
+        assert_eq!(tag.version, 2000);
+        assert_eq!(tag.offset, 10);
+        assert_eq!(
+            tag.entries,
+            vec![
+                ("title".to_string(), "First;Second".to_string()),
+                ("date".to_string(), "2021".to_string()),
+                ("album_artist".to_string(), "Various".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_id3v2_takes_precedence_over_ape_for_mpeg() {
+        let mut file = vec![b'I', b'D', b'3', 3, 0, 0, 0, 0, 0, 17];
+        file.extend_from_slice(b"TIT2\x00\x00\x00\x07\x00\x00\x00ID3 v2");
+        file.extend(build_tag(
+            &[("Title", b"APE", 0), ("Artist", b"Band", 0)],
+            false,
+        ));
+
+        let metadata = id3::read_metadata(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(metadata["title"], "ID3 v2");
+        assert_eq!(metadata["artist"], "Band");
+
+        let metadata = read_metadata(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(metadata["title"], "APE");
+    }
+}
@@ -0,0 +1,263 @@
+// 埋め込みアートワーク（ID3 APIC・FLAC PICTURE・MP4 covr・Vorbis METADATA_BLOCK_PICTURE）
+//
+// ピクチャータイプの表記はffprobeがattached_picストリームのcommentタグに出すものに揃える。
+// 幅・高さやMIMEタイプが記録されていない形式では画像データの先頭から判定する。
+
+use super::{flac::Picture, id3, ParseResult};
+use crate::{Artwork, AudioInfo};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// ID3v2/FLACのピクチャータイプ（FFmpegのff_id3v2_picture_typesと同じ表記）
+const PICTURE_TYPES: [(&str, &str); 21] = [
+    ("Other", "other"),
+    ("32x32 pixels 'file icon'", "icon"),
+    ("Other file icon", "other_icon"),
+    ("Cover (front)", "cover_front"),
+    ("Cover (back)", "cover_back"),
+    ("Leaflet page", "leaflet"),
+    ("Media (e.g. label side of CD)", "media"),
+    ("Lead artist/lead performer/soloist", "lead_artist"),
+    ("Artist/performer", "artist"),
+    ("Conductor", "conductor"),
+    ("Band/Orchestra", "band"),
+    ("Composer", "composer"),
+    ("Lyricist/text writer", "lyricist"),
+    ("Recording Location", "recording_location"),
+    ("During recording", "during_recording"),
+    ("During performance", "during_performance"),
+    ("Movie/video screen capture", "screen_capture"),
+    ("A bright coloured fish", "fish"),
+    ("Illustration", "illustration"),
+    ("Band/artist logotype", "band_logo"),
+    ("Publisher/Studio logotype", "publisher_logo"),
+];
+
+/// ピクチャータイプ番号の表示名
+pub fn picture_type_name(picture_type: u32) -> &'static str {
+    PICTURE_TYPES
+        .get(picture_type as usize)
+        .map_or("Other", |(name, _)| name)
+}
+
+/// 書き出すファイル名に使うピクチャータイプの短い名前
+fn picture_type_slug(name: &str) -> &'static str {
+    PICTURE_TYPES
+        .iter()
+        .find(|(type_name, _)| *type_name == name)
+        .map_or("other", |(_, slug)| slug)
+}
+
+/// 画像データの先頭からMIMEタイプを判定する
+pub fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    Some(match data {
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'B', b'M', ..] => "image/bmp",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => return None,
+    })
+}
+
+/// MIMEタイプに対応する拡張子
+fn extension(mime_type: &str) -> &'static str {
+    match mime_type.to_ascii_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" | "jpg" => "jpg",
+        "image/png" | "png" => "png",
+        "image/gif" => "gif",
+        "image/bmp" => "bmp",
+        "image/webp" => "webp",
+        _ => "bin",
+    }
+}
+
+/// 画像データの先頭から(幅, 高さ)を読む（JPEG・PNG・GIF・BMP）
+pub fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| {
+        data.get(i..i + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+    };
+    let le16 = |i: usize| {
+        data.get(i..i + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as u32)
+    };
+    let be32 = |i: usize| {
+        data.get(i..i + 4)
+            .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+    };
+    let le32 = |i: usize| {
+        data.get(i..i + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    };
+
+    match sniff_mime_type(data)? {
+        "image/png" => Some((be32(16)?, be32(20)?)),
+        "image/gif" => Some((le16(6)?, le16(8)?)),
+        // BITMAPINFOHEADERの高さは負（トップダウン）の場合がある
+        "image/bmp" => Some((le32(18)?, (le32(22)? as i32).unsigned_abs())),
+        "image/jpeg" => {
+            // SOFnマーカー（DHT・JPG・DACを除くC0〜CF）を探す
+            let mut pos = 2;
+            while let (Some(0xFF), Some(marker)) = (data.get(pos), data.get(pos + 1)) {
+                let len = be16(pos + 2)? as usize;
+                if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                    return Some((be16(pos + 7)?, be16(pos + 5)?));
+                }
+                pos += 2 + len;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// 画像データから`Artwork`を作る（幅・高さとMIMEタイプが不明な場合は画像から補う）
+pub fn from_picture(picture: Picture, source: &str) -> Artwork {
+    let dimensions = Some((picture.width, picture.height))
+        .filter(|(width, height)| *width > 0 && *height > 0)
+        .or_else(|| image_dimensions(&picture.data));
+    // "-->"はID3のリンク（画像がURLで示される）
+    let mime_type = match picture.mime_type.as_str() {
+        "" | "-->" => sniff_mime_type(&picture.data).unwrap_or("application/octet-stream"),
+        mime_type => mime_type,
+    };
+    Artwork {
+        picture_type: picture_type_name(picture.picture_type).to_string(),
+        mime_type: mime_type.to_string(),
+        description: Some(picture.description).filter(|d| !d.is_empty()),
+        width: dimensions.map(|(width, _)| width),
+        height: dimensions.map(|(_, height)| height),
+        size: picture.data.len() as u64,
+        source: source.to_string(),
+        extracted_path: None,
+        data: picture.data,
+    }
+}
+
+/// ネイティブパーサーが対応していないファイル向けに、先頭のID3v2タグのAPICだけを読む
+pub fn read_id3_path(path: &Path) -> ParseResult<Vec<Artwork>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(id3::read_v2_at(&mut reader, 0)?
+        .map(|tag| tag.artwork())
+        .unwrap_or_default())
+}
+
+/// パス文字列のFNV-1aハッシュ（別ディレクトリの同名ファイルを区別するため）
+fn path_hash(path: &Path) -> u32 {
+    path.to_string_lossy()
+        .bytes()
+        .fold(0x811C_9DC5u32, |hash, b| {
+            (hash ^ b as u32).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// 書き出すファイル名: "<元ファイル名>.<パスのハッシュ>.<番号>-<ピクチャータイプ>.<拡張子>"
+///
+/// 同じ入力に対しては常に同じ名前になり、再実行時は上書きする
+pub fn file_name(audio_path: &Path, index: usize, artwork: &Artwork) -> String {
+    let stem = audio_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "artwork".to_string());
+    format!(
+        "{}.{:08x}.{}-{}.{}",
+        stem,
+        path_hash(audio_path),
+        index,
+        picture_type_slug(&artwork.picture_type),
+        extension(&artwork.mime_type)
+    )
+}
+
+/// 画像データを持つアートワークを`dir`に書き出し、書き出し先を記録する
+///
+/// 書き出しに失敗した場合やデータがない（ffprobeのみで検出した）場合は警告に残す
+pub fn extract(audio_info: &mut AudioInfo, dir: &Path) {
+    let audio_path = audio_info.file_path.clone();
+    for (index, artwork) in audio_info.artwork.iter_mut().enumerate() {
+        if artwork.data.is_empty() {
+            audio_info.warnings.push(format!(
+                "Artwork #{} ({}) has no embedded data available for extraction",
+                index, artwork.picture_type
+            ));
+            continue;
+        }
+        let target = dir.join(file_name(&audio_path, index, artwork));
+        match std::fs::write(&target, &artwork.data) {
+            Ok(()) => artwork.extracted_path = Some(target),
+            Err(e) => audio_info.warnings.push(format!(
+                "Failed to extract artwork #{} to {:?}: {}",
+                index, target, e
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&[8, 2, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        assert_eq!(image_dimensions(&png(600, 400)), Some((600, 400)));
+
+        // SOI + APP0 + SOF0（高さ 300, 幅 500）
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+        jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 0x08, 0x01, 0x2C, 0x01, 0xF4]);
+        assert_eq!(image_dimensions(&jpeg), Some((500, 300)));
+
+        assert_eq!(image_dimensions(b"GIF89a\x10\x00\x20\x00"), Some((16, 32)));
+        assert_eq!(image_dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_from_picture_and_extract() {
+        let picture = Picture {
+            picture_type: 3,
+            mime_type: String::new(),
+            description: String::new(),
+            width: 0,
+            height: 0,
+            data: png(32, 32),
+        };
+        let artwork = from_picture(picture, "id3_apic");
+        assert_eq!(artwork.picture_type, "Cover (front)");
+        assert_eq!(artwork.mime_type, "image/png");
+        assert_eq!((artwork.width, artwork.height), (Some(32), Some(32)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut info = AudioInfo::new("music/song.mp3".into());
+        info.artwork.push(artwork);
+        info.artwork.push(Artwork {
+            picture_type: "Other".to_string(),
+            source: "ffprobe".to_string(),
+            ..Default::default()
+        });
+        extract(&mut info, dir.path());
+
+        let name = file_name(Path::new("music/song.mp3"), 0, &info.artwork[0]);
+        assert!(name.starts_with("song.") && name.ends_with(".0-cover_front.png"));
+        assert_eq!(
+            name,
+            file_name(Path::new("music/song.mp3"), 0, &info.artwork[0])
+        );
+        assert_ne!(
+            name,
+            file_name(Path::new("other/song.mp3"), 0, &info.artwork[0])
+        );
+        let written = info.artwork[0].extracted_path.as_ref().unwrap();
+        assert_eq!(std::fs::read(written).unwrap(), png(32, 32));
+        assert_eq!(info.artwork[1].extracted_path, None);
+        assert_eq!(info.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,431 @@
+// ASF (WMA/WMV) ヘッダーオブジェクトパーサー
+
+use super::{
+    read_array, read_vec, sniff::ASF_HEADER_GUID, wav::WaveFormat, ParseError, ParseResult,
+};
+use crate::AudioInfo;
+use std::io::{Read, Seek};
+
+// ---- オブジェクトGUID（ファイル上のバイト順） ----
+const FILE_PROPERTIES_GUID: [u8; 16] = [
+    0xA1, 0xDC, 0xAB, 0x8C, 0x47, 0xA9, 0xCF, 0x11, 0x8E, 0xE4, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65,
+];
+const STREAM_PROPERTIES_GUID: [u8; 16] = [
+    0x91, 0x07, 0xDC, 0xB7, 0xB7, 0xA9, 0xCF, 0x11, 0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65,
+];
+const CONTENT_DESCRIPTION_GUID: [u8; 16] = [
+    0x33, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+const EXTENDED_CONTENT_DESCRIPTION_GUID: [u8; 16] = [
+    0x40, 0xA4, 0xD0, 0xD2, 0x07, 0xE3, 0xD2, 0x11, 0x97, 0xF0, 0x00, 0xA0, 0xC9, 0x5E, 0xA8, 0x50,
+];
+const AUDIO_MEDIA_GUID: [u8; 16] = [
+    0x40, 0x9E, 0x69, 0xF8, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
+];
+const VIDEO_MEDIA_GUID: [u8; 16] = [
+    0xC0, 0xEF, 0x19, 0xBC, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
+];
+
+/// Header Objectの上限（WM/Pictureを含むことがあるため大きめ）
+const MAX_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 各オブジェクトの共通ヘッダー（GUID + 64ビットサイズ）
+const OBJECT_HEADER_LEN: usize = 24;
+
+fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+}
+
+fn le_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+}
+
+/// 終端のNULを除いたUTF-16LE文字列
+fn decode_utf16le(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    let end = units.iter().position(|u| *u == 0).unwrap_or(units.len());
+    String::from_utf16_lossy(&units[..end])
+}
+
+/// 拡張コンテンツ記述の属性名をffprobe互換のキーに変換する
+fn attribute_key(name: &str) -> String {
+    match name {
+        "WM/AlbumArtist" => "album_artist",
+        "WM/AlbumTitle" => "album",
+        "Author" => "artist",
+        "Description" => "comment",
+        "WM/Composer" => "composer",
+        "WM/EncodedBy" => "encoded_by",
+        "WM/EncodingSettings" | "WM/Tool" => "encoder",
+        "WM/Genre" => "genre",
+        "WM/Language" => "language",
+        "WM/OriginalFilename" => "filename",
+        "WM/PartOfSet" => "disc",
+        "WM/Publisher" => "publisher",
+        "WM/TrackNumber" => "track",
+        "WM/Year" => "date",
+        "WM/MediaStationCallSign" => "service_provider",
+        "WM/MediaStationName" => "service_name",
+        _ => return name.to_lowercase(),
+    }
+    .to_string()
+}
+
+/// Stream Properties Object
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsfStream {
+    pub number: u16,
+    pub is_audio: bool,
+    pub is_video: bool,
+    /// 音声ストリームのWAVEFORMATEX
+    pub format: Option<WaveFormat>,
+}
+
+impl AsfStream {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let stream_type = data.get(0..16)?;
+        let type_specific_len = le_u32(data, 40)? as usize;
+        let flags = le_u16(data, 48)?;
+        let is_audio = stream_type == AUDIO_MEDIA_GUID;
+        let format = if is_audio {
+            data.get(54..54 + type_specific_len)
+                .and_then(|wfx| WaveFormat::parse(wfx).ok())
+        } else {
+            None
+        };
+        Some(Self {
+            number: flags & 0x7F,
+            is_audio,
+            is_video: stream_type == VIDEO_MEDIA_GUID,
+            format,
+        })
+    }
+}
+
+/// ASFヘッダー全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsfFile {
+    pub file_size: u64,
+    /// 100ナノ秒単位
+    pub play_duration: u64,
+    /// ミリ秒
+    pub preroll: u64,
+    /// ライブ配信（再生時間が無効）
+    pub broadcast: bool,
+    pub max_bit_rate: u32,
+    pub streams: Vec<AsfStream>,
+    /// Content DescriptionとExtended Content Descriptionの(キー, 値)
+    pub metadata: Vec<(String, String)>,
+}
+
+impl AsfFile {
+    pub fn primary_audio_stream(&self) -> Option<&AsfStream> {
+        self.streams
+            .iter()
+            .find(|s| s.is_audio && s.format.is_some())
+    }
+
+    /// Play Durationにはプリロールが含まれるため差し引く
+    pub fn duration_seconds(&self) -> f64 {
+        if self.broadcast {
+            return 0.0;
+        }
+        let duration = self.play_duration as f64 / 10_000_000.0 - self.preroll as f64 / 1000.0;
+        duration.max(0.0)
+    }
+
+    fn parse_file_properties(&mut self, data: &[u8]) -> Option<()> {
+        self.file_size = le_u64(data, 16)?;
+        self.play_duration = le_u64(data, 40)?;
+        self.preroll = le_u64(data, 56)?;
+        self.broadcast = le_u32(data, 64)? & 0x1 != 0;
+        self.max_bit_rate = le_u32(data, 76)?;
+        Some(())
+    }
+
+    fn parse_content_description(&mut self, data: &[u8]) -> Option<()> {
+        let keys = ["title", "artist", "copyright", "comment", "rating"];
+        let mut offset = 10;
+        for (i, key) in keys.iter().enumerate() {
+            let len = le_u16(data, i * 2)? as usize;
+            let value = decode_utf16le(data.get(offset..offset + len)?);
+            offset += len;
+            if !value.is_empty() {
+                self.metadata.push((key.to_string(), value));
+            }
+        }
+        Some(())
+    }
+
+    fn parse_extended_content_description(&mut self, data: &[u8]) -> Option<()> {
+        let count = le_u16(data, 0)?;
+        let mut offset = 2;
+        let mut track_zero_based = None;
+        for _ in 0..count {
+            let name_len = le_u16(data, offset)? as usize;
+            let name = decode_utf16le(data.get(offset + 2..offset + 2 + name_len)?);
+            offset += 2 + name_len;
+            let value_type = le_u16(data, offset)?;
+            let value_len = le_u16(data, offset + 2)? as usize;
+            let raw = data.get(offset + 4..offset + 4 + value_len)?;
+            offset += 4 + value_len;
+
+            let value = match value_type {
+                0 => decode_utf16le(raw),
+                2 => (le_u32(raw, 0).unwrap_or(0) != 0).to_string(),
+                3 => le_u32(raw, 0)?.to_string(),
+                4 => le_u64(raw, 0)?.to_string(),
+                5 => le_u16(raw, 0)?.to_string(),
+                // バイト配列（WM/Pictureなど）は文字列化しない
+                _ => continue,
+            };
+            if value.is_empty() {
+                continue;
+            }
+            // WM/Trackは0始まり（WM/TrackNumberがない場合のみ使う）
+            if name == "WM/Track" {
+                track_zero_based = value.parse::<u64>().ok();
+                continue;
+            }
+            self.metadata.push((attribute_key(&name), value));
+        }
+
+        if let Some(track) = track_zero_based {
+            if !self.metadata.iter().any(|(key, _)| key == "track") {
+                self.metadata
+                    .push(("track".to_string(), (track + 1).to_string()));
+            }
+        }
+        Some(())
+    }
+}
+
+/// Header Objectを読み込み、子オブジェクトを解析する
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<AsfFile> {
+    let header: [u8; 30] = read_array(reader)?;
+    if header[0..16] != ASF_HEADER_GUID {
+        return Err(ParseError::Invalid("not an ASF file".to_string()));
+    }
+    let header_size = u64::from_le_bytes(header[16..24].try_into().unwrap());
+    if header_size < 30 {
+        return Err(ParseError::Invalid(format!(
+            "invalid ASF header size {}",
+            header_size
+        )));
+    }
+    let body = read_vec(reader, header_size - 30, MAX_HEADER_SIZE)?;
+
+    let mut file = AsfFile {
+        file_size: 0,
+        play_duration: 0,
+        preroll: 0,
+        broadcast: false,
+        max_bit_rate: 0,
+        streams: Vec::new(),
+        metadata: Vec::new(),
+    };
+    let mut has_file_properties = false;
+
+    let mut offset = 0;
+    while offset + OBJECT_HEADER_LEN <= body.len() {
+        let guid = &body[offset..offset + 16];
+        let size = le_u64(&body, offset + 16).unwrap_or(0) as usize;
+        let Some(end) = offset
+            .checked_add(size)
+            .filter(|end| size >= OBJECT_HEADER_LEN && *end <= body.len())
+        else {
+            break;
+        };
+        let data = &body[offset + OBJECT_HEADER_LEN..end];
+
+        if guid == FILE_PROPERTIES_GUID {
+            has_file_properties = file.parse_file_properties(data).is_some();
+        } else if guid == STREAM_PROPERTIES_GUID {
+            if let Some(stream) = AsfStream::parse(data) {
+                file.streams.push(stream);
+            }
+        } else if guid == CONTENT_DESCRIPTION_GUID {
+            file.parse_content_description(data);
+        } else if guid == EXTENDED_CONTENT_DESCRIPTION_GUID {
+            file.parse_extended_content_description(data);
+        }
+
+        offset = end;
+    }
+
+    if !has_file_properties {
+        return Err(ParseError::Invalid(
+            "missing ASF File Properties object".to_string(),
+        ));
+    }
+    Ok(file)
+}
+
+/// ASFファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let asf = read(reader)?;
+    let format = asf
+        .primary_audio_stream()
+        .and_then(|s| s.format.as_ref())
+        .ok_or_else(|| ParseError::Invalid("ASF file has no audio stream".to_string()))?;
+    let (codec_name, codec_long_name) = format.codec_names();
+
+    audio_info.format_name = "asf".to_string();
+    audio_info.format_long_name = "ASF (Advanced / Active Streaming Format)".to_string();
+    audio_info.codec_name = codec_name;
+    audio_info.codec_long_name = codec_long_name;
+    audio_info.sample_rate = format.sample_rate as i32;
+    audio_info.channels = format.channels as i32;
+    audio_info.bits_per_sample = format.bits_per_sample as i32;
+    audio_info.channel_layout = format.channel_layout();
+    audio_info.bit_rate = format.byte_rate as i64 * 8;
+    audio_info.duration_seconds = asf.duration_seconds();
+    audio_info.has_video = asf.streams.iter().any(|s| s.is_video);
+
+    super::merge_metadata(&mut audio_info.metadata, asf.metadata);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn object(guid: &[u8; 16], body: &[u8]) -> Vec<u8> {
+        let mut out = guid.to_vec();
+        out.extend_from_slice(&((body.len() + OBJECT_HEADER_LEN) as u64).to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn utf16(value: &str) -> Vec<u8> {
+        value
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|u| u.to_le_bytes())
+            .collect()
+    }
+
+    fn header(objects: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = objects.concat();
+        let mut out = ASF_HEADER_GUID.to_vec();
+        out.extend_from_slice(&((body.len() + 30) as u64).to_le_bytes());
+        out.extend_from_slice(&(objects.len() as u32).to_le_bytes());
+        out.extend_from_slice(&[1, 2]);
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn file_properties(play_duration: u64, preroll: u64) -> Vec<u8> {
+        let mut body = vec![0u8; 80];
+        body[40..48].copy_from_slice(&play_duration.to_le_bytes());
+        body[56..64].copy_from_slice(&preroll.to_le_bytes());
+        body[64..68].copy_from_slice(&2u32.to_le_bytes());
+        body[76..80].copy_from_slice(&128_000u32.to_le_bytes());
+        object(&FILE_PROPERTIES_GUID, &body)
+    }
+
+    fn wma_stream() -> Vec<u8> {
+        let mut wfx = Vec::new();
+        wfx.extend_from_slice(&0x0161u16.to_le_bytes());
+        wfx.extend_from_slice(&2u16.to_le_bytes());
+        wfx.extend_from_slice(&44100u32.to_le_bytes());
+        wfx.extend_from_slice(&16000u32.to_le_bytes());
+        wfx.extend_from_slice(&2973u16.to_le_bytes());
+        wfx.extend_from_slice(&16u16.to_le_bytes());
+        wfx.extend_from_slice(&10u16.to_le_bytes());
+        wfx.extend_from_slice(&[0; 10]);
+
+        let mut body = AUDIO_MEDIA_GUID.to_vec();
+        body.extend_from_slice(&[0; 16]);
+        body.extend_from_slice(&0u64.to_le_bytes());
+        body.extend_from_slice(&(wfx.len() as u32).to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&[0; 4]);
+        body.extend_from_slice(&wfx);
+        object(&STREAM_PROPERTIES_GUID, &body)
+    }
+
+    #[test]
+    fn test_wma_header() {
+        let title = utf16("Legacy Track");
+        let author = utf16("Old Band");
+        let mut description = Vec::new();
+        for len in [title.len(), author.len(), 0, 0, 0] {
+            description.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        description.extend_from_slice(&title);
+        description.extend_from_slice(&author);
+
+        let mut extended = 3u16.to_le_bytes().to_vec();
+        for (name, value_type, value) in [
+            ("WM/AlbumTitle", 0u16, utf16("Archive")),
+            ("WM/Track", 3, 4u32.to_le_bytes().to_vec()),
+            ("WM/Picture", 1, vec![0xFF, 0xD8]),
+        ] {
+            let name = utf16(name);
+            extended.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            extended.extend_from_slice(&name);
+            extended.extend_from_slice(&value_type.to_le_bytes());
+            extended.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            extended.extend_from_slice(&value);
+        }
+
+        let bytes = header(&[
+            // 3分 + プリロール3秒
+            file_properties(1_830_000_000, 3000),
+            wma_stream(),
+            object(&CONTENT_DESCRIPTION_GUID, &description),
+            object(&EXTENDED_CONTENT_DESCRIPTION_GUID, &extended),
+        ]);
+
+        let mut info = AudioInfo::new("legacy.wma".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "wmav2");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bit_rate, 128_000);
+        assert_eq!(info.duration_seconds, 180.0);
+        assert_eq!(info.metadata["title"], "Legacy Track");
+        assert_eq!(info.metadata["artist"], "Old Band");
+        assert_eq!(info.metadata["album"], "Archive");
+        assert_eq!(info.metadata["track"], "5");
+        assert!(!info.metadata.contains_key("wm/picture"));
+    }
+
+    #[test]
+    fn test_oversized_header_object() {
+        // 24バイトの空オブジェクトの後に、サイズが2^64-8のオブジェクトが続く
+        let mut oversized = [0xAA; 16].to_vec();
+        oversized.extend_from_slice(&(u64::MAX - 7).to_le_bytes());
+        oversized.extend_from_slice(&[0; 8]);
+        let bytes = header(&[
+            file_properties(30_000_000, 0),
+            object(&[0x55; 16], &[]),
+            oversized,
+        ]);
+        let asf = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(asf.play_duration, 30_000_000);
+        assert!(asf.streams.is_empty());
+    }
+
+    #[test]
+    fn test_not_an_asf_file() {
+        let result = read(&mut Cursor::new(
+            b"This is a test MP3 file, padded out.".to_vec(),
+        ));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,190 @@
+// Sun AU (.au/.snd) ヘッダーパーサー
+//
+// 24バイトのビッグエンディアンヘッダーの後、データ開始位置までが注釈（annotation）になる。
+
+use super::{default_channel_layout, read_array, read_vec, stream_len, ParseError, ParseResult};
+use crate::AudioInfo;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+const MAGIC: &[u8; 4] = b".snd";
+const HEADER_LEN: u32 = 24;
+
+/// データサイズ不明
+const UNKNOWN_SIZE: u32 = u32::MAX;
+
+/// 注釈の読み込み上限
+const MAX_ANNOTATION_SIZE: u64 = 64 * 1024;
+
+/// Sun AUのヘッダー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuHeader {
+    pub data_offset: u32,
+    pub data_size: Option<u32>,
+    pub encoding: u32,
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub annotation: String,
+}
+
+impl AuHeader {
+    /// (codec_name, codec_long_name, 1サンプルあたりのビット数)
+    pub fn codec(&self) -> Option<(&'static str, &'static str, u32)> {
+        Some(match self.encoding {
+            1 => ("pcm_mulaw", "PCM mu-law / G.711 mu-law", 8),
+            2 => ("pcm_s8", "PCM signed 8-bit", 8),
+            3 => ("pcm_s16be", "PCM signed 16-bit big-endian", 16),
+            4 => ("pcm_s24be", "PCM signed 24-bit big-endian", 24),
+            5 => ("pcm_s32be", "PCM signed 32-bit big-endian", 32),
+            6 => ("pcm_f32be", "PCM 32-bit floating point big-endian", 32),
+            7 => ("pcm_f64be", "PCM 64-bit floating point big-endian", 64),
+            23 => ("adpcm_g726le", "G.726 ADPCM little-endian", 4),
+            24 => ("adpcm_g722", "G.722 ADPCM", 4),
+            25 => ("adpcm_g726le", "G.726 ADPCM little-endian", 3),
+            26 => ("adpcm_g726le", "G.726 ADPCM little-endian", 5),
+            27 => ("pcm_alaw", "PCM A-law / G.711 A-law", 8),
+            _ => return None,
+        })
+    }
+
+    /// 注釈をメタデータに変換する
+    ///
+    /// FFmpegと同様に"key=value"形式（改行区切り）の既知のキーを取り出し、
+    /// それ以外の形式の注釈はそのままcommentとして扱う。
+    pub fn metadata(&self) -> HashMap<String, String> {
+        const KEYS: [&str; 5] = ["title", "artist", "album", "track", "genre"];
+
+        let mut metadata = HashMap::new();
+        let annotation = self.annotation.trim();
+        if annotation.is_empty() {
+            return metadata;
+        }
+        for line in annotation.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim().to_lowercase();
+                if KEYS.contains(&key.as_str()) && !value.trim().is_empty() {
+                    metadata.insert(key, value.trim().to_string());
+                }
+            }
+        }
+        if metadata.is_empty() {
+            metadata.insert("comment".to_string(), annotation.to_string());
+        }
+        metadata
+    }
+}
+
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<AuHeader> {
+    reader.seek(SeekFrom::Start(0))?;
+    let header: [u8; 24] = read_array(reader)?;
+    if &header[0..4] != MAGIC {
+        return Err(ParseError::Invalid("missing .snd magic".to_string()));
+    }
+    let field = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap());
+    let data_offset = field(4);
+    if data_offset < HEADER_LEN {
+        return Err(ParseError::Invalid(format!(
+            "invalid AU data offset {}",
+            data_offset
+        )));
+    }
+
+    let annotation = read_vec(
+        reader,
+        (data_offset - HEADER_LEN) as u64,
+        MAX_ANNOTATION_SIZE,
+    )?;
+    let end = annotation
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(annotation.len());
+
+    Ok(AuHeader {
+        data_offset,
+        data_size: Some(field(8)).filter(|size| *size != UNKNOWN_SIZE),
+        encoding: field(12),
+        sample_rate: field(16),
+        channels: field(20),
+        annotation: String::from_utf8_lossy(&annotation[..end]).to_string(),
+    })
+}
+
+/// Sun AUファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let header = read(reader)?;
+    let (codec_name, codec_long_name, bits) = header.codec().ok_or_else(|| {
+        ParseError::Invalid(format!("unsupported AU encoding {}", header.encoding))
+    })?;
+    if header.sample_rate == 0 || header.channels == 0 {
+        return Err(ParseError::Invalid("invalid AU header".to_string()));
+    }
+
+    // データサイズ不明（ストリーミング出力）の場合はファイル末尾まで
+    let file_len = stream_len(reader)?;
+    let available = file_len.saturating_sub(header.data_offset as u64);
+    let data_size = header
+        .data_size
+        .map_or(available, |size| (size as u64).min(available));
+
+    let bit_rate = header.sample_rate as u64 * header.channels as u64 * bits as u64;
+
+    audio_info.format_name = "au".to_string();
+    audio_info.format_long_name = "Sun AU".to_string();
+    audio_info.codec_name = codec_name.to_string();
+    audio_info.codec_long_name = codec_long_name.to_string();
+    audio_info.sample_rate = header.sample_rate as i32;
+    audio_info.channels = header.channels as i32;
+    audio_info.bits_per_sample = bits as i32;
+    audio_info.channel_layout = default_channel_layout(header.channels as u16);
+    audio_info.bit_rate = bit_rate as i64;
+    audio_info.duration_seconds = data_size as f64 * 8.0 / bit_rate as f64;
+    audio_info
+        .codec_params
+        .insert("encoding".to_string(), header.encoding.to_string());
+    audio_info.metadata = header.metadata();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn au_file(encoding: u32, data_size: u32, annotation: &[u8], data_len: usize) -> Vec<u8> {
+        let mut file = MAGIC.to_vec();
+        let offset = HEADER_LEN + annotation.len() as u32;
+        for value in [offset, data_size, encoding, 8000, 1] {
+            file.extend_from_slice(&value.to_be_bytes());
+        }
+        file.extend_from_slice(annotation);
+        file.resize(file.len() + data_len, 0);
+        file
+    }
+
+    #[test]
+    fn test_au_mulaw_with_annotation() {
+        let file = au_file(1, 16000, b"title=Greeting\nartist=Front Desk\0\0\0", 16000);
+
+        let mut info = AudioInfo::new("greeting.au".into());
+        probe(&mut Cursor::new(file), &mut info).unwrap();
+        assert_eq!(info.codec_name, "pcm_mulaw");
+        assert_eq!(info.sample_rate, 8000);
+        assert_eq!(info.channel_layout, "mono");
+        assert_eq!(info.bit_rate, 64000);
+        assert_eq!(info.duration_seconds, 2.0);
+        assert_eq!(info.metadata["title"], "Greeting");
+        assert_eq!(info.metadata["artist"], "Front Desk");
+    }
+
+    #[test]
+    fn test_au_unknown_size_and_plain_annotation() {
+        let file = au_file(3, UNKNOWN_SIZE, b"recorded by sox\0", 8000);
+
+        let mut info = AudioInfo::new("beep.snd".into());
+        probe(&mut Cursor::new(file), &mut info).unwrap();
+        assert_eq!(info.codec_name, "pcm_s16be");
+        assert_eq!(info.duration_seconds, 0.5);
+        assert_eq!(info.metadata["comment"], "recorded by sox");
+    }
+}
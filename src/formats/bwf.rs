@@ -0,0 +1,633 @@
+// Broadcast Wave (EBU Tech 3285) の拡張チャンクパーサー
+//
+// `bext`（放送用拡張情報）・`iXML`（ロケ録音のメタデータ）・`cue `/`adtl`（マーカー）・
+// `LIST/INFO`（RIFFの汎用タグ）、BW64の`chna`/`axml`（ADM）を扱う。チャンクの走査自体はwavモジュールが行う。
+
+use super::le_u32_at;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// `bext`チャンクのバージョン0部分の固定長（Coding Historyを除く）
+const BEXT_MIN_SIZE: usize = 602;
+
+/// WAVファイルの放送用メタデータ
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BroadcastWave {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bext: Option<BextChunk>,
+    /// TimeReferenceから求めた開始タイムコード
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timecode_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ixml: Option<IxmlInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adm: Option<AdmInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<CueMarker>,
+}
+
+/// `bext`チャンク（Broadcast Audio Extension）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BextChunk {
+    pub version: u16,
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    /// yyyy-mm-dd
+    pub origination_date: String,
+    /// hh:mm:ss
+    pub origination_time: String,
+    /// 深夜0時からのサンプル数
+    pub time_reference: u64,
+    /// SMPTE 330M UMID（16進数、バージョン1以降）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub umid: Option<String>,
+    /// ラウドネス値（バージョン2以降）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loudness: Option<Loudness>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub coding_history: String,
+}
+
+/// EBU R128のラウドネス値（LUFS / LU / dBTP）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Loudness {
+    pub integrated: f64,
+    pub range: f64,
+    pub max_true_peak: f64,
+    pub max_momentary: f64,
+    pub max_short_term: f64,
+}
+
+/// iXMLの主な項目
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IxmlInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub take: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tape: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circled: Option<bool>,
+    /// "25/1"・"30000/1001"など
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timecode_rate: Option<String>,
+    /// "DF"（ドロップフレーム）または"NDF"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timecode_flag: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tracks: Vec<IxmlTrack>,
+}
+
+/// iXMLのTRACK_LIST内のトラック
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IxmlTrack {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_index: Option<u32>,
+    pub name: String,
+}
+
+/// ADM（ITU-R BS.2076）によるオブジェクトベース音声の構成
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdmInfo {
+    /// `chna`のトラック数
+    pub track_count: u16,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tracks: Vec<AdmTrack>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub programmes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<String>,
+    /// audioPackFormatの種類（DirectSpeakers・Objects・HOAなど）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pack_types: Vec<String>,
+}
+
+/// `chna`のトラックとADM要素の対応
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdmTrack {
+    pub track_index: u16,
+    pub uid: String,
+    pub track_format: String,
+    pub pack_format: String,
+}
+
+/// `cue `チャンクのマーカー（`adtl`のラベル・注記を含む）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CueMarker {
+    pub id: u32,
+    /// 先頭からのサンプル位置
+    pub position: u64,
+    pub time_seconds: f64,
+    /// `ltxt`で指定された区間の長さ（サンプル数）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// NUL終端・NUL詰めの固定長文字列
+fn fixed_str(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+/// RIFFのサブチャンクを走査する（ID, データ）
+fn subchunks(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let id = data.get(pos..pos + 4)?;
+        let size = le_u32_at(data, pos + 4)? as usize;
+        let body = data.get(pos + 8..(pos + 8 + size).min(data.len()))?;
+        pos += 8 + size + (size & 1);
+        Some((id, body))
+    })
+}
+
+impl BextChunk {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < BEXT_MIN_SIZE {
+            return None;
+        }
+        let version = u16::from_le_bytes([data[346], data[347]]);
+        let time_reference = le_u32_at(data, 338)? as u64 | (le_u32_at(data, 342)? as u64) << 32;
+
+        let umid = Some(&data[348..412])
+            .filter(|umid| version >= 1 && umid.iter().any(|b| *b != 0))
+            .map(|umid| {
+                // 基本UMIDは32バイトで、後半32バイトは拡張UMIDのみ使う
+                let len = if umid[32..].iter().all(|b| *b == 0) {
+                    32
+                } else {
+                    64
+                };
+                umid[..len].iter().map(|b| format!("{:02x}", b)).collect()
+            });
+
+        let loudness = (version >= 2).then(|| {
+            let value = |i: usize| i16::from_le_bytes([data[412 + i * 2], data[413 + i * 2]]);
+            Loudness {
+                integrated: value(0) as f64 / 100.0,
+                range: value(1) as f64 / 100.0,
+                max_true_peak: value(2) as f64 / 100.0,
+                max_momentary: value(3) as f64 / 100.0,
+                max_short_term: value(4) as f64 / 100.0,
+            }
+        });
+
+        Some(Self {
+            version,
+            description: fixed_str(&data[0..256]),
+            originator: fixed_str(&data[256..288]),
+            originator_reference: fixed_str(&data[288..320]),
+            origination_date: fixed_str(&data[320..330]),
+            origination_time: fixed_str(&data[330..338]),
+            time_reference,
+            umid,
+            loudness,
+            coding_history: fixed_str(&data[BEXT_MIN_SIZE..]),
+        })
+    }
+}
+
+/// XMLの実体参照を戻す
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// `<TAG>...</TAG>`の中身を順に取り出す（iXMLは属性も名前空間も使わない）
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let body = &rest[start + open.len()..];
+        let Some(end) = body.find(&close) else {
+            break;
+        };
+        found.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    found
+}
+
+fn element_text(xml: &str, tag: &str) -> Option<String> {
+    elements(xml, tag)
+        .first()
+        .map(|text| unescape_xml(text.trim()))
+        .filter(|text| !text.is_empty())
+}
+
+impl IxmlInfo {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let xml = String::from_utf8_lossy(data);
+        if !xml.contains("<BWFXML>") {
+            return None;
+        }
+        // TIMECODE_RATEなどはSPEED要素の中にある
+        let speed = elements(&xml, "SPEED").first().copied().unwrap_or_default();
+        let tracks = elements(&xml, "TRACK_LIST")
+            .first()
+            .map(|list| {
+                elements(list, "TRACK")
+                    .into_iter()
+                    .filter_map(|track| {
+                        Some(IxmlTrack {
+                            channel_index: element_text(track, "CHANNEL_INDEX")
+                                .and_then(|index| index.parse().ok()),
+                            name: element_text(track, "NAME")?,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            project: element_text(&xml, "PROJECT"),
+            scene: element_text(&xml, "SCENE"),
+            take: element_text(&xml, "TAKE"),
+            tape: element_text(&xml, "TAPE"),
+            note: element_text(&xml, "NOTE"),
+            circled: element_text(&xml, "CIRCLED").map(|value| value.eq_ignore_ascii_case("TRUE")),
+            timecode_rate: element_text(speed, "TIMECODE_RATE"),
+            timecode_flag: element_text(speed, "TIMECODE_FLAG"),
+            tracks,
+        })
+    }
+
+    /// タイムコードのフレームレート（分子, 分母）
+    pub fn frame_rate(&self) -> Option<(u32, u32)> {
+        let (num, den) = self.timecode_rate.as_deref()?.split_once('/')?;
+        let rate = (num.trim().parse().ok()?, den.trim().parse().ok()?);
+        (rate.0 > 0 && rate.1 > 0).then_some(rate)
+    }
+
+    pub fn drop_frame(&self) -> bool {
+        self.timecode_flag
+            .as_deref()
+            .is_some_and(|flag| flag.eq_ignore_ascii_case("DF"))
+    }
+}
+
+/// `name="..."`形式の属性値を順に取り出す（重複は除く）
+fn attribute_values(xml: &str, name: &str) -> Vec<String> {
+    let pattern = format!(" {}=\"", name);
+    let mut values: Vec<String> = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&pattern) {
+        let body = &rest[start + pattern.len()..];
+        let Some(end) = body.find('"') else {
+            break;
+        };
+        let value = unescape_xml(&body[..end]);
+        if !value.is_empty() && !values.contains(&value) {
+            values.push(value);
+        }
+        rest = &body[end..];
+    }
+    values
+}
+
+impl AdmInfo {
+    pub fn parse(chna: Option<&[u8]>, axml: Option<&[u8]>) -> Self {
+        let mut adm = AdmInfo::default();
+
+        if let Some(chna) = chna.filter(|chna| chna.len() >= 4) {
+            adm.track_count = u16::from_le_bytes([chna[0], chna[1]]);
+            let uid_count = u16::from_le_bytes([chna[2], chna[3]]) as usize;
+            // trackIndex(2) UID(12) trackRef(14) packRef(11) 詰め物(1)
+            adm.tracks = chna[4..]
+                .chunks_exact(40)
+                .take(uid_count)
+                .map(|entry| AdmTrack {
+                    track_index: u16::from_le_bytes([entry[0], entry[1]]),
+                    uid: fixed_str(&entry[2..14]),
+                    track_format: fixed_str(&entry[14..28]),
+                    pack_format: fixed_str(&entry[28..39]),
+                })
+                .filter(|track| track.track_index > 0)
+                .collect();
+        }
+
+        if let Some(axml) = axml {
+            let xml = String::from_utf8_lossy(axml);
+            adm.programmes = attribute_values(&xml, "audioProgrammeName");
+            adm.contents = attribute_values(&xml, "audioContentName");
+            adm.objects = attribute_values(&xml, "audioObjectName");
+            adm.pack_types = elements_with_attributes(&xml, "audioPackFormat")
+                .into_iter()
+                .flat_map(|tag| attribute_values(tag, "typeDefinition"))
+                .fold(Vec::new(), |mut types, value| {
+                    if !types.contains(&value) {
+                        types.push(value);
+                    }
+                    types
+                });
+        }
+        adm
+    }
+}
+
+/// `<TAG ...>`の開始タグ（属性部分）を順に取り出す
+fn elements_with_attributes<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{} ", tag);
+    xml.match_indices(&open)
+        .filter_map(|(start, _)| {
+            let body = &xml[start + 1..];
+            body.find('>').map(|end| &body[tag.len()..end])
+        })
+        .collect()
+}
+
+/// `cue `チャンクの(ID, サンプル位置)の一覧
+pub fn parse_cue(data: &[u8]) -> Vec<(u32, u64)> {
+    let count = le_u32_at(data, 0).unwrap_or(0) as usize;
+    data.get(4..)
+        .unwrap_or_default()
+        .chunks_exact(24)
+        .take(count)
+        .map(|point| {
+            // dwSampleOffsetはデータチャンク先頭からのサンプル位置
+            let id = le_u32_at(point, 0).unwrap();
+            (id, le_u32_at(point, 20).unwrap() as u64)
+        })
+        .collect()
+}
+
+/// `LIST/adtl`のラベル・注記・区間長をマーカーに反映する
+pub fn apply_adtl(data: &[u8], markers: &mut [CueMarker]) {
+    for (id, body) in subchunks(data) {
+        let Some(cue_id) = le_u32_at(body, 0) else {
+            continue;
+        };
+        let Some(marker) = markers.iter_mut().find(|marker| marker.id == cue_id) else {
+            continue;
+        };
+        match id {
+            b"labl" => marker.label = Some(fixed_str(&body[4..])).filter(|s| !s.is_empty()),
+            b"note" => marker.note = Some(fixed_str(&body[4..])).filter(|s| !s.is_empty()),
+            b"ltxt" => {
+                marker.length = le_u32_at(body, 4).filter(|len| *len > 0);
+                if marker.label.is_none() {
+                    marker.label = body.get(20..).map(fixed_str).filter(|s| !s.is_empty());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `LIST/INFO`の項目IDをffprobe互換のキーに変換する
+fn info_key(id: &[u8]) -> String {
+    match id {
+        b"IART" => "artist",
+        b"ICMT" => "comment",
+        b"ICOP" => "copyright",
+        b"ICRD" => "date",
+        b"IGNR" => "genre",
+        b"ILNG" => "language",
+        b"INAM" => "title",
+        b"IPRD" => "album",
+        b"IPRT" | b"ITRK" => "track",
+        b"ISFT" => "encoder",
+        b"ISMP" => "timecode",
+        b"ITCH" => "encoded_by",
+        // 未知の項目はFFmpegと同様にIDのまま出力する
+        other => return String::from_utf8_lossy(other).trim().to_string(),
+    }
+    .to_string()
+}
+
+/// `LIST/INFO`をメタデータに変換する
+pub fn parse_info(data: &[u8]) -> HashMap<String, String> {
+    subchunks(data)
+        .map(|(id, body)| (info_key(id), fixed_str(body)))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+        .collect()
+}
+
+/// サンプル位置をタイムコードに変換する
+///
+/// フレームレートが分かる場合は"HH:MM:SS:FF"（ドロップフレームは"HH:MM:SS;FF"）、
+/// 分からない場合は"HH:MM:SS.mmm"を返す。
+pub fn timecode(
+    samples: u64,
+    sample_rate: u32,
+    frame_rate: Option<(u32, u32)>,
+    drop_frame: bool,
+) -> String {
+    if sample_rate == 0 {
+        return String::new();
+    }
+    let Some((num, den)) = frame_rate else {
+        let millis = samples as u128 * 1000 / sample_rate as u128;
+        let secs = millis / 1000;
+        return format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            millis % 1000
+        );
+    };
+
+    // 29.97fpsなどは公称フレームレート（30）でラベル付けする
+    let nominal = (num as u64).div_ceil(den as u64);
+    let mut frames = (samples as u128 * num as u128 / (sample_rate as u128 * den as u128)) as u64;
+    let drop_frame = drop_frame && nominal % 30 == 0;
+    if drop_frame {
+        // 10分ごとを除く毎分、先頭のフレーム番号を(公称/15)個飛ばす
+        let dropped = nominal / 15;
+        let per_ten_minutes = nominal * 600 - dropped * 9;
+        let per_minute = nominal * 60 - dropped;
+        let tens = frames / per_ten_minutes;
+        let rest = frames % per_ten_minutes;
+        frames += dropped * 9 * tens;
+        if rest > dropped {
+            frames += dropped * ((rest - dropped) / per_minute);
+        }
+    }
+
+    let secs = frames / nominal;
+    format!(
+        "{:02}:{:02}:{:02}{}{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        if drop_frame { ';' } else { ':' },
+        frames % nominal
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bext(version: u16, time_reference: u64) -> Vec<u8> {
+        let mut data = vec![0u8; BEXT_MIN_SIZE];
+        data[..12].copy_from_slice(b"Scene 12 mix");
+        data[256..263].copy_from_slice(b"Sound D");
+        data[320..330].copy_from_slice(b"2024-05-01");
+        data[330..338].copy_from_slice(b"14:30:00");
+        data[338..346].copy_from_slice(&time_reference.to_le_bytes());
+        data[346..348].copy_from_slice(&version.to_le_bytes());
+        data[348..380].fill(0xAB);
+        data[412..414].copy_from_slice(&(-2300i16).to_le_bytes());
+        data[414..416].copy_from_slice(&650i16.to_le_bytes());
+        data.extend_from_slice(b"A=PCM,F=48000,W=24,M=stereo\r\n");
+        data
+    }
+
+    #[test]
+    fn test_bext_v2() {
+        let chunk = BextChunk::parse(&bext(2, 48000 * 3600)).unwrap();
+        assert_eq!(chunk.description, "Scene 12 mix");
+        assert_eq!(chunk.originator, "Sound D");
+        assert_eq!(chunk.origination_date, "2024-05-01");
+        assert_eq!(chunk.time_reference, 172_800_000);
+        assert_eq!(chunk.umid.as_deref(), Some("ab".repeat(32).as_str()));
+        let loudness = chunk.loudness.unwrap();
+        assert_eq!(loudness.integrated, -23.0);
+        assert_eq!(loudness.range, 6.5);
+        assert_eq!(chunk.coding_history, "A=PCM,F=48000,W=24,M=stereo");
+
+        let v0 = BextChunk::parse(&bext(0, 0)).unwrap();
+        assert_eq!(v0.umid, None);
+        assert_eq!(v0.loudness, None);
+        assert!(BextChunk::parse(&[0; 100]).is_none());
+    }
+
+    #[test]
+    fn test_ixml() {
+        let xml = br#"<?xml version="1.0"?><BWFXML><PROJECT>Night &amp; Day</PROJECT>
+            <SCENE>12A</SCENE><TAKE>3</TAKE><TAPE>DAY02</TAPE><CIRCLED>TRUE</CIRCLED>
+            <SPEED><TIMECODE_RATE>30000/1001</TIMECODE_RATE><TIMECODE_FLAG>DF</TIMECODE_FLAG></SPEED>
+            <TRACK_LIST><TRACK_COUNT>2</TRACK_COUNT>
+            <TRACK><CHANNEL_INDEX>1</CHANNEL_INDEX><NAME>Boom</NAME></TRACK>
+            <TRACK><CHANNEL_INDEX>2</CHANNEL_INDEX><NAME>Lav 1</NAME></TRACK>
+            </TRACK_LIST></BWFXML>"#;
+        let ixml = IxmlInfo::parse(xml).unwrap();
+        assert_eq!(ixml.project.as_deref(), Some("Night & Day"));
+        assert_eq!(ixml.scene.as_deref(), Some("12A"));
+        assert_eq!(ixml.take.as_deref(), Some("3"));
+        assert_eq!(ixml.circled, Some(true));
+        assert_eq!(ixml.frame_rate(), Some((30000, 1001)));
+        assert!(ixml.drop_frame());
+        assert_eq!(ixml.tracks.len(), 2);
+        assert_eq!(ixml.tracks[1].name, "Lav 1");
+        assert_eq!(ixml.tracks[1].channel_index, Some(2));
+    }
+
+    #[test]
+    fn test_timecode() {
+        let one_hour = 48000 * 3600;
+        assert_eq!(
+            timecode(one_hour + 24000, 48000, None, false),
+            "01:00:00.500"
+        );
+        assert_eq!(
+            timecode(one_hour + 24000, 48000, Some((25, 1)), false),
+            "01:00:00:12"
+        );
+        // 29.97DFでは実時間1時間 = 107892フレーム = 01:00:00;00
+        assert_eq!(
+            timecode(one_hour, 48000, Some((30000, 1001)), true),
+            "01:00:00;00"
+        );
+        // 1分ちょうど（1800フレーム）は00:01:00;02になる
+        let minute = 1800 * 48000 * 1001 / 30000;
+        assert_eq!(
+            timecode(minute, 48000, Some((30000, 1001)), true),
+            "00:01:00;02"
+        );
+    }
+
+    #[test]
+    fn test_cue_and_adtl() {
+        let mut cue = 2u32.to_le_bytes().to_vec();
+        for (id, offset) in [(1u32, 48000u32), (2, 96000)] {
+            cue.extend_from_slice(&id.to_le_bytes());
+            cue.extend_from_slice(&0u32.to_le_bytes());
+            cue.extend_from_slice(b"data");
+            cue.extend_from_slice(&[0; 8]);
+            cue.extend_from_slice(&offset.to_le_bytes());
+        }
+        let points = parse_cue(&cue);
+        assert_eq!(points, vec![(1, 48000), (2, 96000)]);
+
+        let mut markers: Vec<CueMarker> = points
+            .into_iter()
+            .map(|(id, position)| CueMarker {
+                id,
+                position,
+                ..Default::default()
+            })
+            .collect();
+        let mut adtl = b"labl\x08\x00\x00\x00\x01\x00\x00\x00Cut\x00".to_vec();
+        let mut ltxt = 2u32.to_le_bytes().to_vec();
+        ltxt.extend_from_slice(&4800u32.to_le_bytes());
+        ltxt.extend_from_slice(b"rgn \0\0\0\0\0\0\0\0Region\0");
+        adtl.extend_from_slice(b"ltxt");
+        adtl.extend_from_slice(&(ltxt.len() as u32).to_le_bytes());
+        adtl.extend(ltxt);
+        adtl.push(0);
+        apply_adtl(&adtl, &mut markers);
+        assert_eq!(markers[0].label.as_deref(), Some("Cut"));
+        assert_eq!(markers[1].label.as_deref(), Some("Region"));
+        assert_eq!(markers[1].length, Some(4800));
+    }
+
+    #[test]
+    fn test_adm_chna_axml() {
+        let mut chna = 2u16.to_le_bytes().to_vec();
+        chna.extend_from_slice(&2u16.to_le_bytes());
+        for (index, uid) in [(1u16, b"ATU_00000001"), (2, b"ATU_00000002")] {
+            chna.extend_from_slice(&index.to_le_bytes());
+            chna.extend_from_slice(uid);
+            chna.extend_from_slice(b"AT_00031001_01");
+            chna.extend_from_slice(b"AP_00031001");
+            chna.push(0);
+        }
+        let axml = br#"<ebuCoreMain><audioFormatExtended>
+            <audioProgramme audioProgrammeID="APR_1001" audioProgrammeName="Main Mix">
+            <audioContent audioContentID="ACO_1001" audioContentName="Dialogue">
+            <audioObject audioObjectID="AO_1001" audioObjectName="Narrator">
+            <audioPackFormat audioPackFormatID="AP_00031001" typeLabel="0003" typeDefinition="Objects">
+            <audioChannelFormat audioChannelFormatID="AC_00031001" typeDefinition="Objects">
+            </audioFormatExtended></ebuCoreMain>"#;
+
+        let adm = AdmInfo::parse(Some(&chna), Some(axml));
+        assert_eq!(adm.track_count, 2);
+        assert_eq!(adm.tracks.len(), 2);
+        assert_eq!(adm.tracks[1].uid, "ATU_00000002");
+        assert_eq!(adm.tracks[1].pack_format, "AP_00031001");
+        assert_eq!(adm.programmes, vec!["Main Mix"]);
+        assert_eq!(adm.contents, vec!["Dialogue"]);
+        assert_eq!(adm.objects, vec!["Narrator"]);
+        assert_eq!(adm.pack_types, vec!["Objects"]);
+    }
+
+    #[test]
+    fn test_info_list() {
+        let info = b"INAM\x06\x00\x00\x00Intro\x00IART\x04\x00\x00\x00Band\
+                     IENG\x03\x00\x00\x00Kim\x00";
+        let metadata = parse_info(info);
+        assert_eq!(metadata["title"], "Intro");
+        assert_eq!(metadata["artist"], "Band");
+        assert_eq!(metadata["IENG"], "Kim");
+    }
+}
@@ -0,0 +1,203 @@
+// DSF (DSD Stream File) パーサー
+//
+// "DSD "・"fmt "・"data"の3チャンクで構成され、すべてリトルエンディアン。
+// メタデータはDSDチャンクのポインターが指す位置のID3v2タグに格納される。
+
+use super::{
+    channel_layout_from_mask, default_channel_layout, id3, merge_metadata, read_array, ParseError,
+    ParseResult,
+};
+use crate::AudioInfo;
+use std::io::{Read, Seek, SeekFrom};
+
+/// "fmt "チャンクのチャンネルタイプ → スピーカーマスク
+const CHANNEL_TYPE_MASKS: [u32; 7] = [0x4, 0x3, 0x7, 0x33, 0xF, 0x37, 0x3F];
+
+/// DSDのサンプリング周波数の呼称（DSD64 = 2.8224MHzなど）
+pub(crate) fn dsd_rate_name(sample_rate: u32) -> Option<String> {
+    [44100, 48000]
+        .into_iter()
+        .find(|base| sample_rate % base == 0 && sample_rate / base >= 32)
+        .map(|base| format!("DSD{}", sample_rate / base))
+}
+
+/// "fmt "チャンク
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsfFormat {
+    pub format_id: u32,
+    pub channel_type: u32,
+    pub channels: u32,
+    /// 1ビットのサンプリング周波数（Hz）
+    pub sample_rate: u32,
+    /// 1（LSBファースト）または8（MSBファースト）
+    pub bits_per_sample: u32,
+    /// チャンネルあたりのサンプル数
+    pub sample_count: u64,
+    pub block_size: u32,
+}
+
+impl DsfFormat {
+    fn parse(data: &[u8; 40]) -> Self {
+        let u32_at = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+        Self {
+            format_id: u32_at(4),
+            channel_type: u32_at(8),
+            channels: u32_at(12),
+            sample_rate: u32_at(16),
+            bits_per_sample: u32_at(20),
+            sample_count: u64::from_le_bytes(data[24..32].try_into().unwrap()),
+            block_size: u32_at(32),
+        }
+    }
+
+    pub fn channel_layout(&self) -> String {
+        match CHANNEL_TYPE_MASKS.get((self.channel_type as usize).wrapping_sub(1)) {
+            Some(mask) => channel_layout_from_mask(*mask, self.channels as u16),
+            None => default_channel_layout(self.channels as u16),
+        }
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        if self.sample_rate == 0 {
+            0.0
+        } else {
+            self.sample_count as f64 / self.sample_rate as f64
+        }
+    }
+}
+
+/// DSFファイルの解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct DsfFile {
+    pub format: DsfFormat,
+    pub metadata_offset: Option<u64>,
+}
+
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<DsfFile> {
+    reader.seek(SeekFrom::Start(0))?;
+    let dsd: [u8; 28] = read_array(reader)?;
+    if &dsd[0..4] != b"DSD " {
+        return Err(ParseError::Invalid("missing DSF 'DSD ' chunk".to_string()));
+    }
+    let dsd_size = u64::from_le_bytes(dsd[4..12].try_into().unwrap());
+    let metadata_offset = u64::from_le_bytes(dsd[20..28].try_into().unwrap());
+
+    reader.seek(SeekFrom::Start(dsd_size))?;
+    let header: [u8; 12] = read_array(reader)?;
+    if &header[0..4] != b"fmt " {
+        return Err(ParseError::Invalid("missing DSF 'fmt ' chunk".to_string()));
+    }
+    let format = DsfFormat::parse(&read_array(reader)?);
+    if format.format_id != 0 {
+        return Err(ParseError::Invalid(format!(
+            "unsupported DSF format id {}",
+            format.format_id
+        )));
+    }
+    if format.channels == 0 || format.sample_rate == 0 {
+        return Err(ParseError::Invalid("invalid DSF fmt chunk".to_string()));
+    }
+
+    Ok(DsfFile {
+        format,
+        metadata_offset: Some(metadata_offset).filter(|offset| *offset != 0),
+    })
+}
+
+/// DSFファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let dsf = read(reader)?;
+    let format = &dsf.format;
+
+    audio_info.format_name = "dsf".to_string();
+    audio_info.format_long_name = "DSD Stream File (DSF)".to_string();
+    if format.bits_per_sample == 8 {
+        audio_info.codec_name = "dsd_msbf_planar".to_string();
+        audio_info.codec_long_name =
+            "DSD (Direct Stream Digital), most significant bit first, planar".to_string();
+    } else {
+        audio_info.codec_name = "dsd_lsbf_planar".to_string();
+        audio_info.codec_long_name =
+            "DSD (Direct Stream Digital), least significant bit first, planar".to_string();
+    }
+    // ffprobeは1/8のレートを報告するが、ここではDSD本来の1ビットのレートを使う
+    audio_info.sample_rate = format.sample_rate as i32;
+    audio_info.channels = format.channels as i32;
+    audio_info.bits_per_sample = 1;
+    audio_info.channel_layout = format.channel_layout();
+    audio_info.duration_seconds = format.duration_seconds();
+    audio_info.bit_rate = format.sample_rate as i64 * format.channels as i64;
+
+    if let Some(name) = dsd_rate_name(format.sample_rate) {
+        audio_info.codec_params.insert("dsd_rate".to_string(), name);
+    }
+    audio_info
+        .codec_params
+        .insert("block_size".to_string(), format.block_size.to_string());
+
+    if let Some(offset) = dsf.metadata_offset {
+        if let Some(tag) = id3::read_v2_at(reader, offset)? {
+            audio_info.chapters = tag.chapter_list();
+            audio_info.artwork = tag.artwork();
+            merge_metadata(&mut audio_info.metadata, tag.entries);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_dsf_dsd128_with_id3() {
+        let sample_rate: u32 = 5_644_800;
+        let sample_count = sample_rate as u64 * 3;
+
+        let mut fmt = Vec::new();
+        for value in [1u32, 0, 2, 2, sample_rate, 1] {
+            fmt.extend_from_slice(&value.to_le_bytes());
+        }
+        fmt.extend_from_slice(&sample_count.to_le_bytes());
+        fmt.extend_from_slice(&4096u32.to_le_bytes());
+        fmt.extend_from_slice(&0u32.to_le_bytes());
+
+        let data_len = 8192u64;
+        let metadata_offset = 28 + 52 + 12 + data_len;
+        let mut tag = vec![b'I', b'D', b'3', 3, 0, 0, 0, 0, 0, 18];
+        tag.extend_from_slice(b"TIT2\x00\x00\x00\x08\x00\x00\x00Sonata!");
+
+        let mut file = b"DSD ".to_vec();
+        file.extend_from_slice(&28u64.to_le_bytes());
+        file.extend_from_slice(&(metadata_offset + tag.len() as u64).to_le_bytes());
+        file.extend_from_slice(&metadata_offset.to_le_bytes());
+        file.extend_from_slice(b"fmt ");
+        file.extend_from_slice(&52u64.to_le_bytes());
+        file.extend(fmt);
+        file.extend_from_slice(b"data");
+        file.extend_from_slice(&(12 + data_len).to_le_bytes());
+        file.resize(metadata_offset as usize, 0x69);
+        file.extend(tag);
+
+        let mut info = AudioInfo::new("sonata.dsf".into());
+        probe(&mut Cursor::new(file), &mut info).unwrap();
+        assert_eq!(info.codec_name, "dsd_lsbf_planar");
+        assert_eq!(info.sample_rate, 5_644_800);
+        assert_eq!(info.bits_per_sample, 1);
+        assert_eq!(info.channel_layout, "stereo");
+        assert_eq!(info.duration_seconds, 3.0);
+        assert_eq!(info.bit_rate, 11_289_600);
+        assert_eq!(info.codec_params["dsd_rate"], "DSD128");
+        assert_eq!(info.metadata["title"], "Sonata!");
+    }
+
+    #[test]
+    fn test_dsd_rate_names() {
+        assert_eq!(dsd_rate_name(2_822_400).as_deref(), Some("DSD64"));
+        assert_eq!(dsd_rate_name(11_289_600).as_deref(), Some("DSD256"));
+        assert_eq!(dsd_rate_name(3_072_000).as_deref(), Some("DSD64"));
+        assert_eq!(dsd_rate_name(44_100), None);
+    }
+}
@@ -0,0 +1,253 @@
+// DTS Coherent Acoustics コアストリームパーサー
+//
+// 16ビット・ビッグエンディアン形式（同期ワード0x7FFE8001）のみ対応する。
+// 14ビット形式やリトルエンディアン形式はffprobeに任せる。
+
+use super::{
+    channel_layout_from_mask, id3, scan_frames, stream_len, BitReader, FrameScan, ParseError,
+    ParseResult, Window,
+};
+use crate::AudioInfo;
+use std::io::{Read, Seek};
+
+const SYNC_WORD: [u8; 4] = [0x7F, 0xFE, 0x80, 0x01];
+
+/// フレームヘッダーの解析に必要なバイト数（87ビット）
+const HEADER_LEN: usize = 11;
+
+/// 最初のフレームを探す範囲
+const MAX_SYNC_SEARCH: u64 = 64 * 1024;
+
+/// SFREQ → サンプリングレート（0は無効値）
+const SAMPLE_RATES: [u32; 16] = [
+    0, 8000, 16000, 32000, 0, 0, 11025, 22050, 44100, 0, 0, 12000, 24000, 48000, 0, 0,
+];
+
+/// RATE → 公称ビットレート（29以降はopen/variable/lossless）
+const BIT_RATES: [u32; 29] = [
+    32000, 56000, 64000, 96000, 112000, 128000, 192000, 224000, 256000, 320000, 384000, 448000,
+    512000, 576000, 640000, 768000, 896000, 1024000, 1152000, 1280000, 1344000, 1408000, 1411200,
+    1472000, 1536000, 1920000, 2048000, 3072000, 3840000,
+];
+
+/// AMODEごとのチャンネル数
+const AMODE_CHANNELS: [u8; 10] = [1, 2, 2, 2, 2, 3, 3, 4, 4, 5];
+
+/// AMODEごとのスピーカーマスク
+const AMODE_MASKS: [u32; 10] = [0x4, 0x3, 0x3, 0x3, 0x3, 0x7, 0x103, 0x107, 0x603, 0x607];
+
+/// DTSコアフレームヘッダー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoreFrame {
+    /// NBLKS（PCMサンプルブロック数 - 1）
+    pub blocks: u8,
+    /// バイト単位のフレーム長（FSIZE + 1）
+    pub frame_size: u64,
+    pub amode: u8,
+    pub sample_rate: u32,
+    pub rate_index: u8,
+    /// 拡張オーディオ（XCh/X96など）の種類
+    pub ext_audio: Option<u8>,
+    /// LFF（0: LFEなし）
+    pub lff: u8,
+}
+
+impl CoreFrame {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || bytes[0..4] != SYNC_WORD {
+            return None;
+        }
+        let mut bits = BitReader::new(&bytes[4..]);
+        bits.skip(1 + 5 + 1)?; // FTYPE, SHORT, CPF
+        let blocks = bits.read(7)? as u8;
+        let frame_size = bits.read(14)? as u64 + 1;
+        let amode = bits.read(6)? as u8;
+        let sample_rate = SAMPLE_RATES[bits.read(4)? as usize];
+        let rate_index = bits.read(5)? as u8;
+        bits.skip(5)?; // FixedBit, DYNF, TIMEF, AUXF, HDCD
+        let ext_audio_id = bits.read(3)? as u8;
+        let ext_audio = bits.read(1)? == 1;
+        bits.skip(1)?; // ASPF
+        let lff = bits.read(2)? as u8;
+
+        // NBLKSは5以上、FSIZEは95以上が規格上の最小値
+        if blocks < 5
+            || frame_size < 96
+            || sample_rate == 0
+            || amode as usize >= AMODE_CHANNELS.len()
+        {
+            return None;
+        }
+
+        Some(Self {
+            blocks,
+            frame_size,
+            amode,
+            sample_rate,
+            rate_index,
+            ext_audio: ext_audio.then_some(ext_audio_id),
+            lff,
+        })
+    }
+
+    pub fn samples(&self) -> u64 {
+        (self.blocks as u64 + 1) * 32
+    }
+
+    pub fn has_lfe(&self) -> bool {
+        self.lff != 0
+    }
+
+    pub fn channels(&self) -> u16 {
+        AMODE_CHANNELS[self.amode as usize] as u16 + self.has_lfe() as u16
+    }
+
+    pub fn channel_layout(&self) -> String {
+        let lfe = if self.has_lfe() { 0x8 } else { 0 };
+        channel_layout_from_mask(AMODE_MASKS[self.amode as usize] | lfe, self.channels())
+    }
+
+    pub fn nominal_bit_rate(&self) -> Option<i64> {
+        BIT_RATES
+            .get(self.rate_index as usize)
+            .map(|&rate| rate as i64)
+    }
+
+    /// ffprobeと同じプロファイル名
+    pub fn profile(&self) -> &'static str {
+        match self.ext_audio {
+            Some(0) | Some(6) => "DTS-ES",
+            Some(2) => "DTS 96/24",
+            _ => "DTS",
+        }
+    }
+}
+
+/// DTSストリーム全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct DtsStream {
+    pub first_frame: CoreFrame,
+    pub scan: FrameScan,
+}
+
+impl DtsStream {
+    pub fn duration_seconds(&self) -> f64 {
+        self.scan.sample_count as f64 / self.first_frame.sample_rate as f64
+    }
+
+    pub fn bit_rate(&self) -> i64 {
+        if let Some(bit_rate) = self.first_frame.nominal_bit_rate() {
+            return bit_rate;
+        }
+        let duration = self.duration_seconds();
+        if duration > 0.0 {
+            (self.scan.byte_count as f64 * 8.0 / duration).round() as i64
+        } else {
+            0
+        }
+    }
+}
+
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<DtsStream> {
+    let file_len = stream_len(reader)?;
+    let start = id3::skip_v2(reader, file_len)?;
+
+    let scan = scan_frames(
+        reader,
+        start,
+        file_len,
+        HEADER_LEN,
+        MAX_SYNC_SEARCH,
+        |bytes| CoreFrame::parse(bytes).map(|f| (f.frame_size, f.samples())),
+    )?
+    .ok_or_else(|| ParseError::Invalid("no DTS core frame found".to_string()))?;
+
+    let first_frame = Window::new(reader)
+        .get(scan.first_offset, HEADER_LEN)?
+        .and_then(CoreFrame::parse)
+        .ok_or_else(|| ParseError::Invalid("no DTS core frame found".to_string()))?;
+
+    Ok(DtsStream { first_frame, scan })
+}
+
+/// DTSファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let stream = read(reader)?;
+    let frame = &stream.first_frame;
+
+    audio_info.format_name = "dts".to_string();
+    audio_info.format_long_name = "raw DTS".to_string();
+    audio_info.codec_name = "dts".to_string();
+    audio_info.codec_long_name = "DCA (DTS Coherent Acoustics)".to_string();
+    audio_info.sample_rate = frame.sample_rate as i32;
+    audio_info.channels = frame.channels() as i32;
+    audio_info.channel_layout = frame.channel_layout();
+    audio_info.duration_seconds = stream.duration_seconds();
+    audio_info.bit_rate = stream.bit_rate();
+
+    let params = &mut audio_info.codec_params;
+    params.insert("profile".to_string(), frame.profile().to_string());
+    params.insert("amode".to_string(), frame.amode.to_string());
+    params.insert("lfe".to_string(), (frame.has_lfe() as u8).to_string());
+    params.insert("frames".to_string(), stream.scan.frame_count.to_string());
+
+    audio_info.metadata = id3::read_metadata(reader)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// 48kHz・1536kbps・3/2+LFE・512サンプルのコアフレーム
+    fn core_frame() -> Vec<u8> {
+        let frame_size: u64 = 2013;
+        let mut fields: Vec<(u64, usize)> = vec![
+            (1, 1),  // FTYPE
+            (31, 5), // SHORT
+            (0, 1),  // CPF
+            (15, 7), // NBLKS
+            (frame_size - 1, 14),
+            (9, 6),  // AMODE
+            (13, 4), // SFREQ
+            (24, 5), // RATE
+            (0, 5),
+            (0, 3),
+            (0, 1),
+            (0, 1),
+            (1, 2), // LFF
+        ];
+        fields.push((0, 7)); // バイト境界まで埋める
+
+        let mut frame = SYNC_WORD.to_vec();
+        let (mut acc, mut len) = (0u64, 0usize);
+        for (value, bits) in fields {
+            acc = (acc << bits) | value;
+            len += bits;
+            while len >= 8 {
+                len -= 8;
+                frame.push((acc >> len) as u8);
+            }
+        }
+        frame.resize(frame_size as usize, 0);
+        frame
+    }
+
+    #[test]
+    fn test_dts_core_stream() {
+        let bytes: Vec<u8> = (0..375).flat_map(|_| core_frame()).collect();
+
+        let mut info = AudioInfo::new("movie.dts".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "dts");
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.channel_layout, "5.1(side)");
+        assert_eq!(info.duration_seconds, 4.0);
+        assert_eq!(info.bit_rate, 1_536_000);
+        assert_eq!(info.codec_params["profile"], "DTS");
+        assert_eq!(info.codec_params["lfe"], "1");
+    }
+}
@@ -0,0 +1,601 @@
+// FLAC メタデータブロックリーダー
+
+use super::{
+    artwork, id3, merge_metadata, read_array, read_vec, stream_len,
+    vorbis::{self, VorbisComments},
+    ParseError, ParseResult,
+};
+use crate::{AudioInfo, Chapter};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+/// メタデータブロックの読み込み上限（ブロック長は24bitで表される）
+const MAX_BLOCK_SIZE: u64 = 16 * 1024 * 1024;
+
+const BLOCK_STREAMINFO: u8 = 0;
+const BLOCK_PADDING: u8 = 1;
+const BLOCK_SEEKTABLE: u8 = 3;
+const BLOCK_VORBIS_COMMENT: u8 = 4;
+const BLOCK_CUESHEET: u8 = 5;
+const BLOCK_PICTURE: u8 = 6;
+
+/// STREAMINFOブロック
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    pub min_block_size: u16,
+    pub max_block_size: u16,
+    pub min_frame_size: u32,
+    pub max_frame_size: u32,
+    pub sample_rate: u32,
+    pub channels: u8,
+    pub bits_per_sample: u8,
+    /// 0は不明
+    pub total_samples: u64,
+    pub md5: [u8; 16],
+}
+
+impl StreamInfo {
+    pub fn parse(data: &[u8]) -> ParseResult<Self> {
+        if data.len() < 34 {
+            return Err(ParseError::Invalid(
+                "STREAMINFO block too short".to_string(),
+            ));
+        }
+        let packed = u64::from_be_bytes(data[10..18].try_into().unwrap());
+        let info = StreamInfo {
+            min_block_size: u16::from_be_bytes([data[0], data[1]]),
+            max_block_size: u16::from_be_bytes([data[2], data[3]]),
+            min_frame_size: u32::from_be_bytes([0, data[4], data[5], data[6]]),
+            max_frame_size: u32::from_be_bytes([0, data[7], data[8], data[9]]),
+            // 20bit: サンプルレート, 3bit: チャンネル数-1, 5bit: ビット深度-1, 36bit: 総サンプル数
+            sample_rate: (packed >> 44) as u32,
+            channels: ((packed >> 41) & 0x7) as u8 + 1,
+            bits_per_sample: ((packed >> 36) & 0x1F) as u8 + 1,
+            total_samples: packed & 0xF_FFFF_FFFF,
+            md5: data[18..34].try_into().unwrap(),
+        };
+        if info.sample_rate == 0 {
+            return Err(ParseError::Invalid(
+                "STREAMINFO has zero sample rate".to_string(),
+            ));
+        }
+        Ok(info)
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        self.total_samples as f64 / self.sample_rate as f64
+    }
+
+    /// 未設定（全て0）の場合は`None`
+    pub fn md5_hex(&self) -> Option<String> {
+        if self.md5.iter().all(|b| *b == 0) {
+            None
+        } else {
+            Some(self.md5.iter().map(|b| format!("{:02x}", b)).collect())
+        }
+    }
+
+    /// FLACの既定チャンネル割り当てはVorbisと同じ
+    pub fn channel_layout(&self) -> String {
+        vorbis::channel_layout(self.channels)
+    }
+}
+
+/// SEEKTABLEのシークポイント
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeekPoint {
+    pub sample_number: u64,
+    pub stream_offset: u64,
+    pub frame_samples: u16,
+}
+
+fn parse_seektable(data: &[u8]) -> Vec<SeekPoint> {
+    data.chunks_exact(18)
+        .map(|point| SeekPoint {
+            sample_number: u64::from_be_bytes(point[0..8].try_into().unwrap()),
+            stream_offset: u64::from_be_bytes(point[8..16].try_into().unwrap()),
+            frame_samples: u16::from_be_bytes([point[16], point[17]]),
+        })
+        // プレースホルダーは除外
+        .filter(|point| point.sample_number != u64::MAX)
+        .collect()
+}
+
+/// CUESHEETのトラック
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueTrack {
+    pub number: u8,
+    /// ストリーム先頭からのサンプル位置
+    pub offset: u64,
+    pub isrc: String,
+    pub is_audio: bool,
+    /// (インデックス番号, トラック先頭からのサンプル位置)
+    pub indices: Vec<(u8, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueSheet {
+    pub media_catalog_number: String,
+    pub lead_in_samples: u64,
+    pub is_cd: bool,
+    pub tracks: Vec<CueTrack>,
+}
+
+impl CueSheet {
+    /// 音声トラックをチャプターにする（開始位置はINDEX 01、なければ最初のインデックス）
+    ///
+    /// リードアウトトラック（CD-DAは170、それ以外は255）は除く
+    pub fn chapters(&self, sample_rate: u32) -> Vec<Chapter> {
+        let lead_out = if self.is_cd { 170 } else { 255 };
+        self.tracks
+            .iter()
+            .filter(|track| track.is_audio && track.number != lead_out)
+            .map(|track| {
+                let index = track
+                    .indices
+                    .iter()
+                    .find(|(number, _)| *number == 1)
+                    .or(track.indices.first())
+                    .map_or(0, |(_, offset)| *offset);
+                let mut tags = HashMap::new();
+                if !track.isrc.is_empty() {
+                    tags.insert("isrc".to_string(), track.isrc.clone());
+                }
+                Chapter {
+                    id: track.number as i64,
+                    start_time: (track.offset + index) as f64 / sample_rate as f64,
+                    end_time: 0.0,
+                    title: None,
+                    tags,
+                }
+            })
+            .collect()
+    }
+}
+
+fn ascii_field(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+fn parse_cuesheet(data: &[u8]) -> ParseResult<CueSheet> {
+    let truncated = || ParseError::Invalid("truncated CUESHEET block".to_string());
+    if data.len() < 396 {
+        return Err(truncated());
+    }
+
+    let mut cuesheet = CueSheet {
+        media_catalog_number: ascii_field(&data[0..128]),
+        lead_in_samples: u64::from_be_bytes(data[128..136].try_into().unwrap()),
+        is_cd: data[136] & 0x80 != 0,
+        tracks: Vec::new(),
+    };
+
+    let track_count = data[395];
+    let mut pos = 396;
+    for _ in 0..track_count {
+        let track = data.get(pos..pos + 36).ok_or_else(truncated)?;
+        let index_count = track[35] as usize;
+        pos += 36;
+
+        let mut indices = Vec::with_capacity(index_count);
+        for _ in 0..index_count {
+            let index = data.get(pos..pos + 12).ok_or_else(truncated)?;
+            indices.push((
+                index[8],
+                u64::from_be_bytes(index[0..8].try_into().unwrap()),
+            ));
+            pos += 12;
+        }
+
+        cuesheet.tracks.push(CueTrack {
+            number: track[8],
+            offset: u64::from_be_bytes(track[0..8].try_into().unwrap()),
+            isrc: ascii_field(&track[9..21]),
+            is_audio: track[21] & 0x80 == 0,
+            indices,
+        });
+    }
+
+    Ok(cuesheet)
+}
+
+/// PICTUREブロック（Vorbisコメントの METADATA_BLOCK_PICTURE と同じ形式）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    pub picture_type: u32,
+    pub mime_type: String,
+    pub description: String,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+pub fn parse_picture(data: &[u8]) -> ParseResult<Picture> {
+    let truncated = || ParseError::Invalid("truncated PICTURE block".to_string());
+    let mut pos = 0;
+    let u32_field = |pos: &mut usize| -> ParseResult<u32> {
+        let bytes = data.get(*pos..*pos + 4).ok_or_else(truncated)?;
+        *pos += 4;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    };
+
+    let picture_type = u32_field(&mut pos)?;
+    let mime_len = u32_field(&mut pos)? as usize;
+    let mime_type =
+        String::from_utf8_lossy(data.get(pos..pos + mime_len).ok_or_else(truncated)?).into_owned();
+    pos += mime_len;
+    let description_len = u32_field(&mut pos)? as usize;
+    let description =
+        String::from_utf8_lossy(data.get(pos..pos + description_len).ok_or_else(truncated)?)
+            .into_owned();
+    pos += description_len;
+    let width = u32_field(&mut pos)?;
+    let height = u32_field(&mut pos)?;
+    // 色深度とパレット数は使わない
+    pos += 8;
+    let data_len = u32_field(&mut pos)? as usize;
+    let picture_data = data.get(pos..pos + data_len).ok_or_else(truncated)?;
+
+    Ok(Picture {
+        picture_type,
+        mime_type,
+        description,
+        width,
+        height,
+        data: picture_data.to_vec(),
+    })
+}
+
+/// FLACファイルのメタデータ全体
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlacFile {
+    pub stream_info: StreamInfo,
+    pub comments: Option<VorbisComments>,
+    pub pictures: Vec<Picture>,
+    pub cuesheet: Option<CueSheet>,
+    pub seek_points: Vec<SeekPoint>,
+    pub padding_bytes: u64,
+    /// 最初の音声フレームの位置
+    pub audio_offset: u64,
+    pub audio_size: u64,
+    /// 読み飛ばした壊れたブロック
+    pub warnings: Vec<String>,
+}
+
+impl FlacFile {
+    pub fn average_bit_rate(&self) -> i64 {
+        let duration = self.stream_info.duration_seconds();
+        if duration > 0.0 {
+            (self.audio_size as f64 * 8.0 / duration).round() as i64
+        } else {
+            0
+        }
+    }
+}
+
+/// "fLaC"マーカーに続くメタデータブロック列を読む（`reader`はマーカーの直後を指すこと）
+pub fn read_metadata_blocks<R: Read + Seek>(
+    reader: &mut R,
+    stream_end: u64,
+) -> ParseResult<FlacFile> {
+    let mut stream_info = None;
+    let mut comments = None;
+    let mut pictures = Vec::new();
+    let mut cuesheet = None;
+    let mut seek_points = Vec::new();
+    let mut padding_bytes = 0;
+    let mut warnings = Vec::new();
+
+    loop {
+        let header: [u8; 4] = read_array(reader)?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7F;
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as u64;
+        let body_start = reader.stream_position()?;
+        if body_start + length > stream_end {
+            return Err(ParseError::Invalid(
+                "metadata block extends past end of file".to_string(),
+            ));
+        }
+
+        match block_type {
+            BLOCK_STREAMINFO => {
+                let body = read_vec(reader, length, MAX_BLOCK_SIZE)?;
+                stream_info = Some(StreamInfo::parse(&body)?);
+            }
+            BLOCK_PADDING => padding_bytes += length,
+            BLOCK_SEEKTABLE => {
+                let body = read_vec(reader, length, MAX_BLOCK_SIZE)?;
+                seek_points = parse_seektable(&body);
+            }
+            // VORBIS_COMMENT・CUESHEET・PICTUREは任意のブロックのため、壊れていても再生時間などは返す
+            BLOCK_VORBIS_COMMENT => {
+                let body = read_vec(reader, length, MAX_BLOCK_SIZE)?;
+                match vorbis::parse_comments(&body) {
+                    Ok(parsed) => comments = Some(parsed),
+                    Err(e) => {
+                        warnings.push(format!("Skipped malformed VORBIS_COMMENT block: {}", e))
+                    }
+                }
+            }
+            BLOCK_CUESHEET => {
+                let body = read_vec(reader, length, MAX_BLOCK_SIZE)?;
+                match parse_cuesheet(&body) {
+                    Ok(parsed) => cuesheet = Some(parsed),
+                    Err(e) => warnings.push(format!("Skipped malformed CUESHEET block: {}", e)),
+                }
+            }
+            BLOCK_PICTURE => {
+                let body = read_vec(reader, length, MAX_BLOCK_SIZE)?;
+                match parse_picture(&body) {
+                    Ok(picture) => pictures.push(picture),
+                    Err(e) => warnings.push(format!("Skipped malformed PICTURE block: {}", e)),
+                }
+            }
+            // APPLICATIONと未知のブロックは読み飛ばす
+            _ => {}
+        }
+
+        reader.seek(SeekFrom::Start(body_start + length))?;
+        if is_last {
+            break;
+        }
+    }
+
+    let stream_info =
+        stream_info.ok_or_else(|| ParseError::Invalid("missing STREAMINFO block".to_string()))?;
+    let audio_offset = reader.stream_position()?;
+
+    Ok(FlacFile {
+        stream_info,
+        comments,
+        pictures,
+        cuesheet,
+        seek_points,
+        padding_bytes,
+        audio_offset,
+        audio_size: stream_end.saturating_sub(audio_offset),
+        warnings,
+    })
+}
+
+/// ネイティブFLACファイル（先頭のID3v2タグは許容）を読む
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<FlacFile> {
+    let file_len = stream_len(reader)?;
+
+    let offset = id3::skip_v2(reader, file_len)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let marker: [u8; 4] = read_array(reader)?;
+    if &marker != b"fLaC" {
+        return Err(ParseError::Invalid(
+            "missing fLaC stream marker".to_string(),
+        ));
+    }
+
+    read_metadata_blocks(reader, file_len)
+}
+
+/// FLACファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let flac = read(reader)?;
+    let info = &flac.stream_info;
+
+    audio_info.format_name = "flac".to_string();
+    audio_info.format_long_name = "raw FLAC".to_string();
+    audio_info.codec_name = "flac".to_string();
+    audio_info.codec_long_name = "FLAC (Free Lossless Audio Codec)".to_string();
+    audio_info.sample_rate = info.sample_rate as i32;
+    audio_info.channels = info.channels as i32;
+    audio_info.bits_per_sample = info.bits_per_sample as i32;
+    audio_info.channel_layout = info.channel_layout();
+    audio_info.duration_seconds = info.duration_seconds();
+    audio_info.bit_rate = flac.average_bit_rate();
+    audio_info.audio_md5 = info.md5_hex();
+    audio_info.warnings.extend(flac.warnings);
+
+    audio_info.artwork = flac
+        .pictures
+        .into_iter()
+        .map(|picture| artwork::from_picture(picture, "flac_picture"))
+        .collect();
+    if let Some(comments) = flac.comments {
+        merge_metadata(&mut audio_info.metadata, comments.entries);
+        audio_info.chapters = comments.chapters;
+        audio_info.artwork.extend(
+            comments
+                .pictures
+                .into_iter()
+                .map(|picture| artwork::from_picture(picture, "vorbis_picture")),
+        );
+    }
+    // Vorbisコメントにチャプターがなければ、CUESHEETのトラックをチャプターとする
+    if audio_info.chapters.is_empty() {
+        if let Some(cuesheet) = &flac.cuesheet {
+            audio_info.chapters = cuesheet.chapters(info.sample_rate);
+        }
+    }
+
+    if flac.padding_bytes > 0 {
+        audio_info
+            .codec_params
+            .insert("padding_bytes".to_string(), flac.padding_bytes.to_string());
+    }
+    if !flac.seek_points.is_empty() {
+        audio_info.codec_params.insert(
+            "seek_points".to_string(),
+            flac.seek_points.len().to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) fn build_streaminfo(sample_rate: u32, channels: u8, bits: u8, samples: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&4096u16.to_be_bytes());
+    out.extend_from_slice(&4096u16.to_be_bytes());
+    out.extend_from_slice(&[0; 6]);
+    let packed = ((sample_rate as u64) << 44)
+        | (((channels - 1) as u64) << 41)
+        | (((bits - 1) as u64) << 36)
+        | samples;
+    out.extend_from_slice(&packed.to_be_bytes());
+    out.extend_from_slice(&[0xAB; 16]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn block(block_type: u8, last: bool, body: &[u8]) -> Vec<u8> {
+        let len = body.len() as u32;
+        let mut out = vec![
+            block_type | if last { 0x80 } else { 0 },
+            (len >> 16) as u8,
+            (len >> 8) as u8,
+            len as u8,
+        ];
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn test_streaminfo_and_comments() {
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend(block(
+            BLOCK_STREAMINFO,
+            false,
+            &build_streaminfo(96000, 2, 24, 96000 * 10),
+        ));
+        bytes.extend(block(
+            BLOCK_VORBIS_COMMENT,
+            false,
+            &vorbis::build_comments("reference libFLAC", &["TITLE=Song", "TRACKNUMBER=4"]),
+        ));
+        bytes.extend(block(BLOCK_PADDING, true, &[0; 100]));
+        bytes.extend(vec![0u8; 125_000]);
+
+        let mut info = AudioInfo::new("test.flac".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.sample_rate, 96000);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, 24);
+        assert_eq!(info.duration_seconds, 10.0);
+        assert_eq!(info.bit_rate, 100_000);
+        assert_eq!(
+            info.audio_md5.as_deref(),
+            Some("abababababababababababababababab")
+        );
+        assert_eq!(info.metadata["title"], "Song");
+        assert_eq!(info.metadata["track"], "4");
+    }
+
+    #[test]
+    fn test_seektable_cuesheet_and_picture() {
+        let mut seektable = Vec::new();
+        seektable.extend_from_slice(&0u64.to_be_bytes());
+        seektable.extend_from_slice(&0u64.to_be_bytes());
+        seektable.extend_from_slice(&4096u16.to_be_bytes());
+        seektable.extend_from_slice(&u64::MAX.to_be_bytes());
+        seektable.extend_from_slice(&[0; 10]);
+
+        // トラック1（INDEX 01は1秒）とリードアウト
+        let mut cuesheet = vec![0u8; 396];
+        cuesheet[395] = 2;
+        let mut track = vec![0u8; 36];
+        track[0..8].copy_from_slice(&44100u64.to_be_bytes());
+        track[8] = 1;
+        track[9..21].copy_from_slice(b"JPXX01234567");
+        track[35] = 1;
+        cuesheet.extend(track);
+        cuesheet.extend(vec![0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]);
+        let mut lead_out = vec![0u8; 36];
+        lead_out[0..8].copy_from_slice(&441000u64.to_be_bytes());
+        lead_out[8] = 255;
+        cuesheet.extend(lead_out);
+
+        let mut picture = Vec::new();
+        picture.extend_from_slice(&3u32.to_be_bytes());
+        picture.extend_from_slice(&10u32.to_be_bytes());
+        picture.extend_from_slice(b"image/jpeg");
+        picture.extend_from_slice(&0u32.to_be_bytes());
+        picture.extend_from_slice(&300u32.to_be_bytes());
+        picture.extend_from_slice(&200u32.to_be_bytes());
+        picture.extend_from_slice(&[0; 8]);
+        picture.extend_from_slice(&4u32.to_be_bytes());
+        picture.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xE0]);
+
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend(block(
+            BLOCK_STREAMINFO,
+            false,
+            &build_streaminfo(44100, 1, 16, 0),
+        ));
+        bytes.extend(block(BLOCK_SEEKTABLE, false, &seektable));
+        bytes.extend(block(BLOCK_CUESHEET, false, &cuesheet));
+        bytes.extend(block(BLOCK_PADDING, false, &[0; 50]));
+        bytes.extend(block(BLOCK_PICTURE, true, &picture));
+
+        let mut info = AudioInfo::new("test.flac".into());
+        probe(&mut Cursor::new(bytes.clone()), &mut info).unwrap();
+        assert_eq!(info.chapters.len(), 1);
+        assert_eq!(info.chapters[0].id, 1);
+        assert_eq!(info.chapters[0].start_time, 1.0);
+        assert_eq!(info.chapters[0].tags["isrc"], "JPXX01234567");
+        assert_eq!(info.codec_params["seek_points"], "1");
+        assert_eq!(info.codec_params["padding_bytes"], "50");
+
+        let flac = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(flac.seek_points.len(), 1);
+        let cuesheet = flac.cuesheet.unwrap();
+        assert_eq!(cuesheet.tracks[0].offset, 44100);
+        assert_eq!(cuesheet.tracks[0].indices, vec![(1, 0)]);
+        assert_eq!(flac.pictures[0].mime_type, "image/jpeg");
+        assert_eq!(
+            (flac.pictures[0].width, flac.pictures[0].height),
+            (300, 200)
+        );
+        assert_eq!(flac.pictures[0].data.len(), 4);
+    }
+
+    #[test]
+    fn test_malformed_optional_blocks_are_skipped() {
+        // データ長が本体を超えるPICTUREと、トラック数に満たないCUESHEET、ベンダー文字列が切れたVORBIS_COMMENT
+        let mut picture = Vec::new();
+        picture.extend_from_slice(&3u32.to_be_bytes());
+        picture.extend_from_slice(&1000u32.to_be_bytes());
+        picture.extend_from_slice(b"image/png");
+        let mut cuesheet = vec![0u8; 396];
+        cuesheet[395] = 2;
+
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend(block(
+            BLOCK_STREAMINFO,
+            false,
+            &build_streaminfo(48000, 2, 16, 48000 * 5),
+        ));
+        bytes.extend(block(BLOCK_PICTURE, false, &picture));
+        bytes.extend(block(BLOCK_CUESHEET, false, &cuesheet));
+        bytes.extend(block(BLOCK_VORBIS_COMMENT, true, &[0xFF, 0, 0, 0]));
+
+        let mut info = AudioInfo::new("test.flac".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.duration_seconds, 5.0);
+        assert!(info.artwork.is_empty());
+        assert_eq!(info.warnings.len(), 3);
+        assert!(info.warnings[0].contains("PICTURE"));
+        assert!(info.warnings[1].contains("CUESHEET"));
+        assert!(info.warnings[2].contains("VORBIS_COMMENT"));
+    }
+
+    #[test]
+    fn test_not_a_flac_file() {
+        let mut file = std::fs::File::open("test_sample.flac").unwrap();
+        assert!(read(&mut file).is_err());
+    }
+}
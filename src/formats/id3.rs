@@ -0,0 +1,971 @@
+// ID3v2.2/2.3/2.4 および ID3v1/ID3v1.1 タグリーダー
+//
+// キー名はffprobe（FFmpegのメタデータ変換表）と同じ小文字のキーに揃える。
+// 同一ファイルにID3v2とID3v1の両方がある場合はID3v2を優先し、ID3v1は欠けているキーのみを補う。
+// 末尾にAPEタグもある場合はID3v2とID3v1の間の優先度で扱う（apetag.rs参照）。
+
+use super::{
+    apetag, artwork, flac::Picture, merge_metadata, read_array, stream_len, ParseError, ParseResult,
+};
+use crate::{Artwork, Chapter};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+/// ID3v2タグの最大サイズ（syncsafe整数の上限は256MB）
+const MAX_TAG_SIZE: u64 = 256 * 1024 * 1024;
+
+/// ID3v1のジャンル番号（Winamp拡張を含む）
+pub(crate) const GENRES: [&str; 192] = [
+    "Blues",
+    "Classic Rock",
+    "Country",
+    "Dance",
+    "Disco",
+    "Funk",
+    "Grunge",
+    "Hip-Hop",
+    "Jazz",
+    "Metal",
+    "New Age",
+    "Oldies",
+    "Other",
+    "Pop",
+    "R&B",
+    "Rap",
+    "Reggae",
+    "Rock",
+    "Techno",
+    "Industrial",
+    "Alternative",
+    "Ska",
+    "Death Metal",
+    "Pranks",
+    "Soundtrack",
+    "Euro-Techno",
+    "Ambient",
+    "Trip-Hop",
+    "Vocal",
+    "Jazz+Funk",
+    "Fusion",
+    "Trance",
+    "Classical",
+    "Instrumental",
+    "Acid",
+    "House",
+    "Game",
+    "Sound Clip",
+    "Gospel",
+    "Noise",
+    "AlternRock",
+    "Bass",
+    "Soul",
+    "Punk",
+    "Space",
+    "Meditative",
+    "Instrumental Pop",
+    "Instrumental Rock",
+    "Ethnic",
+    "Gothic",
+    "Darkwave",
+    "Techno-Industrial",
+    "Electronic",
+    "Pop-Folk",
+    "Eurodance",
+    "Dream",
+    "Southern Rock",
+    "Comedy",
+    "Cult",
+    "Gangsta",
+    "Top 40",
+    "Christian Rap",
+    "Pop/Funk",
+    "Jungle",
+    "Native American",
+    "Cabaret",
+    "New Wave",
+    "Psychadelic",
+    "Rave",
+    "Showtunes",
+    "Trailer",
+    "Lo-Fi",
+    "Tribal",
+    "Acid Punk",
+    "Acid Jazz",
+    "Polka",
+    "Retro",
+    "Musical",
+    "Rock & Roll",
+    "Hard Rock",
+    "Folk",
+    "Folk-Rock",
+    "National Folk",
+    "Swing",
+    "Fast Fusion",
+    "Bebob",
+    "Latin",
+    "Revival",
+    "Celtic",
+    "Bluegrass",
+    "Avantgarde",
+    "Gothic Rock",
+    "Progressive Rock",
+    "Psychedelic Rock",
+    "Symphonic Rock",
+    "Slow Rock",
+    "Big Band",
+    "Chorus",
+    "Easy Listening",
+    "Acoustic",
+    "Humour",
+    "Speech",
+    "Chanson",
+    "Opera",
+    "Chamber Music",
+    "Sonata",
+    "Symphony",
+    "Booty Bass",
+    "Primus",
+    "Porn Groove",
+    "Satire",
+    "Slow Jam",
+    "Club",
+    "Tango",
+    "Samba",
+    "Folklore",
+    "Ballad",
+    "Power Ballad",
+    "Rhythmic Soul",
+    "Freestyle",
+    "Duet",
+    "Punk Rock",
+    "Drum Solo",
+    "A capella",
+    "Euro-House",
+    "Dance Hall",
+    "Goa",
+    "Drum & Bass",
+    "Club-House",
+    "Hardcore Techno",
+    "Terror",
+    "Indie",
+    "BritPop",
+    "Negerpunk",
+    "Polsk Punk",
+    "Beat",
+    "Christian Gangsta Rap",
+    "Heavy Metal",
+    "Black Metal",
+    "Crossover",
+    "Contemporary Christian",
+    "Christian Rock",
+    "Merengue",
+    "Salsa",
+    "Thrash Metal",
+    "Anime",
+    "Jpop",
+    "Synthpop",
+    "Abstract",
+    "Art Rock",
+    "Baroque",
+    "Bhangra",
+    "Big Beat",
+    "Breakbeat",
+    "Chillout",
+    "Downtempo",
+    "Dub",
+    "EBM",
+    "Eclectic",
+    "Electro",
+    "Electroclash",
+    "Emo",
+    "Experimental",
+    "Garage",
+    "Global",
+    "IDM",
+    "Illbient",
+    "Industro-Goth",
+    "Jam Band",
+    "Krautrock",
+    "Leftfield",
+    "Lounge",
+    "Math Rock",
+    "New Romantic",
+    "Nu-Breakz",
+    "Post-Punk",
+    "Post-Rock",
+    "Psytrance",
+    "Shoegaze",
+    "Space Rock",
+    "Trop Rock",
+    "World Music",
+    "Neoclassical",
+    "Audiobook",
+    "Audio Theatre",
+    "Neue Deutsche Welle",
+    "Podcast",
+    "Indie Rock",
+    "G-Funk",
+    "Dubstep",
+    "Garage Rock",
+    "Psybient",
+];
+
+/// フレームIDからffprobe互換のメタデータキーへの変換
+fn frame_key(frame_id: &str) -> Option<&'static str> {
+    Some(match frame_id {
+        "TALB" | "TAL" => "album",
+        "TCOM" | "TCM" => "composer",
+        "TCON" | "TCO" => "genre",
+        "TCOP" | "TCR" => "copyright",
+        "TENC" | "TEN" => "encoded_by",
+        "TIT1" | "TT1" => "grouping",
+        "TIT2" | "TT2" => "title",
+        "TIT3" | "TT3" => "subtitle",
+        "TLAN" | "TLA" => "language",
+        "TPE1" | "TP1" => "artist",
+        "TPE2" | "TP2" => "album_artist",
+        "TPE3" | "TP3" => "performer",
+        "TPOS" | "TPA" => "disc",
+        "TPUB" | "TPB" => "publisher",
+        "TRCK" | "TRK" => "track",
+        "TSSE" | "TSS" => "encoder",
+        "TCMP" | "TCP" => "compilation",
+        "TDRC" | "TYER" | "TYE" => "date",
+        "TDRL" => "release_date",
+        "TDEN" => "creation_time",
+        "TSOA" => "album-sort",
+        "TSOP" => "artist-sort",
+        "TSOT" => "title-sort",
+        "TSO2" => "album_artist-sort",
+        "TSOC" => "composer-sort",
+        "TBPM" | "TBP" => "bpm",
+        "TSRC" | "TRC" => "isrc",
+        _ => return None,
+    })
+}
+
+/// ID3v2タグ（10バイトヘッダー）の全長を返す
+pub(crate) fn v2_tag_len(header: &[u8; 10]) -> Option<u64> {
+    if &header[0..3] != b"ID3" || header[6..10].iter().any(|b| b & 0x80 != 0) {
+        return None;
+    }
+    // フッター付きの場合はさらに10バイト
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + syncsafe(&header[6..10]) + footer)
+}
+
+fn syncsafe(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0u64, |acc, b| (acc << 7) | (*b & 0x7F) as u64)
+}
+
+fn be_uint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64)
+}
+
+/// 非同期化（0xFF 0x00 → 0xFF）を解除する
+fn remove_unsynchronisation(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        out.push(data[i]);
+        if data[i] == 0xFF && data.get(i + 1) == Some(&0x00) {
+            i += 1;
+        }
+        i += 1;
+    }
+    out
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+fn decode_utf16(bytes: &[u8], default_big_endian: bool) -> String {
+    let (big_endian, body) = match bytes {
+        [0xFE, 0xFF, rest @ ..] => (true, rest),
+        [0xFF, 0xFE, rest @ ..] => (false, rest),
+        _ => (default_big_endian, bytes),
+    };
+    let units: Vec<u16> = body
+        .chunks_exact(2)
+        .map(|pair| {
+            if big_endian {
+                u16::from_be_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_le_bytes([pair[0], pair[1]])
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn decode_string(encoding: u8, bytes: &[u8]) -> String {
+    match encoding {
+        1 => decode_utf16(bytes, false),
+        2 => decode_utf16(bytes, true),
+        3 => String::from_utf8_lossy(bytes).into_owned(),
+        _ => decode_latin1(bytes),
+    }
+}
+
+/// 終端文字で区切られた文字列群に分割してデコードする
+fn split_strings(encoding: u8, data: &[u8]) -> Vec<String> {
+    let wide = matches!(encoding, 1 | 2);
+    let mut strings = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < data.len() {
+        let is_terminator = if wide {
+            i + 1 < data.len() && data[i] == 0 && data[i + 1] == 0
+        } else {
+            data[i] == 0
+        };
+        if is_terminator {
+            strings.push(decode_string(encoding, &data[start..i]));
+            i += if wide { 2 } else { 1 };
+            start = i;
+        } else {
+            i += if wide { 2 } else { 1 };
+        }
+    }
+    if start < data.len() {
+        strings.push(decode_string(encoding, &data[start..]));
+    }
+    strings
+}
+
+/// 先頭の1文字列（終端まで）と残りのバイト列に分ける
+fn take_string(encoding: u8, data: &[u8]) -> (String, &[u8]) {
+    let wide = matches!(encoding, 1 | 2);
+    let step = if wide { 2 } else { 1 };
+    let mut i = 0;
+    while i + step <= data.len() {
+        if data[i..i + step].iter().all(|b| *b == 0) {
+            return (decode_string(encoding, &data[..i]), &data[i + step..]);
+        }
+        i += step;
+    }
+    (decode_string(encoding, data), &[])
+}
+
+/// "(13)" や "13" 形式のジャンル参照を名前に展開する
+fn resolve_genre(value: &str) -> String {
+    let trimmed = value.trim();
+    let number = trimmed
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(number, rest)| (number, rest.trim()))
+        .unwrap_or((trimmed, ""));
+    match number {
+        ("RX", _) => "Remix".to_string(),
+        ("CR", _) => "Cover".to_string(),
+        (number, rest) => match number.parse::<usize>() {
+            Ok(index) if index < GENRES.len() => {
+                if rest.is_empty() {
+                    GENRES[index].to_string()
+                } else {
+                    rest.to_string()
+                }
+            }
+            _ => value.to_string(),
+        },
+    }
+}
+
+/// CHAPフレーム（ID3v2 Chapter Frame Addendum）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Id3Chapter {
+    pub element_id: String,
+    /// ミリ秒
+    pub start_ms: u32,
+    /// ミリ秒（0xFFFFFFFFは未指定）
+    pub end_ms: u32,
+    /// 埋め込みフレーム（TIT2など）の(キー, 値)
+    pub entries: Vec<(String, String)>,
+}
+
+/// 解析済みのID3v2タグ
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Id3v2Tag {
+    pub major_version: u8,
+    /// 出現順の(キー, 値)。同じキーが複数回現れることがある
+    pub entries: Vec<(String, String)>,
+    pub chapters: Vec<Id3Chapter>,
+    /// 最上位のCTOCフレームが列挙する子要素ID
+    pub toc: Vec<String>,
+    /// APIC/PICフレーム
+    pub pictures: Vec<Picture>,
+}
+
+impl Id3v2Tag {
+    fn push(&mut self, key: &str, values: Vec<String>) {
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|v| v.trim_end_matches('\0').to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if !values.is_empty() {
+            self.entries.push((key.to_lowercase(), values.join(";")));
+        }
+    }
+
+    fn parse_frame(&mut self, frame_id: &str, body: &[u8]) {
+        if body.is_empty() {
+            return;
+        }
+        let encoding = body[0];
+
+        match frame_id {
+            "TXXX" | "TXX" => {
+                let (description, rest) = take_string(encoding, &body[1..]);
+                let key = if description.is_empty() {
+                    "TXXX".to_string()
+                } else {
+                    description
+                };
+                self.push(&key, split_strings(encoding, rest));
+            }
+            "COMM" | "COM" => {
+                // 言語コード(3バイト) + 短い説明 + 本文
+                if body.len() < 4 {
+                    return;
+                }
+                let (description, rest) = take_string(encoding, &body[4..]);
+                let key = if description.is_empty() {
+                    "comment".to_string()
+                } else {
+                    format!("comment-{}", description)
+                };
+                self.push(&key, split_strings(encoding, rest));
+            }
+            "APIC" | "PIC" => self.parse_apic(frame_id, body),
+            "CHAP" => self.parse_chap(body),
+            "CTOC" => self.parse_ctoc(body),
+            id if id.starts_with('T') => {
+                let mut values = split_strings(encoding, &body[1..]);
+                if matches!(id, "TCON" | "TCO") {
+                    values = values.iter().map(|v| resolve_genre(v)).collect();
+                }
+                let key = frame_key(id).unwrap_or(id);
+                self.push(key, values);
+            }
+            _ => {}
+        }
+    }
+
+    /// エンコーディング + MIMEタイプ（v2.2は3文字の形式名） + ピクチャータイプ + 説明 + 画像データ
+    fn parse_apic(&mut self, frame_id: &str, body: &[u8]) {
+        let encoding = body[0];
+        let (mime_type, rest) = if frame_id == "PIC" {
+            let Some(format) = body.get(1..4) else {
+                return;
+            };
+            let mime_type = match format {
+                b"JPG" => "image/jpeg".to_string(),
+                b"PNG" => "image/png".to_string(),
+                _ => String::new(),
+            };
+            (mime_type, &body[4..])
+        } else {
+            take_string(0, &body[1..])
+        };
+        let Some((picture_type, rest)) = rest.split_first() else {
+            return;
+        };
+        let (description, data) = take_string(encoding, rest);
+        if data.is_empty() {
+            return;
+        }
+        self.pictures.push(Picture {
+            picture_type: *picture_type as u32,
+            mime_type,
+            description,
+            width: 0,
+            height: 0,
+            data: data.to_vec(),
+        });
+    }
+
+    /// APIC/PICフレームの画像
+    pub fn artwork(&self) -> Vec<Artwork> {
+        self.pictures
+            .iter()
+            .map(|picture| artwork::from_picture(picture.clone(), "id3_apic"))
+            .collect()
+    }
+
+    /// 要素ID + 開始/終了時刻(ms) + 開始/終了バイト位置 + 埋め込みフレーム
+    fn parse_chap(&mut self, body: &[u8]) {
+        let (element_id, rest) = take_string(0, body);
+        if rest.len() < 16 {
+            return;
+        }
+        let mut embedded = Id3v2Tag {
+            major_version: self.major_version,
+            ..Default::default()
+        };
+        embedded.parse_frames(&rest[16..], false);
+        self.chapters.push(Id3Chapter {
+            element_id,
+            start_ms: be_uint(&rest[0..4]) as u32,
+            end_ms: be_uint(&rest[4..8]) as u32,
+            entries: embedded.entries,
+        });
+    }
+
+    /// 要素ID + フラグ + 子要素数 + 子要素ID列（最上位の目次のみ使う）
+    fn parse_ctoc(&mut self, body: &[u8]) {
+        let (_, rest) = take_string(0, body);
+        let [flags, count, children @ ..] = rest else {
+            return;
+        };
+        if flags & 0x01 == 0 || !self.toc.is_empty() {
+            return;
+        }
+        let mut rest = children;
+        for _ in 0..*count {
+            let (child, next) = take_string(0, rest);
+            self.toc.push(child);
+            rest = next;
+        }
+    }
+
+    /// 最上位CTOCの順序（記載のないものは開始時刻順で後ろに続ける）でチャプターを返す
+    pub fn chapter_list(&self) -> Vec<Chapter> {
+        let mut ordered: Vec<&Id3Chapter> = self
+            .toc
+            .iter()
+            .filter_map(|id| self.chapters.iter().find(|c| &c.element_id == id))
+            .collect();
+        let mut rest: Vec<&Id3Chapter> = self
+            .chapters
+            .iter()
+            .filter(|c| !self.toc.contains(&c.element_id))
+            .collect();
+        rest.sort_by_key(|c| c.start_ms);
+        ordered.extend(rest);
+
+        ordered
+            .into_iter()
+            .enumerate()
+            .map(|(id, chapter)| {
+                let mut tags = HashMap::new();
+                merge_metadata(&mut tags, chapter.entries.iter().cloned());
+                Chapter {
+                    id: id as i64,
+                    start_time: chapter.start_ms as f64 / 1000.0,
+                    end_time: if chapter.end_ms == u32::MAX {
+                        0.0
+                    } else {
+                        chapter.end_ms as f64 / 1000.0
+                    },
+                    title: tags.remove("title"),
+                    tags,
+                }
+            })
+            .collect()
+    }
+
+    /// フレーム列（ヘッダー以降、またはCHAP/CTOCの埋め込みフレーム）を解析する
+    fn parse_frames(&mut self, body: &[u8], unsync_tag: bool) {
+        let mut pos = 0usize;
+        let (id_len, header_len) = if self.major_version == 2 {
+            (3, 6)
+        } else {
+            (4, 10)
+        };
+
+        while pos + header_len <= body.len() {
+            let frame_header = &body[pos..pos + header_len];
+            // パディング領域
+            if frame_header[0] == 0 {
+                break;
+            }
+            let frame_id = match std::str::from_utf8(&frame_header[..id_len]) {
+                Ok(id)
+                    if id
+                        .bytes()
+                        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) =>
+                {
+                    id
+                }
+                _ => break,
+            };
+
+            let size_bytes =
+                &frame_header[id_len..id_len + if self.major_version == 2 { 3 } else { 4 }];
+            let frame_size = if self.major_version == 4 {
+                syncsafe(size_bytes)
+            } else {
+                be_uint(size_bytes)
+            } as usize;
+            let frame_flags = if self.major_version == 2 {
+                0
+            } else {
+                be_uint(&frame_header[8..10]) as u16
+            };
+
+            let start = pos + header_len;
+            let end = start + frame_size;
+            if end > body.len() {
+                break;
+            }
+            pos = end;
+
+            let mut frame_body = &body[start..end];
+            let (compressed, encrypted, grouping, unsync, length_indicator) =
+                match self.major_version {
+                    3 => (
+                        frame_flags & 0x0080 != 0,
+                        frame_flags & 0x0040 != 0,
+                        frame_flags & 0x0020 != 0,
+                        false,
+                        false,
+                    ),
+                    4 => (
+                        frame_flags & 0x0008 != 0,
+                        frame_flags & 0x0004 != 0,
+                        frame_flags & 0x0040 != 0,
+                        frame_flags & 0x0002 != 0 || unsync_tag,
+                        frame_flags & 0x0001 != 0,
+                    ),
+                    _ => (false, false, false, false, false),
+                };
+            if compressed || encrypted {
+                continue;
+            }
+            if grouping && !frame_body.is_empty() {
+                frame_body = &frame_body[1..];
+            }
+            if length_indicator && frame_body.len() >= 4 {
+                frame_body = &frame_body[4..];
+            }
+
+            if unsync {
+                let resynced = remove_unsynchronisation(frame_body);
+                self.parse_frame(frame_id, &resynced);
+            } else {
+                self.parse_frame(frame_id, frame_body);
+            }
+        }
+    }
+}
+
+/// ID3v2タグ全体（10バイトヘッダーを含む）を解析する
+pub fn parse_v2(data: &[u8]) -> ParseResult<Id3v2Tag> {
+    if data.len() < 10 {
+        return Err(ParseError::Invalid("ID3v2 tag too short".to_string()));
+    }
+    let header: [u8; 10] = data[..10].try_into().unwrap();
+    v2_tag_len(&header).ok_or_else(|| ParseError::Invalid("invalid ID3v2 header".to_string()))?;
+
+    let major_version = header[3];
+    let flags = header[5];
+    if !(2..=4).contains(&major_version) {
+        return Err(ParseError::Invalid(format!(
+            "unsupported ID3v2 version 2.{}",
+            major_version
+        )));
+    }
+
+    let body_end = (10 + syncsafe(&header[6..10])).min(data.len() as u64) as usize;
+    let mut body = &data[10..body_end];
+
+    // v2.2/v2.3はタグ全体に非同期化が適用される
+    let unsync_tag = flags & 0x80 != 0;
+    let resynced;
+    if unsync_tag && major_version < 4 {
+        resynced = remove_unsynchronisation(body);
+        body = &resynced;
+    }
+
+    let mut tag = Id3v2Tag {
+        major_version,
+        ..Default::default()
+    };
+
+    // v2.2の圧縮フラグは定義されておらず、読めない
+    if major_version == 2 && flags & 0x40 != 0 {
+        return Ok(tag);
+    }
+
+    // 拡張ヘッダーのスキップ
+    let mut pos = 0usize;
+    if major_version >= 3 && flags & 0x40 != 0 && body.len() >= 4 {
+        pos = if major_version == 3 {
+            4 + be_uint(&body[0..4]) as usize
+        } else {
+            syncsafe(&body[0..4]) as usize
+        };
+    }
+
+    tag.parse_frames(&body[pos.min(body.len())..], unsync_tag);
+
+    Ok(tag)
+}
+
+/// 指定位置からID3v2タグを読む（タグでなければ`None`）
+pub fn read_v2_at<R: Read + Seek>(reader: &mut R, offset: u64) -> ParseResult<Option<Id3v2Tag>> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let header: [u8; 10] = match read_array(reader) {
+        Ok(header) => header,
+        Err(_) => return Ok(None),
+    };
+    let Some(tag_len) = v2_tag_len(&header) else {
+        return Ok(None);
+    };
+    if tag_len > MAX_TAG_SIZE {
+        return Err(ParseError::Invalid(format!(
+            "ID3v2 tag of {} bytes is too large",
+            tag_len
+        )));
+    }
+
+    let mut data = header.to_vec();
+    reader.by_ref().take(tag_len - 10).read_to_end(&mut data)?;
+    parse_v2(&data).map(Some)
+}
+
+/// 先頭のID3v2タグ（複数連結されている場合も含む）をスキップした位置を返す
+pub fn skip_v2<R: Read + Seek>(reader: &mut R, file_len: u64) -> ParseResult<u64> {
+    let mut offset = 0;
+    while offset + 10 <= file_len {
+        reader.seek(SeekFrom::Start(offset))?;
+        let header: [u8; 10] = read_array(reader)?;
+        match v2_tag_len(&header) {
+            Some(len) => offset += len,
+            None => break,
+        }
+    }
+    Ok(offset)
+}
+
+/// ファイル末尾のID3v1/ID3v1.1タグを読む
+pub fn read_v1<R: Read + Seek>(reader: &mut R) -> ParseResult<Vec<(String, String)>> {
+    let file_len = stream_len(reader)?;
+    if file_len < 128 {
+        return Ok(Vec::new());
+    }
+    reader.seek(SeekFrom::Start(file_len - 128))?;
+    let tag: [u8; 128] = read_array(reader)?;
+    if &tag[0..3] != b"TAG" {
+        return Ok(Vec::new());
+    }
+
+    let field = |range: std::ops::Range<usize>| {
+        let bytes = &tag[range];
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        decode_latin1(&bytes[..end]).trim().to_string()
+    };
+
+    let mut entries = vec![
+        ("title".to_string(), field(3..33)),
+        ("artist".to_string(), field(33..63)),
+        ("album".to_string(), field(63..93)),
+        ("date".to_string(), field(93..97)),
+    ];
+
+    // ID3v1.1: コメントの29バイト目が0でトラック番号が続く
+    if tag[125] == 0 && tag[126] != 0 {
+        entries.push(("comment".to_string(), field(97..125)));
+        entries.push(("track".to_string(), tag[126].to_string()));
+    } else {
+        entries.push(("comment".to_string(), field(97..127)));
+    }
+    if let Some(genre) = GENRES.get(tag[127] as usize) {
+        entries.push(("genre".to_string(), genre.to_string()));
+    }
+
+    entries.retain(|(_, value)| !value.is_empty());
+    Ok(entries)
+}
+
+/// 先頭のID3v2タグ、末尾のAPEタグとID3v1タグを読み、ffprobe互換のメタデータにまとめる
+pub fn read_metadata<R: Read + Seek>(reader: &mut R) -> ParseResult<HashMap<String, String>> {
+    let mut metadata = HashMap::new();
+    if let Some(tag) = read_v2_at(reader, 0)? {
+        merge_metadata(&mut metadata, tag.entries);
+    }
+    let ape_entries = apetag::read(reader)?.map(|tag| tag.entries);
+    for (key, value) in ape_entries.into_iter().flatten().chain(read_v1(reader)?) {
+        metadata.entry(key).or_insert(value);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn tag(version: u8, flags: u8, frames: &[u8]) -> Vec<u8> {
+        let size = frames.len() as u32;
+        let mut out = vec![b'I', b'D', b'3', version, 0, flags];
+        out.extend_from_slice(&[
+            ((size >> 21) & 0x7F) as u8,
+            ((size >> 14) & 0x7F) as u8,
+            ((size >> 7) & 0x7F) as u8,
+            (size & 0x7F) as u8,
+        ]);
+        out.extend_from_slice(frames);
+        out
+    }
+
+    fn frame_v3(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn test_v23_text_frames_and_encodings() {
+        let mut frames = frame_v3(b"TIT2", b"\x00Caf\xe9");
+        // UTF-16 BOM付き
+        frames.extend(frame_v3(b"TPE1", b"\x01\xff\xfeA\x00B\x00"));
+        frames.extend(frame_v3(b"TCON", b"\x00(13)"));
+        frames.extend(frame_v3(b"TRCK", b"\x003/12"));
+        frames.extend(vec![0u8; 16]);
+
+        let tag = parse_v2(&tag(3, 0, &frames)).unwrap();
+        let metadata: HashMap<_, _> = tag.entries.into_iter().collect();
+        assert_eq!(metadata["title"], "Café");
+        assert_eq!(metadata["artist"], "AB");
+        assert_eq!(metadata["genre"], "Pop");
+        assert_eq!(metadata["track"], "3/12");
+    }
+
+    #[test]
+    fn test_v24_multi_value_txxx_and_unsync() {
+        let mut txxx = b"\x03REPLAYGAIN\x00a\x00b".to_vec();
+        let mut frames = b"TXXX".to_vec();
+        frames.extend_from_slice(&[0, 0, 0, txxx.len() as u8, 0, 0]);
+        frames.append(&mut txxx);
+
+        // フレーム単位の非同期化: 0xFF 0x00 → 0xFF
+        let body = b"\x00\xff\x00X";
+        frames.extend_from_slice(b"TALB");
+        frames.extend_from_slice(&[0, 0, 0, body.len() as u8, 0, 0x02]);
+        frames.extend_from_slice(body);
+
+        // 録音日（TDRC）と発売日（TDRL）は別のキー
+        for (id, body) in [(b"TDRC", &b"\x032018"[..]), (b"TDRL", b"\x032019-05-01")] {
+            frames.extend_from_slice(id);
+            frames.extend_from_slice(&[0, 0, 0, body.len() as u8, 0, 0]);
+            frames.extend_from_slice(body);
+        }
+
+        let tag = parse_v2(&tag(4, 0, &frames)).unwrap();
+        assert_eq!(
+            tag.entries,
+            vec![
+                ("replaygain".to_string(), "a;b".to_string()),
+                ("album".to_string(), "\u{ff}X".to_string()),
+                ("date".to_string(), "2018".to_string()),
+                ("release_date".to_string(), "2019-05-01".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chap_and_ctoc_frames() {
+        let chap = |id: &str, start: u32, end: u32, title: &str| {
+            let mut body = id.as_bytes().to_vec();
+            body.push(0);
+            for value in [start, end, u32::MAX, u32::MAX] {
+                body.extend_from_slice(&value.to_be_bytes());
+            }
+            let mut tit2 = vec![0];
+            tit2.extend_from_slice(title.as_bytes());
+            body.extend(frame_v3(b"TIT2", &tit2));
+            frame_v3(b"CHAP", &body)
+        };
+        // 目次の順序はファイル内のCHAPの順序より優先する
+        let mut ctoc = b"toc\x00\x03\x02".to_vec();
+        ctoc.extend_from_slice(b"ch1\x00ch0\x00");
+        let mut frames = frame_v3(b"TIT2", b"\x00Episode 12");
+        frames.extend(chap("ch0", 0, 90_000, "Intro"));
+        frames.extend(chap("ch1", 90_000, u32::MAX, "News"));
+        frames.extend(frame_v3(b"CTOC", &ctoc));
+
+        let tag = parse_v2(&tag(3, 0, &frames)).unwrap();
+        assert_eq!(tag.entries, vec![("title".into(), "Episode 12".into())]);
+        assert_eq!(tag.toc, vec!["ch1", "ch0"]);
+        let chapters = tag.chapter_list();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title.as_deref(), Some("News"));
+        assert_eq!(chapters[0].start_time, 90.0);
+        assert_eq!(chapters[0].end_time, 0.0);
+        assert_eq!(chapters[1].title.as_deref(), Some("Intro"));
+        assert_eq!(chapters[1].end_time, 90.0);
+    }
+
+    #[test]
+    fn test_apic_frames() {
+        let mut apic = b"\x00image/jpeg\x00\x03Front\x00".to_vec();
+        apic.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xE0]);
+        let mut back = b"\x01image/png\x00\x04\xFF\xFEB\x00\x00\x00".to_vec();
+        back.extend_from_slice(b"\x89PNG");
+        let frames = [frame_v3(b"APIC", &apic), frame_v3(b"APIC", &back)].concat();
+
+        let artwork = parse_v2(&tag(3, 0, &frames)).unwrap().artwork();
+        assert_eq!(artwork.len(), 2);
+        assert_eq!(artwork[0].picture_type, "Cover (front)");
+        assert_eq!(artwork[0].mime_type, "image/jpeg");
+        assert_eq!(artwork[0].description.as_deref(), Some("Front"));
+        assert_eq!(artwork[0].size, 4);
+        assert_eq!(artwork[1].picture_type, "Cover (back)");
+        assert_eq!(artwork[1].description.as_deref(), Some("B"));
+        assert_eq!(artwork[1].source, "id3_apic");
+    }
+
+    #[test]
+    fn test_v22_frames() {
+        let mut frames = b"TT2".to_vec();
+        frames.extend_from_slice(&[0, 0, 6]);
+        frames.extend_from_slice(b"\x00Hello");
+        let tag = parse_v2(&tag(2, 0, &frames)).unwrap();
+        assert_eq!(
+            tag.entries,
+            vec![("title".to_string(), "Hello".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_v1_and_precedence() {
+        let mut v1 = vec![0u8; 128];
+        v1[..3].copy_from_slice(b"TAG");
+        v1[3..8].copy_from_slice(b"Title");
+        v1[33..39].copy_from_slice(b"Artist");
+        v1[93..97].copy_from_slice(b"1999");
+        v1[126] = 7;
+        v1[127] = 17;
+
+        let mut file = tag(3, 0, &frame_v3(b"TIT2", b"\x00V2 Title"));
+        file.extend_from_slice(&[0xFF; 64]);
+        file.extend_from_slice(&v1);
+
+        let metadata = read_metadata(&mut Cursor::new(file)).unwrap();
+        assert_eq!(metadata["title"], "V2 Title");
+        assert_eq!(metadata["artist"], "Artist");
+        assert_eq!(metadata["date"], "1999");
+        assert_eq!(metadata["track"], "7");
+        assert_eq!(metadata["genre"], "Rock");
+        assert!(!metadata.contains_key("album"));
+    }
+
+    #[test]
+    fn test_sample_file_tags() {
+        let mut file = std::fs::File::open("data/sample-3s.mp3").unwrap();
+        let metadata = read_metadata(&mut file).unwrap();
+        assert_eq!(metadata["encoder"], "Lavf57.83.100");
+
+        // TYERとTDRCに同じ年が入っていても連結しない
+        let mut file = std::fs::File::open("data/file_example_MP3_700KB.mp3").unwrap();
+        let metadata = read_metadata(&mut file).unwrap();
+        assert_eq!(metadata["date"], "2018");
+    }
+}
@@ -0,0 +1,1038 @@
+// Matroska / WebM（EBML）パーサー
+
+use super::{
+    default_channel_layout, mp4::aac_channel_layout, mp4::aac_profile_name, mp4::AacConfig,
+    nul_terminated_string, read_vec, stream_len, vorbis, ParseError, ParseResult,
+};
+use crate::{AudioInfo, Chapter, StreamDisposition, StreamInfo};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+// ---- 要素ID ----
+const EBML: u32 = 0x1A45DFA3;
+const DOC_TYPE: u32 = 0x4282;
+const SEGMENT: u32 = 0x18538067;
+const SEEK_HEAD: u32 = 0x114D9B74;
+const INFO: u32 = 0x1549A966;
+const TIMECODE_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const TITLE: u32 = 0x7BA9;
+const MUXING_APP: u32 = 0x4D80;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const TRACK_TYPE: u32 = 0x83;
+const FLAG_DEFAULT: u32 = 0x88;
+const FLAG_FORCED: u32 = 0x55AA;
+const FLAG_COMMENTARY: u32 = 0x55AF;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const LANGUAGE: u32 = 0x22B59C;
+const LANGUAGE_BCP47: u32 = 0x22B59D;
+const NAME: u32 = 0x536E;
+const DEFAULT_DURATION: u32 = 0x23E383;
+const AUDIO: u32 = 0xE1;
+const SAMPLING_FREQUENCY: u32 = 0xB5;
+const OUTPUT_SAMPLING_FREQUENCY: u32 = 0x78B5;
+const CHANNELS: u32 = 0x9F;
+const BIT_DEPTH: u32 = 0x6264;
+const TAGS: u32 = 0x1254C367;
+const TAG: u32 = 0x7373;
+const TARGETS: u32 = 0x63C0;
+const TAG_TRACK_UID: u32 = 0x63C5;
+const SIMPLE_TAG: u32 = 0x67C8;
+const TAG_NAME: u32 = 0x45A3;
+const TAG_STRING: u32 = 0x4487;
+const CHAPTERS: u32 = 0x1043A770;
+const EDITION_ENTRY: u32 = 0x45B9;
+const CHAPTER_ATOM: u32 = 0xB6;
+const CHAPTER_TIME_START: u32 = 0x91;
+const CHAPTER_TIME_END: u32 = 0x92;
+const CHAPTER_DISPLAY: u32 = 0x80;
+const CHAP_STRING: u32 = 0x85;
+const CHAP_LANGUAGE: u32 = 0x437C;
+const CLUSTER: u32 = 0x1F43B675;
+const CLUSTER_TIMECODE: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const BLOCK_GROUP: u32 = 0xA0;
+const BLOCK: u32 = 0xA1;
+const CUES: u32 = 0x1C53BB6B;
+const ATTACHMENTS: u32 = 0x1941A469;
+
+/// Segment直下の要素（サイズ不明のClusterの終端判定に使う）
+const LEVEL1_IDS: [u32; 8] = [
+    SEEK_HEAD,
+    INFO,
+    TRACKS,
+    TAGS,
+    CHAPTERS,
+    CLUSTER,
+    CUES,
+    ATTACHMENTS,
+];
+
+/// メモリに読み込むマスター要素（Info/Tracks/Tags/Chapters）の上限
+const MAX_MASTER_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 既定のTimecodeScale（1ms）
+const DEFAULT_TIMECODE_SCALE: u64 = 1_000_000;
+
+/// 可変長整数の長さ（先頭バイトの先行ゼロ数 + 1）
+fn vint_len(first: u8) -> Option<usize> {
+    match first.leading_zeros() {
+        n @ 0..=7 => Some(n as usize + 1),
+        _ => None,
+    }
+}
+
+/// スライス先頭の要素ID（マーカービットを含む）と長さ
+fn slice_id(data: &[u8]) -> Option<(u32, usize)> {
+    let len = vint_len(*data.first()?)?;
+    if len > 4 {
+        return None;
+    }
+    let id = data
+        .get(..len)?
+        .iter()
+        .fold(0u32, |acc, b| (acc << 8) | *b as u32);
+    Some((id, len))
+}
+
+/// スライス先頭のサイズ（全ビット1は不明サイズで`None`）と長さ
+fn slice_size(data: &[u8]) -> Option<(Option<u64>, usize)> {
+    let len = vint_len(*data.first()?)?;
+    let bytes = data.get(..len)?;
+    let mut value = (bytes[0] as u64) & (0xFF >> len);
+    for b in &bytes[1..] {
+        value = (value << 8) | *b as u64;
+    }
+    let unknown = value == (1u64 << (7 * len)) - 1;
+    Some((if unknown { None } else { Some(value) }, len))
+}
+
+/// スライス内の子要素を順に返すイテレーター
+struct Elements<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, id_len) = slice_id(self.data)?;
+        let (size, size_len) = slice_size(&self.data[id_len..])?;
+        let start = id_len + size_len;
+        // スライス内ではサイズ不明の要素は残り全体とみなす
+        let end = match size {
+            Some(size) => start.checked_add(size as usize)?,
+            None => self.data.len(),
+        };
+        if end > self.data.len() {
+            self.data = &[];
+            return None;
+        }
+        let body = &self.data[start..end];
+        self.data = &self.data[end..];
+        Some((id, body))
+    }
+}
+
+fn elements(data: &[u8]) -> Elements<'_> {
+    Elements { data }
+}
+
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .take(8)
+        .fold(0u64, |acc, b| (acc << 8) | *b as u64)
+}
+
+fn read_float(data: &[u8]) -> f64 {
+    match data.len() {
+        4 => f32::from_be_bytes(data.try_into().unwrap()) as f64,
+        8 => f64::from_be_bytes(data.try_into().unwrap()),
+        _ => 0.0,
+    }
+}
+
+/// ブロック先頭の(トラック番号, クラスター相対タイムコード)
+fn block_timecode(data: &[u8]) -> Option<(u64, i16)> {
+    let (track, len) = slice_size(data)?;
+    let timecode = data.get(len..len + 2)?;
+    Some((track?, i16::from_be_bytes([timecode[0], timecode[1]])))
+}
+
+/// トラック種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackType {
+    Video,
+    Audio,
+    Subtitle,
+    Other(u64),
+}
+
+impl TrackType {
+    /// ffprobeのcodec_type
+    pub fn codec_type(&self) -> &'static str {
+        match self {
+            TrackType::Video => "video",
+            TrackType::Audio => "audio",
+            TrackType::Subtitle => "subtitle",
+            TrackType::Other(_) => "data",
+        }
+    }
+
+    fn from_value(value: u64) -> Self {
+        match value {
+            1 => TrackType::Video,
+            2 => TrackType::Audio,
+            17 => TrackType::Subtitle,
+            other => TrackType::Other(other),
+        }
+    }
+}
+
+/// TrackEntry
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatroskaTrack {
+    pub number: u64,
+    pub uid: u64,
+    pub track_type: TrackType,
+    pub codec_id: String,
+    pub codec_private: Vec<u8>,
+    pub name: String,
+    pub language: String,
+    pub default: bool,
+    pub forced: bool,
+    pub commentary: bool,
+    /// ナノ秒
+    pub default_duration: Option<u64>,
+    pub sampling_frequency: f64,
+    pub output_sampling_frequency: Option<f64>,
+    pub channels: u64,
+    pub bit_depth: Option<u64>,
+    /// トラック対象のタグ
+    pub tags: Vec<(String, String)>,
+}
+
+impl MatroskaTrack {
+    fn parse(data: &[u8]) -> Self {
+        let mut track = MatroskaTrack {
+            number: 0,
+            uid: 0,
+            track_type: TrackType::Other(0),
+            codec_id: String::new(),
+            codec_private: Vec::new(),
+            name: String::new(),
+            // Languageの既定値
+            language: "eng".to_string(),
+            default: true,
+            forced: false,
+            commentary: false,
+            default_duration: None,
+            sampling_frequency: 8000.0,
+            output_sampling_frequency: None,
+            channels: 1,
+            bit_depth: None,
+            tags: Vec::new(),
+        };
+        let mut bcp47 = None;
+
+        for (id, body) in elements(data) {
+            match id {
+                TRACK_NUMBER => track.number = read_uint(body),
+                TRACK_UID => track.uid = read_uint(body),
+                TRACK_TYPE => track.track_type = TrackType::from_value(read_uint(body)),
+                FLAG_DEFAULT => track.default = read_uint(body) != 0,
+                FLAG_FORCED => track.forced = read_uint(body) != 0,
+                FLAG_COMMENTARY => track.commentary = read_uint(body) != 0,
+                CODEC_ID => track.codec_id = nul_terminated_string(body),
+                CODEC_PRIVATE => track.codec_private = body.to_vec(),
+                NAME => track.name = nul_terminated_string(body),
+                LANGUAGE => track.language = nul_terminated_string(body),
+                LANGUAGE_BCP47 => bcp47 = Some(nul_terminated_string(body)),
+                DEFAULT_DURATION => track.default_duration = Some(read_uint(body)),
+                AUDIO => {
+                    for (id, body) in elements(body) {
+                        match id {
+                            SAMPLING_FREQUENCY => track.sampling_frequency = read_float(body),
+                            OUTPUT_SAMPLING_FREQUENCY => {
+                                track.output_sampling_frequency = Some(read_float(body))
+                            }
+                            CHANNELS => track.channels = read_uint(body),
+                            BIT_DEPTH => track.bit_depth = Some(read_uint(body)),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // LanguageBCP47がある場合はそちらが優先される
+        if let Some(language) = bcp47 {
+            track.language = language;
+        }
+        track
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.track_type == TrackType::Audio
+    }
+
+    /// FFmpeg互換のコーデック名と正式名称
+    pub fn codec_names(&self) -> (String, String) {
+        let bits = self.bit_depth.unwrap_or(16);
+        let (name, long_name) = match self.codec_id.as_str() {
+            "A_OPUS" => ("opus", "Opus (Opus Interactive Audio Codec)"),
+            "A_VORBIS" => ("vorbis", "Vorbis"),
+            "A_FLAC" => ("flac", "FLAC (Free Lossless Audio Codec)"),
+            "A_MPEG/L3" => ("mp3", "MP3 (MPEG audio layer 3)"),
+            "A_MPEG/L2" => ("mp2", "MP2 (MPEG audio layer 2)"),
+            "A_AC3" => ("ac3", "ATSC A/52A (AC-3)"),
+            "A_EAC3" => ("eac3", "ATSC A/52B (AC-3, E-AC-3)"),
+            "A_DTS" => ("dts", "DCA (DTS Coherent Acoustics)"),
+            "A_TRUEHD" => ("truehd", "TrueHD"),
+            "A_ALAC" => ("alac", "ALAC (Apple Lossless Audio Codec)"),
+            "A_WAVPACK4" => ("wavpack", "WavPack"),
+            "A_PCM/INT/LIT" => {
+                return (
+                    format!("pcm_s{}le", bits),
+                    format!("PCM signed {}-bit little-endian", bits),
+                )
+            }
+            "A_PCM/INT/BIG" => {
+                return (
+                    format!("pcm_s{}be", bits),
+                    format!("PCM signed {}-bit big-endian", bits),
+                )
+            }
+            "A_PCM/FLOAT/IEEE" => {
+                return (
+                    format!("pcm_f{}le", bits),
+                    format!("PCM {}-bit floating point little-endian", bits),
+                )
+            }
+            id if id.starts_with("A_AAC") => ("aac", "AAC (Advanced Audio Coding)"),
+            "V_MPEG4/ISO/AVC" => ("h264", "H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10"),
+            "V_MPEGH/ISO/HEVC" => ("hevc", "H.265 / HEVC (High Efficiency Video Coding)"),
+            "V_VP8" => ("vp8", "On2 VP8"),
+            "V_VP9" => ("vp9", "Google VP9"),
+            "V_AV1" => ("av1", "Alliance for Open Media AV1"),
+            "S_TEXT/UTF8" => ("subrip", "SubRip subtitle"),
+            "S_TEXT/ASS" | "S_TEXT/SSA" => ("ass", "ASS (Advanced SSA) subtitle"),
+            "S_TEXT/WEBVTT" => ("webvtt", "WebVTT subtitle"),
+            id => return (id.to_lowercase(), id.to_string()),
+        };
+        (name.to_string(), long_name.to_string())
+    }
+
+    fn aac_config(&self) -> Option<AacConfig> {
+        if self.codec_id.starts_with("A_AAC") {
+            AacConfig::parse(&self.codec_private)
+        } else {
+            None
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        // SBRでは出力サンプルレートが実際のレート
+        self.output_sampling_frequency
+            .unwrap_or(self.sampling_frequency)
+            .round() as u32
+    }
+
+    pub fn channel_layout(&self) -> String {
+        let channels = self.channels.min(u8::MAX as u64) as u8;
+        match self.codec_id.as_str() {
+            "A_OPUS" | "A_VORBIS" | "A_FLAC" => vorbis::channel_layout(channels),
+            _ => match self.aac_config() {
+                Some(config) if config.channel_config > 0 => {
+                    aac_channel_layout(config.channel_config)
+                }
+                _ => default_channel_layout(channels as u16),
+            },
+        }
+    }
+
+    /// ストリーム一覧の1項目（indexはTracks内の順序）
+    fn stream_info(&self, index: usize) -> StreamInfo {
+        let (codec_name, codec_long_name) = self.codec_names();
+        let mut tags: HashMap<String, String> = self.tags.iter().cloned().collect();
+        // mkvmergeが書き込む統計タグ
+        let bit_rate = tags
+            .get("bps")
+            .and_then(|bps| bps.parse().ok())
+            .unwrap_or(0);
+        let title = tags
+            .remove("title")
+            .or_else(|| Some(self.name.clone()))
+            .filter(|name| !name.is_empty());
+
+        let mut stream = StreamInfo {
+            index: index as u32,
+            codec_type: self.track_type.codec_type().to_string(),
+            codec_name,
+            codec_long_name,
+            bit_rate,
+            language: Some(self.language.clone()).filter(|lang| lang != "und"),
+            title,
+            disposition: StreamDisposition {
+                default: self.default,
+                forced: self.forced,
+                commentary: self.commentary,
+                ..Default::default()
+            },
+            tags,
+            ..Default::default()
+        };
+        if self.is_audio() {
+            stream.profile = self
+                .aac_config()
+                .map(|config| aac_profile_name(config.object_type))
+                .filter(|name| !name.is_empty())
+                .map(str::to_string);
+            stream.sample_rate = self.sample_rate() as i32;
+            stream.channels = self.channels as i32;
+            stream.channel_layout = self.channel_layout();
+            stream.bits_per_sample = self.bit_depth.unwrap_or(0) as i32;
+        }
+        stream
+    }
+}
+
+/// ChapterAtom
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatroskaChapter {
+    /// ナノ秒
+    pub start: u64,
+    pub end: Option<u64>,
+    pub title: String,
+    pub language: String,
+}
+
+impl MatroskaChapter {
+    /// 終了時刻がない場合は0（`complete_chapters`で補う）
+    fn to_chapter(&self, id: usize) -> Chapter {
+        let mut chapter = Chapter {
+            id: id as i64,
+            start_time: self.start as f64 / 1e9,
+            end_time: self.end.map_or(0.0, |end| end as f64 / 1e9),
+            title: Some(self.title.clone()).filter(|title| !title.is_empty()),
+            ..Default::default()
+        };
+        if !self.language.is_empty() && self.language != "und" {
+            chapter
+                .tags
+                .insert("language".to_string(), self.language.clone());
+        }
+        chapter
+    }
+}
+
+fn parse_chapters(data: &[u8], chapters: &mut Vec<MatroskaChapter>) {
+    for (id, edition) in elements(data) {
+        if id != EDITION_ENTRY {
+            continue;
+        }
+        for (id, atom) in elements(edition) {
+            if id != CHAPTER_ATOM {
+                continue;
+            }
+            let mut chapter = MatroskaChapter {
+                start: 0,
+                end: None,
+                title: String::new(),
+                language: String::new(),
+            };
+            for (id, body) in elements(atom) {
+                match id {
+                    CHAPTER_TIME_START => chapter.start = read_uint(body),
+                    CHAPTER_TIME_END => chapter.end = Some(read_uint(body)),
+                    CHAPTER_DISPLAY if chapter.title.is_empty() => {
+                        for (id, body) in elements(body) {
+                            match id {
+                                CHAP_STRING => chapter.title = nul_terminated_string(body),
+                                CHAP_LANGUAGE => chapter.language = nul_terminated_string(body),
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            chapters.push(chapter);
+        }
+    }
+}
+
+/// SimpleTag（入れ子を含む）を(キー, 値)に展開する
+fn parse_simple_tag(data: &[u8], entries: &mut Vec<(String, String)>) {
+    let mut name = None;
+    let mut value = None;
+    for (id, body) in elements(data) {
+        match id {
+            TAG_NAME => name = Some(nul_terminated_string(body)),
+            TAG_STRING => value = Some(nul_terminated_string(body)),
+            SIMPLE_TAG => parse_simple_tag(body, entries),
+            _ => {}
+        }
+    }
+    if let (Some(name), Some(value)) = (name, value) {
+        if !value.is_empty() {
+            entries.push((name.to_lowercase(), value));
+        }
+    }
+}
+
+/// Matroskaファイル全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatroskaFile {
+    pub doc_type: String,
+    pub timecode_scale: u64,
+    /// Segment Infoの再生時間（TimecodeScale単位）
+    pub duration: Option<f64>,
+    pub title: String,
+    pub muxing_app: String,
+    pub tracks: Vec<MatroskaTrack>,
+    /// Segment全体を対象とするタグ
+    pub tags: Vec<(String, String)>,
+    pub chapters: Vec<MatroskaChapter>,
+    /// トラック番号ごとの最後のブロックのタイムコード（Durationがない場合のみ走査）
+    pub last_block_timecodes: HashMap<u64, i64>,
+}
+
+impl MatroskaFile {
+    pub fn audio_tracks(&self) -> impl Iterator<Item = &MatroskaTrack> {
+        self.tracks.iter().filter(|t| t.is_audio())
+    }
+
+    /// FlagDefaultが立っている最初の音声トラック（なければ最初の音声トラック）
+    pub fn primary_audio_track(&self) -> Option<&MatroskaTrack> {
+        self.audio_tracks()
+            .find(|t| t.default)
+            .or_else(|| self.audio_tracks().next())
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        let scale = self.timecode_scale as f64 / 1e9;
+        if let Some(duration) = self.duration {
+            return duration * scale;
+        }
+
+        // ブラウザのMediaRecorderなどはDurationを書かないため、最後のブロックから求める
+        let Some(track) = self.primary_audio_track() else {
+            return 0.0;
+        };
+        match self.last_block_timecodes.get(&track.number) {
+            Some(&timecode) => {
+                let last_frame = track.default_duration.unwrap_or(0) as f64 / 1e9;
+                timecode.max(0) as f64 * scale + last_frame
+            }
+            None => 0.0,
+        }
+    }
+
+    fn parse_info(&mut self, data: &[u8]) {
+        for (id, body) in elements(data) {
+            match id {
+                TIMECODE_SCALE => self.timecode_scale = read_uint(body),
+                DURATION => self.duration = Some(read_float(body)),
+                TITLE => self.title = nul_terminated_string(body),
+                MUXING_APP => self.muxing_app = nul_terminated_string(body),
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_tracks(&mut self, data: &[u8]) {
+        for (id, body) in elements(data) {
+            if id == TRACK_ENTRY {
+                self.tracks.push(MatroskaTrack::parse(body));
+            }
+        }
+    }
+
+    fn parse_tags(&mut self, data: &[u8]) {
+        for (id, tag) in elements(data) {
+            if id != TAG {
+                continue;
+            }
+            let track_uid = elements(tag)
+                .find(|(id, _)| *id == TARGETS)
+                .and_then(|(_, targets)| {
+                    elements(targets)
+                        .find(|(id, _)| *id == TAG_TRACK_UID)
+                        .map(|(_, body)| read_uint(body))
+                })
+                .filter(|uid| *uid != 0);
+
+            let mut entries = Vec::new();
+            for (id, body) in elements(tag) {
+                if id == SIMPLE_TAG {
+                    parse_simple_tag(body, &mut entries);
+                }
+            }
+            match track_uid.and_then(|uid| self.tracks.iter_mut().find(|t| t.uid == uid)) {
+                Some(track) => track.tags.extend(entries),
+                None => self.tags.extend(entries),
+            }
+        }
+    }
+}
+
+/// ストリーム上の要素ヘッダー（読み取り後はデータ先頭に位置する）
+struct Header {
+    id: u32,
+    size: Option<u64>,
+    data_start: u64,
+}
+
+fn read_header<R: Read + Seek>(reader: &mut R, offset: u64) -> ParseResult<Option<Header>> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut buf = [0u8; 12];
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let data = &buf[..filled];
+    let Some((id, id_len)) = slice_id(data) else {
+        return Ok(None);
+    };
+    let Some((size, size_len)) = slice_size(&data[id_len..]) else {
+        return Ok(None);
+    };
+    let data_start = offset + (id_len + size_len) as u64;
+    reader.seek(SeekFrom::Start(data_start))?;
+    Ok(Some(Header {
+        id,
+        size,
+        data_start,
+    }))
+}
+
+/// Clusterを走査してブロックのタイムコードを記録し、次の要素の位置を返す
+fn scan_cluster<R: Read + Seek>(
+    reader: &mut R,
+    cluster: &Header,
+    segment_end: u64,
+    last_block_timecodes: &mut HashMap<u64, i64>,
+) -> ParseResult<u64> {
+    let end = cluster
+        .size
+        .map(|size| cluster.data_start.saturating_add(size))
+        .unwrap_or(segment_end)
+        .min(segment_end);
+    let mut cluster_timecode = 0i64;
+    let mut offset = cluster.data_start;
+
+    while offset < end {
+        let Some(header) = read_header(reader, offset)? else {
+            break;
+        };
+        // サイズ不明のClusterは次のレベル1要素で終わる
+        if cluster.size.is_none() && LEVEL1_IDS.contains(&header.id) {
+            return Ok(offset);
+        }
+        let Some(size) = header.size else {
+            break;
+        };
+        let block = match header.id {
+            CLUSTER_TIMECODE => {
+                cluster_timecode = read_uint(&read_vec(reader, size, 8)?) as i64;
+                None
+            }
+            SIMPLE_BLOCK => block_timecode(&read_vec(reader, size.min(16), 16)?),
+            BLOCK_GROUP => {
+                let group = read_vec(reader, size, MAX_MASTER_SIZE)?;
+                elements(&group)
+                    .find(|(id, _)| *id == BLOCK)
+                    .and_then(|(_, block)| block_timecode(block))
+            }
+            _ => None,
+        };
+        if let Some((track, relative)) = block {
+            let timecode = cluster_timecode + relative as i64;
+            let last = last_block_timecodes.entry(track).or_insert(timecode);
+            *last = (*last).max(timecode);
+        }
+        offset = header.data_start.saturating_add(size);
+    }
+
+    Ok(end)
+}
+
+/// EBMLヘッダーとSegmentを走査する
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<MatroskaFile> {
+    let file_len = stream_len(reader)?;
+
+    let ebml = read_header(reader, 0)?
+        .filter(|header| header.id == EBML)
+        .ok_or_else(|| ParseError::Invalid("not an EBML file".to_string()))?;
+    let ebml_size = ebml
+        .size
+        .ok_or_else(|| ParseError::Invalid("EBML header has unknown size".to_string()))?;
+    let ebml_body = read_vec(reader, ebml_size, 4096)?;
+    let doc_type = elements(&ebml_body)
+        .find(|(id, _)| *id == DOC_TYPE)
+        .map(|(_, body)| nul_terminated_string(body))
+        .unwrap_or_else(|| "matroska".to_string());
+    if doc_type != "matroska" && doc_type != "webm" {
+        return Err(ParseError::Invalid(format!(
+            "unsupported EBML DocType '{}'",
+            doc_type
+        )));
+    }
+
+    let segment = read_header(reader, ebml.data_start.saturating_add(ebml_size))?
+        .filter(|header| header.id == SEGMENT)
+        .ok_or_else(|| ParseError::Invalid("missing Segment element".to_string()))?;
+    // ライブ録画ではSegmentのサイズが不明のまま書かれる
+    let segment_end = segment
+        .size
+        .map(|size| segment.data_start.saturating_add(size).min(file_len))
+        .unwrap_or(file_len);
+
+    let mut file = MatroskaFile {
+        doc_type,
+        timecode_scale: DEFAULT_TIMECODE_SCALE,
+        duration: None,
+        title: String::new(),
+        muxing_app: String::new(),
+        tracks: Vec::new(),
+        tags: Vec::new(),
+        chapters: Vec::new(),
+        last_block_timecodes: HashMap::new(),
+    };
+    let mut tag_elements = Vec::new();
+
+    let mut offset = segment.data_start;
+    while offset < segment_end {
+        let Some(header) = read_header(reader, offset)? else {
+            break;
+        };
+        // 要素ヘッダーがSegmentの末尾をはみ出している（Segmentが途中で切れている）
+        if header.data_start > segment_end {
+            break;
+        }
+        let remaining = segment_end - header.data_start;
+
+        if header.id == CLUSTER {
+            if file.duration.is_none() {
+                offset =
+                    scan_cluster(reader, &header, segment_end, &mut file.last_block_timecodes)?;
+                continue;
+            }
+        } else if matches!(header.id, INFO | TRACKS | TAGS | CHAPTERS) {
+            let size = header.size.unwrap_or(remaining).min(remaining);
+            let body = read_vec(reader, size, MAX_MASTER_SIZE)?;
+            match header.id {
+                INFO => file.parse_info(&body),
+                TRACKS => file.parse_tracks(&body),
+                // トラックUIDの対応付けのため、Tagsは最後にまとめて処理する
+                TAGS => tag_elements.push(body),
+                _ => parse_chapters(&body, &mut file.chapters),
+            }
+        }
+
+        match header.size {
+            Some(size) => offset = header.data_start.saturating_add(size),
+            None => break,
+        }
+    }
+
+    for tags in &tag_elements {
+        file.parse_tags(tags);
+    }
+
+    Ok(file)
+}
+
+/// Matroska/WebMファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let mkv = read(reader)?;
+    let track = mkv
+        .primary_audio_track()
+        .ok_or_else(|| ParseError::Invalid("Matroska file has no audio track".to_string()))?;
+    let (codec_name, codec_long_name) = track.codec_names();
+
+    audio_info.format_name = "matroska,webm".to_string();
+    audio_info.format_long_name = "Matroska / WebM".to_string();
+    audio_info.codec_name = codec_name;
+    audio_info.codec_long_name = codec_long_name;
+    audio_info.sample_rate = track.sample_rate() as i32;
+    audio_info.channels = track.channels as i32;
+    audio_info.bits_per_sample = track.bit_depth.unwrap_or(0) as i32;
+    audio_info.channel_layout = track.channel_layout();
+    audio_info.has_video = mkv.tracks.iter().any(|t| t.track_type == TrackType::Video);
+    audio_info.duration_seconds = mkv.duration_seconds();
+    if audio_info.duration_seconds > 0.0 {
+        audio_info.bit_rate =
+            (audio_info.file_size as f64 * 8.0 / audio_info.duration_seconds).round() as i64;
+    }
+
+    if !mkv.title.is_empty() {
+        audio_info
+            .metadata
+            .insert("title".to_string(), mkv.title.clone());
+    }
+    if !mkv.muxing_app.is_empty() {
+        audio_info
+            .metadata
+            .insert("encoder".to_string(), mkv.muxing_app.clone());
+    }
+    for (key, value) in mkv.tags.iter().chain(&track.tags) {
+        audio_info.metadata.insert(key.clone(), value.clone());
+    }
+    if track.language != "und" {
+        audio_info
+            .metadata
+            .entry("language".to_string())
+            .or_insert_with(|| track.language.clone());
+    }
+
+    audio_info.streams = mkv
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| track.stream_info(index))
+        .collect();
+    audio_info.chapters = mkv
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(id, chapter)| chapter.to_chapter(id))
+        .collect();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn el(id: u32, body: &[u8]) -> Vec<u8> {
+        let id_bytes = id.to_be_bytes();
+        let skip = id_bytes.iter().position(|b| *b != 0).unwrap_or(3);
+        let mut out = id_bytes[skip..].to_vec();
+        // 短い要素は1バイト、それ以外は8バイトのサイズ
+        if body.len() < 0x7F {
+            out.push(0x80 | body.len() as u8);
+        } else {
+            out.push(0x01);
+            out.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+        }
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn unknown_size(id: u32, body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_be_bytes().to_vec();
+        out.push(0xFF);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn uint(id: u32, value: u64) -> Vec<u8> {
+        el(id, &value.to_be_bytes())
+    }
+
+    fn ebml_header(doc_type: &str) -> Vec<u8> {
+        el(EBML, &el(DOC_TYPE, doc_type.as_bytes()))
+    }
+
+    fn audio_track(number: u64, codec: &str, language: &str, default: bool) -> Vec<u8> {
+        el(
+            TRACK_ENTRY,
+            &[
+                uint(TRACK_NUMBER, number),
+                uint(TRACK_UID, number * 100),
+                uint(TRACK_TYPE, 2),
+                uint(FLAG_DEFAULT, default as u64),
+                el(CODEC_ID, codec.as_bytes()),
+                el(LANGUAGE, language.as_bytes()),
+                el(
+                    AUDIO,
+                    &[
+                        el(SAMPLING_FREQUENCY, &48000f64.to_be_bytes()),
+                        uint(CHANNELS, 2),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat(),
+        )
+    }
+
+    #[test]
+    fn test_mkv_with_multiple_audio_tracks() {
+        let info = el(
+            INFO,
+            &[
+                uint(TIMECODE_SCALE, 1_000_000),
+                el(DURATION, &12_500f64.to_be_bytes()),
+                el(MUXING_APP, b"libwebm-0.3.0.0"),
+            ]
+            .concat(),
+        );
+        let tracks = el(
+            TRACKS,
+            &[
+                audio_track(1, "A_VORBIS", "eng", false),
+                audio_track(2, "A_OPUS", "jpn", true),
+            ]
+            .concat(),
+        );
+        let simple_tag = |name: &str, value: &str| {
+            el(
+                SIMPLE_TAG,
+                &[
+                    el(TAG_NAME, name.as_bytes()),
+                    el(TAG_STRING, value.as_bytes()),
+                ]
+                .concat(),
+            )
+        };
+        let tags = el(
+            TAGS,
+            &[
+                el(
+                    TAG,
+                    &[el(TARGETS, &[]), simple_tag("ARTIST", "Someone")].concat(),
+                ),
+                el(
+                    TAG,
+                    &[
+                        el(TARGETS, &uint(TAG_TRACK_UID, 200)),
+                        simple_tag("TITLE", "Japanese Commentary"),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat(),
+        );
+        let chapters = el(
+            CHAPTERS,
+            &el(
+                EDITION_ENTRY,
+                &el(
+                    CHAPTER_ATOM,
+                    &[
+                        uint(CHAPTER_TIME_START, 0),
+                        uint(CHAPTER_TIME_END, 5_000_000_000),
+                        el(CHAPTER_DISPLAY, &el(CHAP_STRING, b"Intro")),
+                    ]
+                    .concat(),
+                ),
+            ),
+        );
+        let cluster = el(
+            CLUSTER,
+            &[
+                uint(CLUSTER_TIMECODE, 0),
+                el(SIMPLE_BLOCK, &[0x82, 0, 0, 0x80]),
+            ]
+            .concat(),
+        );
+
+        let mut bytes = ebml_header("matroska");
+        bytes.extend(el(
+            SEGMENT,
+            &[info, tracks, cluster, tags, chapters].concat(),
+        ));
+
+        let mkv = read(&mut Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(mkv.chapters.len(), 1);
+        assert_eq!(mkv.chapters[0].title, "Intro");
+        assert_eq!(mkv.chapters[0].end, Some(5_000_000_000));
+
+        let mut info = AudioInfo::new("test.mkv".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "opus");
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.channel_layout, "stereo");
+        assert_eq!(info.duration_seconds, 12.5);
+        assert_eq!(info.metadata["artist"], "Someone");
+        assert_eq!(info.metadata["title"], "Japanese Commentary");
+        assert_eq!(info.metadata["language"], "jpn");
+        assert_eq!(info.metadata["encoder"], "libwebm-0.3.0.0");
+        assert!(info.warnings.is_empty());
+
+        assert_eq!(info.streams.len(), 2);
+        let (first, second) = (&info.streams[0], &info.streams[1]);
+        assert_eq!((first.index, first.codec_name.as_str()), (0, "vorbis"));
+        assert_eq!(first.language.as_deref(), Some("eng"));
+        assert!(!first.disposition.default);
+        assert_eq!((second.index, second.codec_name.as_str()), (1, "opus"));
+        assert_eq!(second.title.as_deref(), Some("Japanese Commentary"));
+        assert!(second.disposition.default);
+        assert_eq!(second.channel_layout, "stereo");
+
+        assert_eq!(info.chapters.len(), 1);
+        assert_eq!(info.chapters[0].title.as_deref(), Some("Intro"));
+        assert_eq!(info.chapters[0].end_time, 5.0);
+    }
+
+    #[test]
+    fn test_live_webm_without_duration() {
+        let info = el(INFO, &uint(TIMECODE_SCALE, 1_000_000));
+        let tracks = el(TRACKS, &audio_track(1, "A_OPUS", "und", true));
+
+        // MediaRecorder形式: サイズ不明のSegmentとCluster、20msごとのSimpleBlock
+        let block = |relative: i16| {
+            let mut body = vec![0x81];
+            body.extend_from_slice(&relative.to_be_bytes());
+            body.extend_from_slice(&[0x80, 0xFC, 0xFF, 0xFE]);
+            el(SIMPLE_BLOCK, &body)
+        };
+        let cluster = |timecode: u64| {
+            let mut body = uint(CLUSTER_TIMECODE, timecode);
+            for i in 0..50 {
+                body.extend(block(i * 20));
+            }
+            unknown_size(CLUSTER, &body)
+        };
+
+        let mut bytes = ebml_header("webm");
+        bytes.extend(unknown_size(
+            SEGMENT,
+            &[info, tracks, cluster(0), cluster(1000), cluster(2000)].concat(),
+        ));
+
+        let mut audio_info = AudioInfo::new("capture.webm".into());
+        probe(&mut Cursor::new(bytes), &mut audio_info).unwrap();
+        assert_eq!(audio_info.codec_name, "opus");
+        assert!((audio_info.duration_seconds - 2.98).abs() < 1e-9);
+        assert!(!audio_info.metadata.contains_key("language"));
+    }
+
+    #[test]
+    fn test_truncated_segment_does_not_underflow() {
+        // Segmentのサイズは3バイトだが、その中にInfoのヘッダーが続く
+        let mut bytes = ebml_header("webm");
+        bytes.extend_from_slice(&SEGMENT.to_be_bytes());
+        bytes.push(0x83);
+        bytes.extend(el(INFO, &uint(TIMECODE_SCALE, 1_000_000)));
+
+        let mkv = read(&mut Cursor::new(bytes.clone())).unwrap();
+        assert!(mkv.tracks.is_empty());
+        assert_eq!(mkv.timecode_scale, DEFAULT_TIMECODE_SCALE);
+
+        let mut audio_info = AudioInfo::new("truncated.webm".into());
+        assert!(probe(&mut Cursor::new(bytes), &mut audio_info).is_err());
+    }
+
+    #[test]
+    fn test_not_a_matroska_file() {
+        let result = read(&mut Cursor::new(b"Test WAV file content\n".to_vec()));
+        assert!(result.is_err());
+    }
+}
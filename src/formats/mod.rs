@@ -0,0 +1,486 @@
+// ネイティブ（ffprobe非依存）の音声フォーマットパーサー群
+
+pub mod ac3;
+pub mod adts;
+pub mod aiff;
+pub mod amr;
+pub mod ape;
+pub mod apetag;
+pub mod artwork;
+pub mod asf;
+pub mod au;
+pub mod bwf;
+pub mod dsdiff;
+pub mod dsf;
+pub mod dts;
+pub mod flac;
+pub mod id3;
+pub mod matroska;
+pub mod mp4;
+pub mod mpeg;
+pub mod ogg;
+pub mod realmedia;
+pub mod sniff;
+pub mod vorbis;
+pub mod wav;
+pub mod wavpack;
+
+use crate::backend::BackendKind;
+use crate::{AudioInfo, AudioProbeError, Chapter, StreamInfo};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("No native parser available for this format")]
+    Unsupported,
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// ファイルをネイティブパーサーで解析し、`AudioInfo`を返す
+///
+/// 内容から判定したコンテナがあればそれを優先し、判定できない場合は拡張子で選ぶ
+pub fn probe_file(
+    path: &Path,
+    container: Option<sniff::Container>,
+) -> Result<AudioInfo, AudioProbeError> {
+    let io_error = |source| AudioProbeError::Io {
+        path: path.to_path_buf(),
+        source,
+        backend: Some(BackendKind::Native),
+    };
+    let file = File::open(path).map_err(io_error)?;
+    let file_size = file.metadata().map_err(io_error)?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut audio_info = AudioInfo::new(path.to_path_buf());
+    audio_info.file_size = file_size;
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+    let format_key = container
+        .map(|c| c.primary_extension())
+        .unwrap_or(extension.as_str());
+
+    let result = match format_key {
+        "wav" | "wave" | "bwf" | "rf64" | "bw64" => wav::probe(&mut reader, &mut audio_info),
+        "aiff" | "aif" | "aifc" => aiff::probe(&mut reader, &mut audio_info),
+        "mp3" | "mp2" | "mp1" => mpeg::probe(&mut reader, &mut audio_info),
+        "aac" | "adts" => adts::probe(&mut reader, &mut audio_info),
+        "ac3" | "eac3" | "ec3" => ac3::probe(&mut reader, &mut audio_info),
+        "dts" | "cpt" => dts::probe(&mut reader, &mut audio_info),
+        "flac" => flac::probe(&mut reader, &mut audio_info),
+        "ogg" | "oga" | "opus" | "spx" => ogg::probe(&mut reader, &mut audio_info),
+        "m4a" | "m4b" | "m4p" | "mp4" | "mov" | "3gp" => mp4::probe(&mut reader, &mut audio_info),
+        "wma" | "asf" | "wmv" => asf::probe(&mut reader, &mut audio_info),
+        "mka" | "mkv" | "webm" | "mk3d" => matroska::probe(&mut reader, &mut audio_info),
+        "ape" => ape::probe(&mut reader, &mut audio_info),
+        "wv" => wavpack::probe(&mut reader, &mut audio_info),
+        "amr" | "awb" => amr::probe(&mut reader, &mut audio_info),
+        "au" | "snd" => au::probe(&mut reader, &mut audio_info),
+        "rm" | "ra" | "rmvb" => realmedia::probe(&mut reader, &mut audio_info),
+        "dsf" => dsf::probe(&mut reader, &mut audio_info),
+        "dff" => dsdiff::probe(&mut reader, &mut audio_info),
+        _ => Err(ParseError::Unsupported),
+    };
+
+    result.map_err(|e| AudioProbeError::InvalidAudioFile {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+
+    if audio_info.profile.is_none() {
+        audio_info.profile = audio_info.codec_params.get("profile").cloned();
+    }
+    if audio_info.sample_fmt.is_none() {
+        audio_info.sample_fmt =
+            sample_fmt(&audio_info.codec_name, audio_info.bits_per_sample).map(str::to_string);
+    }
+
+    // 複数ストリームを扱うパーサー以外は要約フィールドを唯一のストリームとする
+    if audio_info.streams.is_empty() {
+        audio_info
+            .streams
+            .push(StreamInfo::from_summary(&audio_info));
+    }
+    for stream in &mut audio_info.streams {
+        if stream.sample_fmt.is_none() {
+            stream.sample_fmt =
+                sample_fmt(&stream.codec_name, stream.bits_per_sample).map(str::to_string);
+        }
+    }
+    audio_info.nb_streams = Some(audio_info.streams.len() as u32);
+    complete_chapters(&mut audio_info.chapters, audio_info.duration_seconds);
+
+    Ok(audio_info)
+}
+
+/// 終了時刻が記録されていないチャプターを次のチャプターの開始時刻か再生時間で補う
+pub(crate) fn complete_chapters(chapters: &mut [Chapter], duration: f64) {
+    for i in 0..chapters.len() {
+        let start = chapters[i].start_time;
+        if chapters[i].end_time > start {
+            continue;
+        }
+        chapters[i].end_time = chapters
+            .get(i + 1)
+            .map(|next| next.start_time)
+            .filter(|next| *next > start)
+            .unwrap_or(duration.max(start));
+    }
+}
+
+/// FFmpegのデコーダーが出力するサンプルフォーマット（PCMとFLACのみ推定する）
+pub(crate) fn sample_fmt(codec_name: &str, bits_per_sample: i32) -> Option<&'static str> {
+    if codec_name == "flac" {
+        return match bits_per_sample {
+            1..=16 => Some("s16"),
+            17..=32 => Some("s32"),
+            _ => None,
+        };
+    }
+    let pcm = codec_name.strip_prefix("pcm_")?;
+    let planar = pcm.ends_with("_planar");
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|p| pcm.starts_with(p));
+    Some(if starts(&["alaw", "mulaw", "s16", "u16"]) {
+        if planar {
+            "s16p"
+        } else {
+            "s16"
+        }
+    } else if starts(&["s24", "u24", "s32", "u32"]) {
+        if planar {
+            "s32p"
+        } else {
+            "s32"
+        }
+    } else if starts(&["u8", "s8"]) {
+        "u8"
+    } else if starts(&["s64"]) {
+        "s64"
+    } else if starts(&["f32", "f24", "f16"]) {
+        "flt"
+    } else if starts(&["f64"]) {
+        "dbl"
+    } else {
+        return None;
+    })
+}
+
+/// 同一キーの値は";"で連結してメタデータに追加する（既にある値は重ねない）
+pub(crate) fn merge_metadata(
+    metadata: &mut HashMap<String, String>,
+    entries: impl IntoIterator<Item = (String, String)>,
+) {
+    for (key, value) in entries {
+        metadata
+            .entry(key)
+            .and_modify(|existing| {
+                if !existing.split(';').any(|v| v == value) {
+                    existing.push(';');
+                    existing.push_str(&value);
+                }
+            })
+            .or_insert(value);
+    }
+}
+
+// ---- バイト読み取りヘルパー ----
+
+pub(crate) fn read_array<const N: usize, R: Read>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+pub(crate) fn read_u32_le<R: Read>(reader: &mut R) -> io::Result<u32> {
+    read_array(reader).map(u32::from_le_bytes)
+}
+
+pub(crate) fn read_u16_be<R: Read>(reader: &mut R) -> io::Result<u16> {
+    read_array(reader).map(u16::from_be_bytes)
+}
+
+pub(crate) fn read_u32_be<R: Read>(reader: &mut R) -> io::Result<u32> {
+    read_array(reader).map(u32::from_be_bytes)
+}
+
+/// 現在位置から`len`バイト読み飛ばす
+pub(crate) fn skip_bytes<R: Seek>(reader: &mut R, len: i64) -> io::Result<()> {
+    reader.seek(SeekFrom::Current(len)).map(|_| ())
+}
+
+/// 長さ（`len_bytes`が1ならu8、それ以外はビッグエンディアンのu16）付きの文字列
+pub(crate) fn read_prefixed_string<R: Read>(
+    reader: &mut R,
+    len_bytes: usize,
+) -> io::Result<String> {
+    let len = match len_bytes {
+        1 => read_array::<1, _>(reader)?[0] as usize,
+        _ => read_u16_be(reader)? as usize,
+    };
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    Ok(nul_terminated_string(&buf).trim().to_string())
+}
+
+/// 現在位置を保ったままストリーム全体の長さを取得する
+pub(crate) fn stream_len<R: Seek>(reader: &mut R) -> io::Result<u64> {
+    let position = reader.stream_position()?;
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(position))?;
+    Ok(len)
+}
+
+/// 読み込み上限付きでバイト列を読む（壊れたサイズ値による巨大確保を防ぐ）
+pub(crate) fn read_vec<R: Read>(reader: &mut R, len: u64, limit: u64) -> ParseResult<Vec<u8>> {
+    if len > limit {
+        return Err(ParseError::Invalid(format!(
+            "chunk of {} bytes exceeds limit of {} bytes",
+            len, limit
+        )));
+    }
+    let mut buf = vec![0u8; len as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+// ---- スライス読み取りヘルパー（範囲外は`None`） ----
+
+pub(crate) fn be_u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn be_u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn be_u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn le_u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// 最初のNULまで（なければ全体）の文字列
+pub(crate) fn nul_terminated_string(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+/// `*pos`から長さ（リトルエンディアンのu32）付きの文字列を読み、`*pos`を進める
+pub(crate) fn le_prefixed_string_at(data: &[u8], pos: &mut usize) -> Option<String> {
+    let len = le_u32_at(data, *pos)? as usize;
+    let start = *pos + 4;
+    let bytes = data.get(start..start.checked_add(len)?)?;
+    *pos = start + len;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// 前方向の読み取りを効率化するための簡易ウィンドウバッファ
+pub(crate) struct Window<'a, R> {
+    reader: &'a mut R,
+    base: u64,
+    data: Vec<u8>,
+}
+
+impl<'a, R: Read + Seek> Window<'a, R> {
+    /// 一度に読み込む単位
+    const SIZE: usize = 64 * 1024;
+
+    pub(crate) fn new(reader: &'a mut R) -> Self {
+        Self {
+            reader,
+            base: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// `offset`から`len`バイトを返す（ファイル終端を超える場合は`None`）
+    pub(crate) fn get(&mut self, offset: u64, len: usize) -> ParseResult<Option<&[u8]>> {
+        let in_window =
+            offset >= self.base && offset + len as u64 <= self.base + self.data.len() as u64;
+        if !in_window {
+            self.reader.seek(SeekFrom::Start(offset))?;
+            self.data.clear();
+            self.reader
+                .by_ref()
+                .take(Self::SIZE.max(len) as u64)
+                .read_to_end(&mut self.data)?;
+            self.base = offset;
+            if self.data.len() < len {
+                return Ok(None);
+            }
+        }
+        let start = (offset - self.base) as usize;
+        Ok(Some(&self.data[start..start + len]))
+    }
+}
+
+/// MSBファーストのビットリーダー
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// `count`ビット（最大32）を読む（データ終端を超える場合は`None`）
+    pub(crate) fn read(&mut self, count: usize) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..count {
+            let byte = *self.data.get(self.pos / 8)?;
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    pub(crate) fn skip(&mut self, count: usize) -> Option<()> {
+        self.read(count).map(|_| ())
+    }
+}
+
+/// 連続した同期フレームを数えた結果
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FrameScan {
+    pub first_offset: u64,
+    pub frame_count: u64,
+    pub sample_count: u64,
+    pub byte_count: u64,
+}
+
+/// エレメンタリーストリームの同期フレームを走査する
+///
+/// `parse`はフレーム先頭`header_len`バイトから(フレーム長, サンプル数)を返す。
+/// 最初のフレームは続くフレームも同期が取れる位置を`max_search`バイトまで探す。
+pub(crate) fn scan_frames<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    end: u64,
+    header_len: usize,
+    max_search: u64,
+    parse: impl Fn(&[u8]) -> Option<(u64, u64)>,
+) -> ParseResult<Option<FrameScan>> {
+    let mut window = Window::new(reader);
+
+    let header_at = |window: &mut Window<R>, offset: u64| -> ParseResult<Option<(u64, u64)>> {
+        if offset + header_len as u64 > end {
+            return Ok(None);
+        }
+        Ok(window
+            .get(offset, header_len)?
+            .and_then(&parse)
+            .filter(|(len, _)| *len >= header_len as u64))
+    };
+
+    let search_end = (start + max_search).min(end);
+    let mut first = None;
+    for offset in start..search_end {
+        let Some((len, _)) = header_at(&mut window, offset)? else {
+            continue;
+        };
+        if offset + len >= end || header_at(&mut window, offset + len)?.is_some() {
+            first = Some(offset);
+            break;
+        }
+    }
+    let Some(first_offset) = first else {
+        return Ok(None);
+    };
+
+    let mut scan = FrameScan {
+        first_offset,
+        frame_count: 0,
+        sample_count: 0,
+        byte_count: 0,
+    };
+    let mut offset = first_offset;
+    while let Some((len, samples)) = header_at(&mut window, offset)? {
+        let len = len.min(end - offset);
+        scan.frame_count += 1;
+        scan.sample_count += samples;
+        scan.byte_count += len;
+        offset += len;
+    }
+    Ok(Some(scan))
+}
+
+// ---- チャンネルレイアウト ----
+
+/// WAVE_FORMAT_EXTENSIBLEのdwChannelMaskのビット順のスピーカー名
+const SPEAKER_NAMES: [&str; 18] = [
+    "FL", "FR", "FC", "LFE", "BL", "BR", "FLC", "FRC", "BC", "SL", "SR", "TC", "TFL", "TFC", "TFR",
+    "TBL", "TBC", "TBR",
+];
+
+/// FFmpegと同じ名前を持つ既知のチャンネルレイアウト
+const NAMED_LAYOUTS: [(u32, &str); 20] = [
+    (0x4, "mono"),
+    (0x3, "stereo"),
+    (0xB, "2.1"),
+    (0x7, "3.0"),
+    (0x103, "3.0(back)"),
+    (0x107, "4.0"),
+    (0x33, "quad"),
+    (0x603, "quad(side)"),
+    (0xF, "3.1"),
+    (0x37, "5.0"),
+    (0x607, "5.0(side)"),
+    (0x10F, "4.1"),
+    (0x3F, "5.1"),
+    (0x60F, "5.1(side)"),
+    (0x707, "6.0"),
+    (0x70F, "6.1"),
+    (0x637, "7.0"),
+    (0x63F, "7.1"),
+    (0xFF, "7.1(wide)"),
+    (0x6CF, "7.1(wide-side)"),
+];
+
+/// スピーカーマスクをFFmpeg互換のチャンネルレイアウト名に変換する
+pub(crate) fn channel_layout_from_mask(mask: u32, channels: u16) -> String {
+    if mask == 0 {
+        return default_channel_layout(channels);
+    }
+    if let Some((_, name)) = NAMED_LAYOUTS.iter().find(|(m, _)| *m == mask) {
+        return name.to_string();
+    }
+
+    let speakers: Vec<&str> = SPEAKER_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| mask & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    format!("{} channels ({})", speakers.len(), speakers.join("+"))
+}
+
+/// マスク情報がない場合のチャンネル数からのレイアウト推定
+pub(crate) fn default_channel_layout(channels: u16) -> String {
+    match channels {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        _ => String::new(),
+    }
+}
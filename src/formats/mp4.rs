@@ -0,0 +1,1506 @@
+// ISO-BMFF (MP4/M4A/M4B) ボックスウォーカー
+
+use super::{
+    artwork, be_u16_at, be_u32_at, be_u64_at, channel_layout_from_mask,
+    flac::{Picture, StreamInfo},
+    id3, merge_metadata, read_array, read_vec, stream_len, BitReader, ParseError, ParseResult,
+};
+use crate::{Artwork, AudioInfo, Chapter, StreamDisposition};
+use std::io::{Read, Seek, SeekFrom};
+
+/// `moov`ボックスをメモリに読み込む上限（長時間のオーディオブックでもstszは数MB程度）
+const MAX_MOOV_SIZE: u64 = 256 * 1024 * 1024;
+
+/// `moof`ボックスをメモリに読み込む上限
+const MAX_MOOF_SIZE: u64 = 16 * 1024 * 1024;
+
+/// QuickTimeチャプタートラックのサンプル数の上限
+const MAX_CHAPTER_SAMPLES: usize = 4096;
+
+/// チャプターテキストのサンプル1件の読み込み上限
+const MAX_TEXT_SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// AACのsamplingFrequencyIndex
+pub(crate) const AAC_SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// AACのaudioObjectTypeをFFmpegのプロファイル名に変換する
+pub(crate) fn aac_profile_name(object_type: u8) -> &'static str {
+    match object_type {
+        1 => "Main",
+        2 => "LC",
+        3 => "SSR",
+        4 => "LTP",
+        5 => "HE-AAC",
+        23 => "LD",
+        29 => "HE-AACv2",
+        39 => "ELD",
+        _ => "",
+    }
+}
+
+/// AACのchannelConfigurationに対応するレイアウト名
+pub(crate) fn aac_channel_layout(channel_config: u8) -> String {
+    let mask = match channel_config {
+        1 => 0x4,
+        2 => 0x3,
+        3 => 0x7,
+        4 => 0x107,
+        5 => 0x37,
+        6 => 0x3F,
+        7 => 0xFF,
+        _ => return String::new(),
+    };
+    channel_layout_from_mask(mask, 0)
+}
+
+/// AACのchannelConfigurationに対応するチャンネル数
+pub(crate) fn aac_channel_count(channel_config: u8) -> u16 {
+    match channel_config {
+        1..=6 => channel_config as u16,
+        7 => 8,
+        _ => 0,
+    }
+}
+
+/// スライス内の子ボックスを順に返すイテレーター
+struct Boxes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Boxes<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < 8 {
+            return None;
+        }
+        let size = u32::from_be_bytes(self.data[0..4].try_into().unwrap()) as u64;
+        let kind: [u8; 4] = self.data[4..8].try_into().unwrap();
+        let (header_len, total) = match size {
+            0 => (8, self.data.len() as u64),
+            1 if self.data.len() >= 16 => {
+                (16, u64::from_be_bytes(self.data[8..16].try_into().unwrap()))
+            }
+            _ => (8, size),
+        };
+        if total < header_len || total > self.data.len() as u64 {
+            self.data = &[];
+            return None;
+        }
+        let body = &self.data[header_len as usize..total as usize];
+        self.data = &self.data[total as usize..];
+        Some((kind, body))
+    }
+}
+
+fn boxes(data: &[u8]) -> Boxes<'_> {
+    Boxes { data }
+}
+
+fn find_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).find(|(k, _)| k == kind).map(|(_, body)| body)
+}
+
+/// mvhd/mdhdの(タイムスケール, 再生時間)
+fn parse_time_header(data: &[u8]) -> Option<(u32, u64)> {
+    match data.first()? {
+        1 => Some((be_u32_at(data, 20)?, be_u64_at(data, 24)?)),
+        _ => Some((be_u32_at(data, 12)?, be_u32_at(data, 16)? as u64)),
+    }
+}
+
+/// mdhdのパックされたISO-639-2/T言語コード
+fn parse_language(data: &[u8]) -> String {
+    let offset = if data.first() == Some(&1) { 32 } else { 20 };
+    let Some(packed) = be_u16_at(data, offset) else {
+        return String::new();
+    };
+    let code: String = [(packed >> 10) & 0x1F, (packed >> 5) & 0x1F, packed & 0x1F]
+        .iter()
+        .map(|c| (*c as u8 + 0x60) as char)
+        .collect();
+    if code.chars().all(|c| c.is_ascii_lowercase()) {
+        code
+    } else {
+        String::new()
+    }
+}
+
+/// MPEG-4記述子（タグ + 可変長サイズ）を読む
+fn read_descriptor(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *data.first()?;
+    let mut size = 0usize;
+    let mut pos = 1;
+    for _ in 0..4 {
+        let byte = *data.get(pos)?;
+        pos += 1;
+        size = (size << 7) | (byte & 0x7F) as usize;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    let body = data.get(pos..pos + size)?;
+    Some((tag, body, &data[pos + size..]))
+}
+
+/// AudioSpecificConfig
+#[derive(Debug, Clone, PartialEq)]
+pub struct AacConfig {
+    pub object_type: u8,
+    /// SBR使用時は拡張後（出力）のサンプルレート
+    pub sample_rate: u32,
+    pub channel_config: u8,
+}
+
+impl AacConfig {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut bits = BitReader::new(data);
+        let read_object_type = |bits: &mut BitReader| -> Option<u8> {
+            let object_type = bits.read(5)? as u8;
+            if object_type == 31 {
+                Some(32 + bits.read(6)? as u8)
+            } else {
+                Some(object_type)
+            }
+        };
+        let read_sample_rate = |bits: &mut BitReader| -> Option<u32> {
+            match bits.read(4)? {
+                0xF => bits.read(24),
+                index => AAC_SAMPLE_RATES.get(index as usize).copied(),
+            }
+        };
+
+        let object_type = read_object_type(&mut bits)?;
+        let mut sample_rate = read_sample_rate(&mut bits)?;
+        let channel_config = bits.read(4)? as u8;
+
+        // 明示的なSBR/PSシグナリング（拡張サンプルレートが出力レート）
+        if object_type == 5 || object_type == 29 {
+            sample_rate = read_sample_rate(&mut bits)?;
+        }
+
+        Some(Self {
+            object_type,
+            sample_rate,
+            channel_config,
+        })
+    }
+}
+
+/// サンプルエントリーのコーデック固有設定
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodecConfig {
+    Aac {
+        config: Option<AacConfig>,
+        avg_bit_rate: u32,
+    },
+    Mp3 {
+        avg_bit_rate: u32,
+    },
+    Alac {
+        bit_depth: u8,
+        channels: u8,
+        sample_rate: u32,
+        avg_bit_rate: u32,
+    },
+    Opus {
+        channels: u8,
+        pre_skip: u16,
+    },
+    Flac(StreamInfo),
+    Ac3,
+    Eac3,
+    Other,
+}
+
+/// `stsd`の音声サンプルエントリー
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSampleEntry {
+    pub format: [u8; 4],
+    pub channels: u16,
+    pub sample_size: u16,
+    pub sample_rate: u32,
+    pub codec: CodecConfig,
+}
+
+impl AudioSampleEntry {
+    fn parse(format: [u8; 4], body: &[u8]) -> Option<Self> {
+        // SampleEntry(8) + version(2) revision(2) vendor(4) + channels(2) samplesize(2) + ...
+        let version = be_u16_at(body, 8)?;
+        let mut entry = AudioSampleEntry {
+            format,
+            channels: be_u16_at(body, 16)?,
+            sample_size: be_u16_at(body, 18)?,
+            sample_rate: be_u32_at(body, 24)? >> 16,
+            codec: CodecConfig::Other,
+        };
+        // QuickTimeのサウンド記述バージョン1/2は拡張フィールドを持つ
+        let children_offset = match version {
+            1 => 28 + 16,
+            2 => {
+                entry.sample_rate = f64::from_bits(be_u64_at(body, 32)?) as u32;
+                entry.channels = be_u32_at(body, 40)? as u16;
+                28 + 36
+            }
+            _ => 28,
+        };
+        let children = body.get(children_offset..).unwrap_or(&[]);
+
+        entry.codec = match &format {
+            b"mp4a" => {
+                // QuickTimeでは`wave`ボックスの中にesdsが入る
+                let esds = find_box(children, b"esds")
+                    .or_else(|| find_box(children, b"wave").and_then(|w| find_box(w, b"esds")));
+                esds.and_then(|esds| parse_esds(esds.get(4..)?))
+                    .unwrap_or(CodecConfig::Aac {
+                        config: None,
+                        avg_bit_rate: 0,
+                    })
+            }
+            b"alac" => find_box(children, b"alac")
+                .and_then(|alac| {
+                    Some(CodecConfig::Alac {
+                        bit_depth: *alac.get(9)?,
+                        channels: *alac.get(13)?,
+                        avg_bit_rate: be_u32_at(alac, 24)?,
+                        sample_rate: be_u32_at(alac, 28)?,
+                    })
+                })
+                .unwrap_or(CodecConfig::Other),
+            b"Opus" => find_box(children, b"dOps")
+                .and_then(|dops| {
+                    Some(CodecConfig::Opus {
+                        channels: *dops.get(1)?,
+                        pre_skip: be_u16_at(dops, 2)?,
+                    })
+                })
+                .unwrap_or(CodecConfig::Other),
+            b"fLaC" => find_box(children, b"dfLa")
+                .and_then(|dfla| StreamInfo::parse(dfla.get(8..)?).ok())
+                .map(CodecConfig::Flac)
+                .unwrap_or(CodecConfig::Other),
+            b"ac-3" => CodecConfig::Ac3,
+            b"ec-3" => CodecConfig::Eac3,
+            b".mp3" => CodecConfig::Mp3 { avg_bit_rate: 0 },
+            _ => CodecConfig::Other,
+        };
+        Some(entry)
+    }
+
+    pub fn codec_names(&self) -> (String, String) {
+        let (name, long_name) = match &self.codec {
+            CodecConfig::Aac { .. } => ("aac", "AAC (Advanced Audio Coding)"),
+            CodecConfig::Mp3 { .. } => ("mp3", "MP3 (MPEG audio layer 3)"),
+            CodecConfig::Alac { .. } => ("alac", "ALAC (Apple Lossless Audio Codec)"),
+            CodecConfig::Opus { .. } => ("opus", "Opus (Opus Interactive Audio Codec)"),
+            CodecConfig::Flac(_) => ("flac", "FLAC (Free Lossless Audio Codec)"),
+            CodecConfig::Ac3 => ("ac3", "ATSC A/52A (AC-3)"),
+            CodecConfig::Eac3 => ("eac3", "ATSC A/52B (AC-3, E-AC-3)"),
+            CodecConfig::Other => {
+                let fourcc = String::from_utf8_lossy(&self.format).trim().to_string();
+                return (fourcc.clone(), format!("{} audio", fourcc));
+            }
+        };
+        (name.to_string(), long_name.to_string())
+    }
+
+    /// AACのプロファイル名
+    pub fn profile(&self) -> Option<&'static str> {
+        match &self.codec {
+            CodecConfig::Aac {
+                config: Some(config),
+                ..
+            } => Some(aac_profile_name(config.object_type)).filter(|name| !name.is_empty()),
+            _ => None,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        match &self.codec {
+            CodecConfig::Aac {
+                config: Some(config),
+                ..
+            } => config.sample_rate,
+            CodecConfig::Alac { sample_rate, .. } if *sample_rate > 0 => *sample_rate,
+            CodecConfig::Opus { .. } => 48000,
+            CodecConfig::Flac(info) => info.sample_rate,
+            _ => self.sample_rate,
+        }
+    }
+
+    pub fn channels(&self) -> u16 {
+        match &self.codec {
+            CodecConfig::Aac {
+                config: Some(config),
+                ..
+            } if config.object_type == 29 => 2,
+            CodecConfig::Aac {
+                config: Some(config),
+                ..
+            } if aac_channel_count(config.channel_config) > 0 => {
+                aac_channel_count(config.channel_config)
+            }
+            CodecConfig::Alac { channels, .. } if *channels > 0 => *channels as u16,
+            CodecConfig::Opus { channels, .. } => *channels as u16,
+            CodecConfig::Flac(info) => info.channels as u16,
+            _ => self.channels,
+        }
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        match &self.codec {
+            CodecConfig::Alac { bit_depth, .. } => *bit_depth as u16,
+            CodecConfig::Flac(info) => info.bits_per_sample as u16,
+            _ => 0,
+        }
+    }
+
+    pub fn channel_layout(&self) -> String {
+        match &self.codec {
+            CodecConfig::Aac {
+                config: Some(config),
+                ..
+            } if config.object_type != 29 && config.channel_config > 0 => {
+                aac_channel_layout(config.channel_config)
+            }
+            _ => super::default_channel_layout(self.channels()),
+        }
+    }
+
+    fn declared_bit_rate(&self) -> u32 {
+        match &self.codec {
+            CodecConfig::Aac { avg_bit_rate, .. }
+            | CodecConfig::Mp3 { avg_bit_rate }
+            | CodecConfig::Alac { avg_bit_rate, .. } => *avg_bit_rate,
+            _ => 0,
+        }
+    }
+}
+
+/// esds（ES_Descriptor → DecoderConfigDescriptor → DecoderSpecificInfo）
+fn parse_esds(data: &[u8]) -> Option<CodecConfig> {
+    let (tag, es, _) = read_descriptor(data)?;
+    if tag != 0x03 {
+        return None;
+    }
+    // ES_ID(2) + フラグ(1) と、フラグに応じた追加フィールド
+    let flags = *es.get(2)?;
+    let mut pos = 3;
+    if flags & 0x80 != 0 {
+        pos += 2;
+    }
+    if flags & 0x40 != 0 {
+        pos += 1 + *es.get(pos)? as usize;
+    }
+    if flags & 0x20 != 0 {
+        pos += 2;
+    }
+
+    let (tag, decoder_config, _) = read_descriptor(es.get(pos..)?)?;
+    if tag != 0x04 {
+        return None;
+    }
+    let object_type_indication = *decoder_config.first()?;
+    let avg_bit_rate = be_u32_at(decoder_config, 9)?;
+    let specific_info = decoder_config
+        .get(13..)
+        .and_then(read_descriptor)
+        .filter(|(tag, _, _)| *tag == 0x05)
+        .map(|(_, body, _)| body);
+
+    Some(match object_type_indication {
+        0x69 | 0x6B => CodecConfig::Mp3 { avg_bit_rate },
+        _ => CodecConfig::Aac {
+            config: specific_info.and_then(AacConfig::parse),
+            avg_bit_rate,
+        },
+    })
+}
+
+/// トラック（trak）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mp4Track {
+    pub track_id: u32,
+    /// tkhdのtrack_enabledフラグ
+    pub enabled: bool,
+    pub handler: [u8; 4],
+    /// stsdの最初のサンプルエントリーの形式（"mp4a"・"avc1"など）
+    pub sample_format: [u8; 4],
+    pub timescale: u32,
+    pub duration: u64,
+    pub language: String,
+    pub audio_entry: Option<AudioSampleEntry>,
+    pub sample_count: u64,
+    /// stszから求めた全サンプルのバイト数
+    pub sample_bytes: u64,
+    /// フラグメント（moof/trun）のサンプル時間の合計
+    pub fragment_duration: u64,
+    pub fragment_bytes: u64,
+    /// tref/chapで参照されるチャプタートラックのID
+    pub chapter_track_ids: Vec<u32>,
+    /// テキストトラックのサンプル位置（チャプタートラックとして読む場合に使う）
+    pub text_samples: Vec<SampleLocation>,
+}
+
+/// stbl（stts/stsc/stsz/stco/co64）から求めたサンプルの位置と時刻
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleLocation {
+    pub offset: u64,
+    pub size: u32,
+    /// トラックのタイムスケール単位
+    pub time: u64,
+    pub duration: u64,
+}
+
+/// サンプルテーブルから先頭`MAX_CHAPTER_SAMPLES`件までのサンプル位置を求める
+fn sample_locations(stbl: &[u8]) -> Option<Vec<SampleLocation>> {
+    let stsz = find_box(stbl, b"stsz")?;
+    let fixed_size = be_u32_at(stsz, 4)?;
+    let count = (be_u32_at(stsz, 8)? as usize).min(MAX_CHAPTER_SAMPLES);
+
+    let chunk_offsets: Vec<u64> = match find_box(stbl, b"stco") {
+        Some(stco) => (0..be_u32_at(stco, 4)? as usize)
+            .map_while(|i| be_u32_at(stco, 8 + i * 4).map(u64::from))
+            .collect(),
+        None => {
+            let co64 = find_box(stbl, b"co64")?;
+            (0..be_u32_at(co64, 4)? as usize)
+                .map_while(|i| be_u64_at(co64, 8 + i * 8))
+                .collect()
+        }
+    };
+    // (first_chunk, samples_per_chunk)
+    let stsc = find_box(stbl, b"stsc")?;
+    let stsc_entries: Vec<(u32, u32)> = (0..be_u32_at(stsc, 4)? as usize)
+        .map_while(|i| Some((be_u32_at(stsc, 8 + i * 12)?, be_u32_at(stsc, 12 + i * 12)?)))
+        .collect();
+    let stts = find_box(stbl, b"stts")?;
+    let mut durations = Vec::with_capacity(count);
+    for i in 0..be_u32_at(stts, 4)? as usize {
+        let (Some(sample_count), Some(delta)) =
+            (be_u32_at(stts, 8 + i * 8), be_u32_at(stts, 12 + i * 8))
+        else {
+            break;
+        };
+        let remaining = count - durations.len();
+        durations.extend(std::iter::repeat_n(
+            delta as u64,
+            (sample_count as usize).min(remaining),
+        ));
+    }
+
+    let mut samples = Vec::with_capacity(count);
+    let mut time = 0;
+    for (chunk_index, chunk_offset) in chunk_offsets.iter().enumerate() {
+        let chunk_number = chunk_index as u32 + 1;
+        let per_chunk = stsc_entries
+            .iter()
+            .rev()
+            .find(|(first_chunk, _)| *first_chunk <= chunk_number)
+            .map_or(0, |(_, samples)| *samples);
+        let mut offset = *chunk_offset;
+        for _ in 0..per_chunk {
+            if samples.len() >= count {
+                return Some(samples);
+            }
+            let size = if fixed_size > 0 {
+                fixed_size
+            } else {
+                be_u32_at(stsz, 12 + samples.len() * 4)?
+            };
+            let duration = durations.get(samples.len()).copied().unwrap_or(0);
+            samples.push(SampleLocation {
+                offset,
+                size,
+                time,
+                duration,
+            });
+            offset += size as u64;
+            time += duration;
+        }
+    }
+    Some(samples)
+}
+
+/// Neroのチャプターリスト（udta/chpl）。開始時刻は100ナノ秒単位
+fn parse_chpl(data: &[u8]) -> Vec<Chapter> {
+    let mut pos = if data.first() == Some(&1) { 8 } else { 4 };
+    let count = data.get(pos).copied().unwrap_or(0);
+    pos += 1;
+
+    let mut chapters = Vec::new();
+    for id in 0..count {
+        let (Some(start), Some(len)) = (be_u64_at(data, pos), data.get(pos + 8)) else {
+            break;
+        };
+        let Some(title) = data.get(pos + 9..pos + 9 + *len as usize) else {
+            break;
+        };
+        chapters.push(Chapter {
+            id: id as i64,
+            start_time: start as f64 / 10_000_000.0,
+            title: Some(String::from_utf8_lossy(title).into_owned()).filter(|t| !t.is_empty()),
+            ..Default::default()
+        });
+        pos += 9 + *len as usize;
+    }
+    chapters
+}
+
+/// QuickTimeのチャプタートラックのテキストサンプル（16ビット長 + UTF-8/UTF-16本文）を読む
+fn read_text_chapters<R: Read + Seek>(
+    reader: &mut R,
+    track: &Mp4Track,
+) -> ParseResult<Vec<Chapter>> {
+    if track.timescale == 0 {
+        return Ok(Vec::new());
+    }
+    let timescale = track.timescale as f64;
+    let mut chapters = Vec::new();
+    for sample in &track.text_samples {
+        reader.seek(SeekFrom::Start(sample.offset))?;
+        let len = (sample.size as u64).min(MAX_TEXT_SAMPLE_SIZE);
+        let data = read_vec(reader, len, MAX_TEXT_SAMPLE_SIZE)?;
+        let text_len = be_u16_at(&data, 0).unwrap_or(0) as usize;
+        let text = data.get(2..2 + text_len).unwrap_or_default();
+        let title = match text {
+            [0xFE, 0xFF, rest @ ..] => String::from_utf16_lossy(
+                &rest
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>(),
+            ),
+            _ => String::from_utf8_lossy(text).into_owned(),
+        };
+        chapters.push(Chapter {
+            id: chapters.len() as i64,
+            start_time: sample.time as f64 / timescale,
+            end_time: (sample.time + sample.duration) as f64 / timescale,
+            title: Some(title).filter(|t| !t.is_empty()),
+            ..Default::default()
+        });
+    }
+    Ok(chapters)
+}
+
+impl Mp4Track {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let tkhd = find_box(data, b"tkhd")?;
+        let track_id = if tkhd.first() == Some(&1) {
+            be_u32_at(tkhd, 20)?
+        } else {
+            be_u32_at(tkhd, 12)?
+        };
+        let mdia = find_box(data, b"mdia")?;
+        let mdhd = find_box(mdia, b"mdhd")?;
+        let (timescale, duration) = parse_time_header(mdhd)?;
+        let handler = find_box(mdia, b"hdlr")
+            .and_then(|hdlr| hdlr.get(8..12))
+            .map(|h| h.try_into().unwrap())
+            .unwrap_or(*b"    ");
+
+        let stbl = find_box(mdia, b"minf").and_then(|minf| find_box(minf, b"stbl"));
+        let sample_entry = stbl
+            .and_then(|stbl| find_box(stbl, b"stsd"))
+            .and_then(|stsd| boxes(stsd.get(8..)?).next());
+        let sample_format = sample_entry.map_or(*b"    ", |(format, _)| format);
+        let audio_entry = sample_entry
+            .filter(|_| &handler == b"soun")
+            .and_then(|(format, body)| AudioSampleEntry::parse(format, body));
+
+        let (sample_count, sample_bytes) = stbl
+            .and_then(|stbl| find_box(stbl, b"stsz"))
+            .and_then(|stsz| {
+                let sample_size = be_u32_at(stsz, 4)? as u64;
+                let count = be_u32_at(stsz, 8)? as u64;
+                let bytes = if sample_size > 0 {
+                    sample_size * count
+                } else {
+                    stsz.get(12..)?
+                        .chunks_exact(4)
+                        .map(|b| u32::from_be_bytes(b.try_into().unwrap()) as u64)
+                        .sum()
+                };
+                Some((count, bytes))
+            })
+            .unwrap_or((0, 0));
+
+        let chapter_track_ids = find_box(data, b"tref")
+            .and_then(|tref| find_box(tref, b"chap"))
+            .map(|chap| {
+                chap.chunks_exact(4)
+                    .map(|id| u32::from_be_bytes(id.try_into().unwrap()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let text_samples = stbl
+            .filter(|_| matches!(&handler, b"text" | b"sbtl"))
+            .and_then(sample_locations)
+            .unwrap_or_default();
+
+        Some(Self {
+            track_id,
+            enabled: tkhd.get(3).is_some_and(|flags| flags & 0x1 != 0),
+            handler,
+            sample_format,
+            timescale,
+            duration,
+            language: parse_language(mdhd),
+            audio_entry,
+            sample_count,
+            sample_bytes,
+            fragment_duration: 0,
+            fragment_bytes: 0,
+            chapter_track_ids,
+            text_samples,
+        })
+    }
+
+    pub fn is_audio(&self) -> bool {
+        &self.handler == b"soun"
+    }
+
+    /// ffprobeのcodec_type
+    pub fn codec_type(&self) -> &'static str {
+        match &self.handler {
+            b"soun" => "audio",
+            b"vide" => "video",
+            b"sbtl" | b"subt" | b"text" | b"clcp" => "subtitle",
+            _ => "data",
+        }
+    }
+
+    /// 音声以外のトラックのFFmpeg互換コーデック名
+    fn sample_format_names(&self) -> (String, String) {
+        let (name, long_name) = match &self.sample_format {
+            b"avc1" | b"avc3" => ("h264", "H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10"),
+            b"hvc1" | b"hev1" => ("hevc", "H.265 / HEVC (High Efficiency Video Coding)"),
+            b"mp4v" => ("mpeg4", "MPEG-4 part 2"),
+            b"jpeg" => ("mjpeg", "Motion JPEG"),
+            b"av01" => ("av1", "Alliance for Open Media AV1"),
+            b"tx3g" => ("mov_text", "3GPP Timed Text subtitle"),
+            b"wvtt" => ("webvtt", "WebVTT subtitle"),
+            b"text" => ("text", "raw UTF-8 text"),
+            other => {
+                let fourcc = String::from_utf8_lossy(other).trim().to_string();
+                return (fourcc.clone(), fourcc);
+            }
+        };
+        (name.to_string(), long_name.to_string())
+    }
+
+    /// ストリーム一覧の1項目（indexはmoov内のtrakの順序）
+    pub fn stream_info(&self, index: usize) -> crate::StreamInfo {
+        let duration = self.duration_seconds();
+        let bytes = self.sample_bytes + self.fragment_bytes;
+        let mut stream = crate::StreamInfo {
+            index: index as u32,
+            codec_type: self.codec_type().to_string(),
+            bit_rate: if bytes > 0 && duration > 0.0 {
+                (bytes as f64 * 8.0 / duration).round() as i64
+            } else {
+                0
+            },
+            language: Some(self.language.clone()).filter(|lang| !lang.is_empty() && lang != "und"),
+            disposition: StreamDisposition {
+                default: self.enabled,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        match &self.audio_entry {
+            Some(entry) => {
+                (stream.codec_name, stream.codec_long_name) = entry.codec_names();
+                stream.profile = entry.profile().map(str::to_string);
+                stream.sample_rate = entry.sample_rate() as i32;
+                stream.channels = entry.channels() as i32;
+                stream.channel_layout = entry.channel_layout();
+                stream.bits_per_sample = entry.bits_per_sample() as i32;
+                if stream.bit_rate == 0 {
+                    stream.bit_rate = entry.declared_bit_rate() as i64;
+                }
+            }
+            None => (stream.codec_name, stream.codec_long_name) = self.sample_format_names(),
+        }
+        stream
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        if self.timescale == 0 {
+            return 0.0;
+        }
+        let duration = if self.duration > 0 && self.duration != u32::MAX as u64 {
+            self.duration
+        } else {
+            self.fragment_duration
+        };
+        duration as f64 / self.timescale as f64
+    }
+}
+
+/// iTunes `ilst` のカバーアート
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverArt {
+    /// 13: JPEG, 14: PNG, 27: BMP
+    pub data_type: u32,
+    pub data: Vec<u8>,
+}
+
+impl CoverArt {
+    /// covrはピクチャータイプを持たないため、ffprobeと同様に表紙として扱う
+    pub fn to_artwork(&self) -> Artwork {
+        let mime_type = match self.data_type {
+            13 => "image/jpeg",
+            14 => "image/png",
+            27 => "image/bmp",
+            _ => "",
+        };
+        artwork::from_picture(
+            Picture {
+                picture_type: 3,
+                mime_type: mime_type.to_string(),
+                description: String::new(),
+                width: 0,
+                height: 0,
+                data: self.data.clone(),
+            },
+            "mp4_covr",
+        )
+    }
+}
+
+/// ilstのアイテム名をffprobe互換のキーに変換する
+fn ilst_key(kind: &[u8; 4]) -> Option<&'static str> {
+    Some(match kind {
+        b"\xa9nam" => "title",
+        b"\xa9ART" => "artist",
+        b"aART" => "album_artist",
+        b"\xa9alb" => "album",
+        b"\xa9day" => "date",
+        b"\xa9gen" | b"gnre" => "genre",
+        b"\xa9cmt" => "comment",
+        b"\xa9wrt" => "composer",
+        b"\xa9too" => "encoder",
+        b"\xa9grp" => "grouping",
+        b"\xa9lyr" => "lyrics",
+        b"cprt" | b"\xa9cpy" => "copyright",
+        b"desc" => "description",
+        b"ldes" => "synopsis",
+        b"tvsh" => "show",
+        b"tven" => "episode_id",
+        b"tvnn" => "network",
+        b"trkn" => "track",
+        b"disk" => "disc",
+        b"cpil" => "compilation",
+        b"pgap" => "gapless_playback",
+        b"tmpo" => "tmpo",
+        b"stik" => "media_type",
+        b"soal" => "sort_album",
+        b"soar" => "sort_artist",
+        b"sonm" => "sort_name",
+        b"soaa" => "sort_album_artist",
+        _ => return None,
+    })
+}
+
+fn decode_data_value(kind: &[u8; 4], data_type: u32, value: &[u8]) -> Option<String> {
+    match kind {
+        // [予約(2), 番号(2), 総数(2)]
+        b"trkn" | b"disk" => {
+            let number = be_u16_at(value, 2)?;
+            match be_u16_at(value, 4).filter(|total| *total > 0) {
+                Some(total) => Some(format!("{}/{}", number, total)),
+                None => Some(number.to_string()),
+            }
+        }
+        b"gnre" => {
+            let index = be_u16_at(value, 0)? as usize;
+            id3::GENRES
+                .get(index.checked_sub(1)?)
+                .map(|g| g.to_string())
+        }
+        _ => match data_type {
+            1 => Some(String::from_utf8_lossy(value).into_owned()),
+            2 => {
+                let units: Vec<u16> = value
+                    .chunks_exact(2)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                    .collect();
+                Some(String::from_utf16_lossy(&units))
+            }
+            // ビッグエンディアン符号付き整数
+            0 | 21 => {
+                let number = value.iter().fold(0i64, |acc, b| (acc << 8) | *b as i64);
+                Some(number.to_string())
+            }
+            _ => None,
+        },
+    }
+}
+
+/// `ilst`を解析して(キー, 値)とカバーアートを返す
+fn parse_ilst(data: &[u8]) -> (Vec<(String, String)>, Vec<CoverArt>) {
+    let mut entries = Vec::new();
+    let mut covers = Vec::new();
+
+    for (kind, item) in boxes(data) {
+        if &kind == b"----" {
+            // フリーフォーム: mean(例: com.apple.iTunes) + name + data
+            let name = find_box(item, b"name")
+                .and_then(|n| n.get(4..))
+                .map(|n| String::from_utf8_lossy(n).into_owned());
+            let value = find_box(item, b"data")
+                .and_then(|d| decode_data_value(&kind, be_u32_at(d, 0)? & 0xFFFFFF, d.get(8..)?));
+            if let (Some(name), Some(value)) = (name, value) {
+                entries.push((name.to_lowercase(), value));
+            }
+            continue;
+        }
+
+        for (child, data) in boxes(item) {
+            if &child != b"data" {
+                continue;
+            }
+            let data_type = be_u32_at(data, 0).unwrap_or(0) & 0xFFFFFF;
+            let value = data.get(8..).unwrap_or(&[]);
+            if &kind == b"covr" {
+                covers.push(CoverArt {
+                    data_type,
+                    data: value.to_vec(),
+                });
+                continue;
+            }
+            let key = match ilst_key(&kind) {
+                Some(key) => key.to_string(),
+                None => String::from_utf8_lossy(&kind).to_lowercase(),
+            };
+            if let Some(value) = decode_data_value(&kind, data_type, value) {
+                if !value.is_empty() {
+                    entries.push((key, value));
+                }
+            }
+        }
+    }
+
+    (entries, covers)
+}
+
+/// `meta`ボックス（フルボックスとQuickTime形式の両方）から`ilst`を探す
+fn find_ilst(meta: &[u8]) -> Option<&[u8]> {
+    find_box(meta, b"ilst").or_else(|| find_box(meta.get(4..)?, b"ilst"))
+}
+
+/// MP4ファイル全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mp4File {
+    pub major_brand: String,
+    pub movie_timescale: u32,
+    pub movie_duration: u64,
+    pub tracks: Vec<Mp4Track>,
+    pub metadata: Vec<(String, String)>,
+    pub covers: Vec<CoverArt>,
+    /// QuickTimeのチャプタートラック、なければNeroのchplから読んだチャプター
+    pub chapters: Vec<Chapter>,
+    pub fragmented: bool,
+    pub mdat_bytes: u64,
+}
+
+impl Mp4File {
+    pub fn primary_audio_track(&self) -> Option<&Mp4Track> {
+        self.tracks
+            .iter()
+            .find(|t| t.is_audio() && t.audio_entry.is_some())
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        if let Some(track) = self.primary_audio_track() {
+            let duration = track.duration_seconds();
+            if duration > 0.0 {
+                return duration;
+            }
+        }
+        if self.movie_timescale > 0 {
+            self.movie_duration as f64 / self.movie_timescale as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn parse_moov(&mut self, moov: &[u8]) {
+        if let Some((timescale, duration)) = find_box(moov, b"mvhd").and_then(parse_time_header) {
+            self.movie_timescale = timescale;
+            self.movie_duration = duration;
+        }
+        // フラグメント化されたファイルの総再生時間（mvex/mehd）
+        if let Some(mehd) = find_box(moov, b"mvex").and_then(|mvex| find_box(mvex, b"mehd")) {
+            self.fragmented = true;
+            let fragment_duration = if mehd.first() == Some(&1) {
+                be_u64_at(mehd, 4)
+            } else {
+                be_u32_at(mehd, 4).map(|d| d as u64)
+            };
+            if self.movie_duration == 0 {
+                self.movie_duration = fragment_duration.unwrap_or(0);
+            }
+        }
+
+        for (kind, body) in boxes(moov) {
+            match &kind {
+                b"trak" => {
+                    if let Some(track) = Mp4Track::parse(body) {
+                        self.tracks.push(track);
+                    }
+                }
+                b"udta" => {
+                    if let Some(chpl) = find_box(body, b"chpl") {
+                        self.chapters = parse_chpl(chpl);
+                    }
+                    if let Some(ilst) = find_box(body, b"meta").and_then(find_ilst) {
+                        let (entries, covers) = parse_ilst(ilst);
+                        self.metadata.extend(entries);
+                        self.covers.extend(covers);
+                    }
+                }
+                b"meta" => {
+                    if let Some(ilst) = find_ilst(body) {
+                        let (entries, covers) = parse_ilst(ilst);
+                        self.metadata.extend(entries);
+                        self.covers.extend(covers);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// moof/traf/trunのサンプル時間とサイズを各トラックに加算する
+    fn parse_moof(&mut self, moof: &[u8], trex_defaults: &[(u32, u32, u32)]) {
+        self.fragmented = true;
+        for (kind, traf) in boxes(moof) {
+            if &kind != b"traf" {
+                continue;
+            }
+            let Some(tfhd) = find_box(traf, b"tfhd") else {
+                continue;
+            };
+            let Some(track_id) = be_u32_at(tfhd, 4) else {
+                continue;
+            };
+            let (mut default_duration, mut default_size) = trex_defaults
+                .iter()
+                .find(|(id, _, _)| *id == track_id)
+                .map(|(_, duration, size)| (*duration, *size))
+                .unwrap_or((0, 0));
+
+            // tfhdのオプションフィールド
+            let tfhd_flags = be_u32_at(tfhd, 0).unwrap_or(0) & 0xFFFFFF;
+            let mut pos = 8;
+            if tfhd_flags & 0x01 != 0 {
+                pos += 8;
+            }
+            if tfhd_flags & 0x02 != 0 {
+                pos += 4;
+            }
+            if tfhd_flags & 0x08 != 0 {
+                default_duration = be_u32_at(tfhd, pos).unwrap_or(default_duration);
+                pos += 4;
+            }
+            if tfhd_flags & 0x10 != 0 {
+                default_size = be_u32_at(tfhd, pos).unwrap_or(default_size);
+            }
+
+            let mut duration = 0u64;
+            let mut bytes = 0u64;
+            for (kind, trun) in boxes(traf) {
+                if &kind != b"trun" {
+                    continue;
+                }
+                let flags = be_u32_at(trun, 0).unwrap_or(0) & 0xFFFFFF;
+                let count = be_u32_at(trun, 4).unwrap_or(0) as usize;
+                let mut pos = 8;
+                if flags & 0x01 != 0 {
+                    pos += 4;
+                }
+                if flags & 0x04 != 0 {
+                    pos += 4;
+                }
+                let has_duration = flags & 0x100 != 0;
+                let has_size = flags & 0x200 != 0;
+                let entry_len = [0x100, 0x200, 0x400, 0x800]
+                    .iter()
+                    .filter(|f| flags & **f != 0)
+                    .count()
+                    * 4;
+                for i in 0..count {
+                    let entry = pos + i * entry_len;
+                    let sample_duration = if has_duration {
+                        be_u32_at(trun, entry).unwrap_or(0)
+                    } else {
+                        default_duration
+                    };
+                    let size_offset = entry + if has_duration { 4 } else { 0 };
+                    let sample_size = if has_size {
+                        be_u32_at(trun, size_offset).unwrap_or(0)
+                    } else {
+                        default_size
+                    };
+                    duration += sample_duration as u64;
+                    bytes += sample_size as u64;
+                }
+            }
+
+            if let Some(track) = self.tracks.iter_mut().find(|t| t.track_id == track_id) {
+                track.fragment_duration += duration;
+                track.fragment_bytes += bytes;
+            }
+        }
+    }
+}
+
+/// トップレベルボックスを走査する（moovが末尾にある場合やmoofも扱う）
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<Mp4File> {
+    let file_len = stream_len(reader)?;
+
+    let mut file = Mp4File {
+        major_brand: String::new(),
+        movie_timescale: 0,
+        movie_duration: 0,
+        tracks: Vec::new(),
+        metadata: Vec::new(),
+        covers: Vec::new(),
+        chapters: Vec::new(),
+        fragmented: false,
+        mdat_bytes: 0,
+    };
+    let mut found_moov = false;
+    let mut trex_defaults = Vec::new();
+    let mut moofs = Vec::new();
+
+    let mut offset = 0;
+    while offset + 8 <= file_len {
+        reader.seek(SeekFrom::Start(offset))?;
+        let header: [u8; 8] = read_array(reader)?;
+        let size = u32::from_be_bytes(header[0..4].try_into().unwrap()) as u64;
+        let kind: [u8; 4] = header[4..8].try_into().unwrap();
+        let (header_len, total) = match size {
+            0 => (8, file_len - offset),
+            1 => (16, u64::from_be_bytes(read_array(reader)?)),
+            _ => (8, size),
+        };
+        if total < header_len {
+            return Err(ParseError::Invalid(format!(
+                "invalid box size {} for '{}'",
+                total,
+                String::from_utf8_lossy(&kind)
+            )));
+        }
+        // 先頭ボックスがftypでもmoovでもなければMP4ではない
+        if offset == 0 && !matches!(&kind, b"ftyp" | b"moov" | b"free" | b"skip" | b"wide") {
+            return Err(ParseError::Invalid("not an ISO-BMFF file".to_string()));
+        }
+        let body_len = total.min(file_len - offset) - header_len;
+
+        match &kind {
+            b"ftyp" => {
+                let body = read_vec(reader, body_len.min(4), 4)?;
+                file.major_brand = String::from_utf8_lossy(&body).trim().to_string();
+            }
+            b"moov" => {
+                let body = read_vec(reader, body_len, MAX_MOOV_SIZE)?;
+                file.parse_moov(&body);
+                if let Some(mvex) = find_box(&body, b"mvex") {
+                    for (kind, trex) in boxes(mvex) {
+                        if &kind == b"trex" {
+                            if let (Some(id), Some(duration), Some(size)) =
+                                (be_u32_at(trex, 4), be_u32_at(trex, 12), be_u32_at(trex, 16))
+                            {
+                                trex_defaults.push((id, duration, size));
+                            }
+                        }
+                    }
+                }
+                found_moov = true;
+            }
+            b"moof" => {
+                // moovより前にmoofが来ることはないが、trexを読んでから処理するため保持する
+                moofs.push(read_vec(reader, body_len, MAX_MOOF_SIZE)?);
+            }
+            b"mdat" => file.mdat_bytes += body_len,
+            _ => {}
+        }
+
+        // ファイル末尾を越えるサイズ（途中で切れたボックスや不正なlargesize）ならそこで打ち切る
+        match offset.checked_add(total) {
+            Some(next) if next > offset && total <= file_len - offset => offset = next,
+            _ => break,
+        }
+    }
+
+    if !found_moov {
+        return Err(ParseError::Invalid("missing moov box".to_string()));
+    }
+    for moof in &moofs {
+        file.parse_moof(moof, &trex_defaults);
+    }
+
+    // 音声トラックのtref/chapが参照するテキストトラックをチャプターとして読む
+    let chapter_track = file.tracks.iter().find(|track| {
+        !track.text_samples.is_empty()
+            && file
+                .tracks
+                .iter()
+                .any(|t| t.chapter_track_ids.contains(&track.track_id))
+    });
+    if let Some(track) = chapter_track {
+        let chapters = read_text_chapters(reader, track)?;
+        if !chapters.is_empty() {
+            file.chapters = chapters;
+        }
+    }
+
+    Ok(file)
+}
+
+/// MP4ファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let mp4 = read(reader)?;
+    let track = mp4
+        .primary_audio_track()
+        .ok_or_else(|| ParseError::Invalid("MP4 file has no audio track".to_string()))?;
+    let entry = track.audio_entry.as_ref().unwrap();
+    let (codec_name, codec_long_name) = entry.codec_names();
+
+    audio_info.format_name = "mov,mp4,m4a,3gp,3g2,mj2".to_string();
+    audio_info.format_long_name = "QuickTime / MOV".to_string();
+    audio_info.codec_name = codec_name;
+    audio_info.codec_long_name = codec_long_name;
+    audio_info.sample_rate = entry.sample_rate() as i32;
+    audio_info.channels = entry.channels() as i32;
+    audio_info.bits_per_sample = entry.bits_per_sample() as i32;
+    audio_info.channel_layout = entry.channel_layout();
+    audio_info.has_video = mp4.tracks.iter().any(|t| &t.handler == b"vide");
+    audio_info.duration_seconds = mp4.duration_seconds();
+    if let CodecConfig::Flac(info) = &entry.codec {
+        audio_info.audio_md5 = info.md5_hex();
+    }
+
+    let audio_bytes = track.sample_bytes + track.fragment_bytes;
+    audio_info.bit_rate = if audio_bytes > 0 && audio_info.duration_seconds > 0.0 {
+        (audio_bytes as f64 * 8.0 / audio_info.duration_seconds).round() as i64
+    } else {
+        entry.declared_bit_rate() as i64
+    };
+
+    merge_metadata(&mut audio_info.metadata, mp4.metadata.iter().cloned());
+
+    audio_info.streams = mp4
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| track.stream_info(index))
+        .collect();
+    audio_info.artwork = mp4.covers.iter().map(CoverArt::to_artwork).collect();
+    audio_info.chapters = mp4.chapters;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn bx(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn full_box(kind: &[u8; 4], version: u8, body: &[u8]) -> Vec<u8> {
+        let mut content = vec![version, 0, 0, 0];
+        content.extend_from_slice(body);
+        bx(kind, &content)
+    }
+
+    fn time_header(kind: &[u8; 4], timescale: u32, duration: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 8];
+        body.extend_from_slice(&timescale.to_be_bytes());
+        body.extend_from_slice(&duration.to_be_bytes());
+        // mdhdの言語 "eng" + pre_defined
+        body.extend_from_slice(&[0x15, 0xC7, 0, 0]);
+        full_box(kind, 0, &body)
+    }
+
+    fn mp4a_entry(avg_bit_rate: u32) -> Vec<u8> {
+        // AudioSpecificConfig: AAC LC, 44.1kHz, 2ch
+        let asc = [0x12, 0x10];
+        let mut decoder_config = vec![0x40, 0x15, 0, 0, 0];
+        decoder_config.extend_from_slice(&avg_bit_rate.to_be_bytes());
+        decoder_config.extend_from_slice(&avg_bit_rate.to_be_bytes());
+        decoder_config.extend_from_slice(&[0x05, asc.len() as u8]);
+        decoder_config.extend_from_slice(&asc);
+        let mut es = vec![0, 1, 0, 0x04, decoder_config.len() as u8];
+        es.extend_from_slice(&decoder_config);
+        let mut esds = vec![0x03, es.len() as u8];
+        esds.extend_from_slice(&es);
+
+        let mut entry = vec![0u8; 6];
+        entry.extend_from_slice(&1u16.to_be_bytes());
+        entry.extend_from_slice(&[0; 8]);
+        entry.extend_from_slice(&2u16.to_be_bytes());
+        entry.extend_from_slice(&16u16.to_be_bytes());
+        entry.extend_from_slice(&[0; 4]);
+        entry.extend_from_slice(&(44100u32 << 16).to_be_bytes());
+        entry.extend(full_box(b"esds", 0, &esds));
+        bx(b"mp4a", &entry)
+    }
+
+    fn audio_trak(duration: u32, sample_sizes: &[u32]) -> Vec<u8> {
+        let mut tkhd = vec![0u8; 8];
+        tkhd.extend_from_slice(&1u32.to_be_bytes());
+        tkhd.extend_from_slice(&[0; 72]);
+
+        let mut stsd = 1u32.to_be_bytes().to_vec();
+        stsd.extend(mp4a_entry(128000));
+        let mut stsz = 0u32.to_be_bytes().to_vec();
+        stsz.extend_from_slice(&(sample_sizes.len() as u32).to_be_bytes());
+        for size in sample_sizes {
+            stsz.extend_from_slice(&size.to_be_bytes());
+        }
+        let stbl = bx(
+            b"stbl",
+            &[full_box(b"stsd", 0, &stsd), full_box(b"stsz", 0, &stsz)].concat(),
+        );
+
+        let mut hdlr = vec![0u8; 4];
+        hdlr.extend_from_slice(b"soun");
+        hdlr.extend_from_slice(&[0; 13]);
+
+        let mdia = bx(
+            b"mdia",
+            &[
+                time_header(b"mdhd", 44100, duration),
+                full_box(b"hdlr", 0, &hdlr),
+                bx(b"minf", &stbl),
+            ]
+            .concat(),
+        );
+        bx(b"trak", &[full_box(b"tkhd", 0, &tkhd), mdia].concat())
+    }
+
+    fn data_item(kind: &[u8; 4], data_type: u32, value: &[u8]) -> Vec<u8> {
+        let mut data = data_type.to_be_bytes().to_vec();
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(value);
+        bx(kind, &bx(b"data", &data))
+    }
+
+    #[test]
+    fn test_m4a_with_moov_at_end() {
+        let mut freeform = full_box(b"mean", 0, b"com.apple.iTunes");
+        freeform.extend(full_box(b"name", 0, b"iTunSMPB"));
+        let mut data = 1u32.to_be_bytes().to_vec();
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(b" 00000000 00000840");
+        freeform.extend(bx(b"data", &data));
+
+        let ilst = bx(
+            b"ilst",
+            &[
+                data_item(b"\xa9nam", 1, "Chapter One".as_bytes()),
+                data_item(b"\xa9ART", 1, b"Narrator"),
+                data_item(b"aART", 1, b"Author"),
+                data_item(b"trkn", 0, &[0, 0, 0, 3, 0, 12, 0, 0]),
+                data_item(b"disk", 0, &[0, 0, 0, 1, 0, 2]),
+                data_item(b"covr", 13, &[0xFF, 0xD8, 0xFF]),
+                bx(b"----", &freeform),
+            ]
+            .concat(),
+        );
+        let mut hdlr = vec![0u8; 4];
+        hdlr.extend_from_slice(b"mdir");
+        hdlr.extend_from_slice(&[0; 13]);
+        let meta = full_box(b"meta", 0, &[full_box(b"hdlr", 0, &hdlr), ilst].concat());
+
+        let moov = bx(
+            b"moov",
+            &[
+                time_header(b"mvhd", 1000, 10_000),
+                audio_trak(441_000, &[16_000; 10]),
+                bx(b"udta", &meta),
+            ]
+            .concat(),
+        );
+
+        let mut bytes = bx(b"ftyp", b"M4B \x00\x00\x00\x00");
+        bytes.extend(bx(b"mdat", &[0u8; 160_000]));
+        bytes.extend(moov);
+
+        let mut info = AudioInfo::new("book.m4b".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "aac");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.channel_layout, "stereo");
+        assert_eq!(info.duration_seconds, 10.0);
+        assert_eq!(info.bit_rate, 128_000);
+        assert_eq!(info.metadata["title"], "Chapter One");
+        assert_eq!(info.metadata["artist"], "Narrator");
+        assert_eq!(info.metadata["album_artist"], "Author");
+        assert_eq!(info.metadata["track"], "3/12");
+        assert_eq!(info.metadata["disc"], "1/2");
+        assert_eq!(info.metadata["itunsmpb"], " 00000000 00000840");
+        assert_eq!(info.artwork.len(), 1);
+        assert_eq!(info.artwork[0].picture_type, "Cover (front)");
+        assert_eq!(info.artwork[0].mime_type, "image/jpeg");
+        assert_eq!(info.artwork[0].source, "mp4_covr");
+
+        assert_eq!(info.streams.len(), 1);
+        let stream = &info.streams[0];
+        assert_eq!(stream.codec_type, "audio");
+        assert_eq!(stream.profile.as_deref(), Some("LC"));
+        assert_eq!(stream.language.as_deref(), Some("eng"));
+        assert_eq!(stream.bit_rate, 128_000);
+    }
+
+    #[test]
+    fn test_quicktime_chapter_track_and_chpl() {
+        // mdatの先頭（ftyp 16バイト + mdatヘッダー 8バイト）にチャプターテキストを置く
+        let samples = [b"\x00\x05Intro".to_vec(), b"\x00\x04Main".to_vec()];
+        let mdat = bx(b"mdat", &samples.concat());
+
+        let mut tkhd = vec![0u8; 8];
+        tkhd.extend_from_slice(&2u32.to_be_bytes());
+        tkhd.extend_from_slice(&[0; 72]);
+        let mut hdlr = vec![0u8; 4];
+        hdlr.extend_from_slice(b"text");
+        hdlr.extend_from_slice(&[0; 13]);
+        let mut stsd = 1u32.to_be_bytes().to_vec();
+        stsd.extend(bx(b"text", &[0; 8]));
+        let be =
+            |values: &[u32]| -> Vec<u8> { values.iter().flat_map(|v| v.to_be_bytes()).collect() };
+        let stbl = bx(
+            b"stbl",
+            &[
+                full_box(b"stsd", 0, &stsd),
+                full_box(b"stts", 0, &be(&[2, 1, 3000, 1, 7000])),
+                full_box(b"stsc", 0, &be(&[1, 1, 2, 1])),
+                full_box(b"stsz", 0, &be(&[0, 2, 7, 6])),
+                full_box(b"stco", 0, &be(&[1, 24])),
+            ]
+            .concat(),
+        );
+        let text_trak = bx(
+            b"trak",
+            &[
+                full_box(b"tkhd", 0, &tkhd),
+                bx(
+                    b"mdia",
+                    &[
+                        time_header(b"mdhd", 1000, 10_000),
+                        full_box(b"hdlr", 0, &hdlr),
+                        bx(b"minf", &stbl),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat(),
+        );
+        let audio = audio_trak(441_000, &[16_000; 10]);
+        let audio = bx(
+            b"trak",
+            &[&audio[8..], &bx(b"tref", &bx(b"chap", &2u32.to_be_bytes()))].concat(),
+        );
+
+        let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 1];
+        chpl.extend_from_slice(&0u64.to_be_bytes());
+        chpl.push(4);
+        chpl.extend_from_slice(b"Nero");
+        let udta = bx(b"udta", &bx(b"chpl", &chpl));
+        assert_eq!(parse_chpl(&chpl[..])[0].title.as_deref(), Some("Nero"));
+
+        let moov = bx(
+            b"moov",
+            &[time_header(b"mvhd", 1000, 10_000), audio, text_trak, udta].concat(),
+        );
+        let mut bytes = bx(b"ftyp", b"M4B \x00\x00\x00\x00");
+        bytes.extend(mdat);
+        bytes.extend(moov);
+
+        let mut info = AudioInfo::new("book.m4b".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.chapters.len(), 2);
+        assert_eq!(info.chapters[0].title.as_deref(), Some("Intro"));
+        assert_eq!(info.chapters[0].end_time, 3.0);
+        assert_eq!(info.chapters[1].title.as_deref(), Some("Main"));
+        assert_eq!(info.chapters[1].start_time, 3.0);
+        assert_eq!(info.chapters[1].end_time, 10.0);
+        assert_eq!(info.streams[1].codec_type, "subtitle");
+    }
+
+    #[test]
+    fn test_fragmented_mp4() {
+        let mut trex = 1u32.to_be_bytes().to_vec();
+        trex.extend_from_slice(&1u32.to_be_bytes());
+        trex.extend_from_slice(&1024u32.to_be_bytes());
+        trex.extend_from_slice(&300u32.to_be_bytes());
+        trex.extend_from_slice(&0u32.to_be_bytes());
+
+        let moov = bx(
+            b"moov",
+            &[
+                time_header(b"mvhd", 1000, 0),
+                audio_trak(0, &[]),
+                bx(b"mvex", &full_box(b"trex", 0, &trex)),
+            ]
+            .concat(),
+        );
+
+        let mut bytes = bx(b"ftyp", b"iso6\x00\x00\x00\x00");
+        bytes.extend(moov);
+        for _ in 0..2 {
+            let tfhd = full_box(b"tfhd", 0, &1u32.to_be_bytes());
+            // サンプル数のみ（時間・サイズはtrexの既定値）
+            let trun = full_box(b"trun", 0, &(44100u32 / 1024 + 1).to_be_bytes());
+            bytes.extend(bx(b"moof", &bx(b"traf", &[tfhd, trun].concat())));
+            bytes.extend(bx(b"mdat", &[0u8; 64]));
+        }
+
+        let mp4 = read(&mut Cursor::new(bytes)).unwrap();
+        assert!(mp4.fragmented);
+        let track = mp4.primary_audio_track().unwrap();
+        assert_eq!(track.fragment_duration, 2 * 44 * 1024);
+        assert!((mp4.duration_seconds() - 2.0 * 44.0 * 1024.0 / 44100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_aac_config_with_sbr() {
+        // HE-AAC: AOT 5, 22.05kHz core, 1ch, 拡張 44.1kHz
+        let config = AacConfig::parse(&[0x2B, 0x8A, 0x08, 0x00]).unwrap();
+        assert_eq!(config.object_type, 5);
+        assert_eq!(config.sample_rate, 44100);
+        assert_eq!(config.channel_config, 1);
+    }
+
+    #[test]
+    fn test_oversized_largesize_box() {
+        // ftyp 16バイト + largesizeが2^64-16のfreeボックス + 余り16バイト（計48バイト）
+        let mut bytes = bx(b"ftyp", b"M4A \0\0\0\0");
+        bytes.extend_from_slice(&1u32.to_be_bytes());
+        bytes.extend_from_slice(b"free");
+        bytes.extend_from_slice(&0xFFFF_FFFF_FFFF_FFF0u64.to_be_bytes());
+        bytes.extend_from_slice(&[0u8; 16]);
+        assert_eq!(bytes.len(), 48);
+        let result = read(&mut Cursor::new(bytes));
+        assert!(matches!(result, Err(ParseError::Invalid(reason)) if reason == "missing moov box"));
+    }
+
+    #[test]
+    fn test_not_an_mp4_file() {
+        let result = read(&mut Cursor::new(b"This is a test MP3 file\n".to_vec()));
+        assert!(result.is_err());
+    }
+}
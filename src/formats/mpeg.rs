@@ -0,0 +1,489 @@
+// MPEG-1/2/2.5 Layer I/II/III フレームスキャナー
+
+use super::{apetag, id3, read_array, stream_len, ParseError, ParseResult, Window};
+use crate::AudioInfo;
+use std::io::{Read, Seek, SeekFrom};
+
+/// 先頭フレームを探す最大範囲（ID3v2タグの後ろのゴミデータを許容する範囲）
+const MAX_SYNC_SEARCH: u64 = 256 * 1024;
+
+/// ビットレート表（kbps）: [MPEG-1 L1, L2, L3, MPEG-2/2.5 L1, L2/L3]
+const BITRATES: [[u32; 15]; 5] = [
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegVersion {
+    Mpeg1,
+    Mpeg2,
+    Mpeg25,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMode {
+    Stereo,
+    JointStereo,
+    DualChannel,
+    Mono,
+}
+
+/// 4バイトのフレームヘッダー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version: MpegVersion,
+    /// 1〜3
+    pub layer: u8,
+    pub bit_rate: u32,
+    pub sample_rate: u32,
+    pub padding: bool,
+    pub channel_mode: ChannelMode,
+}
+
+impl FrameHeader {
+    pub fn parse(bytes: [u8; 4]) -> Option<Self> {
+        let header = u32::from_be_bytes(bytes);
+        if header & 0xFFE0_0000 != 0xFFE0_0000 {
+            return None;
+        }
+
+        let version = match (header >> 19) & 0x3 {
+            0 => MpegVersion::Mpeg25,
+            2 => MpegVersion::Mpeg2,
+            3 => MpegVersion::Mpeg1,
+            _ => return None,
+        };
+        let layer = match (header >> 17) & 0x3 {
+            1 => 3,
+            2 => 2,
+            3 => 1,
+            _ => return None,
+        };
+
+        // フリーフォーマット(0)と不正値(15)は扱わない
+        let bit_rate_index = ((header >> 12) & 0xF) as usize;
+        if bit_rate_index == 0 || bit_rate_index == 15 {
+            return None;
+        }
+        let table = match (version, layer) {
+            (MpegVersion::Mpeg1, layer) => layer as usize - 1,
+            (_, 1) => 3,
+            _ => 4,
+        };
+        let bit_rate = BITRATES[table][bit_rate_index] * 1000;
+
+        let base_rate = match (header >> 10) & 0x3 {
+            0 => 44100,
+            1 => 48000,
+            2 => 32000,
+            _ => return None,
+        };
+        let sample_rate = match version {
+            MpegVersion::Mpeg1 => base_rate,
+            MpegVersion::Mpeg2 => base_rate / 2,
+            MpegVersion::Mpeg25 => base_rate / 4,
+        };
+
+        let channel_mode = match (header >> 6) & 0x3 {
+            0 => ChannelMode::Stereo,
+            1 => ChannelMode::JointStereo,
+            2 => ChannelMode::DualChannel,
+            _ => ChannelMode::Mono,
+        };
+
+        Some(Self {
+            version,
+            layer,
+            bit_rate,
+            sample_rate,
+            padding: (header >> 9) & 0x1 == 1,
+            channel_mode,
+        })
+    }
+
+    pub fn samples_per_frame(&self) -> u32 {
+        match (self.layer, self.version) {
+            (1, _) => 384,
+            (2, _) | (3, MpegVersion::Mpeg1) => 1152,
+            _ => 576,
+        }
+    }
+
+    pub fn frame_length(&self) -> u64 {
+        let (slot_size, padding) = if self.layer == 1 {
+            (4, self.padding as u64 * 4)
+        } else {
+            (1, self.padding as u64)
+        };
+        let slots = self.samples_per_frame() as u64 / 8 * self.bit_rate as u64
+            / self.sample_rate as u64
+            / slot_size;
+        slots * slot_size + padding
+    }
+
+    pub fn channels(&self) -> u16 {
+        if self.channel_mode == ChannelMode::Mono {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// 同一ストリームのフレームかどうか（誤同期の排除に使う）
+    fn is_compatible(&self, other: &FrameHeader) -> bool {
+        self.version == other.version
+            && self.layer == other.layer
+            && self.sample_rate == other.sample_rate
+    }
+
+    /// Xing/Infoヘッダーの位置（フレーム先頭からのオフセット）
+    fn xing_offset(&self) -> usize {
+        let side_info = match (self.version, self.channel_mode) {
+            (MpegVersion::Mpeg1, ChannelMode::Mono) => 17,
+            (MpegVersion::Mpeg1, _) => 32,
+            (_, ChannelMode::Mono) => 9,
+            _ => 17,
+        };
+        4 + side_info
+    }
+
+    pub fn codec_names(&self) -> (&'static str, &'static str) {
+        match self.layer {
+            1 => ("mp1", "MP1 (MPEG audio layer 1)"),
+            2 => ("mp2", "MP2 (MPEG audio layer 2)"),
+            _ => ("mp3", "MP3 (MPEG audio layer 3)"),
+        }
+    }
+}
+
+/// 再生時間の算出方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationSource {
+    Xing,
+    Info,
+    Vbri,
+    FrameCount,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MpegStream {
+    pub first_header: FrameHeader,
+    pub audio_offset: u64,
+    pub audio_size: u64,
+    pub frame_count: u64,
+    pub duration_source: DurationSource,
+}
+
+impl MpegStream {
+    pub fn duration_seconds(&self) -> f64 {
+        let samples = self.frame_count * self.first_header.samples_per_frame() as u64;
+        samples as f64 / self.first_header.sample_rate as f64
+    }
+
+    pub fn average_bit_rate(&self) -> i64 {
+        let duration = self.duration_seconds();
+        if duration > 0.0 {
+            (self.audio_size as f64 * 8.0 / duration).round() as i64
+        } else {
+            self.first_header.bit_rate as i64
+        }
+    }
+}
+
+fn header_at<R: Read + Seek>(
+    window: &mut Window<R>,
+    offset: u64,
+) -> ParseResult<Option<FrameHeader>> {
+    Ok(window
+        .get(offset, 4)?
+        .and_then(|b| FrameHeader::parse([b[0], b[1], b[2], b[3]])))
+}
+
+/// 末尾のAPEタグ・ID3v1タグ（128バイト）を除いた音声データの終端
+fn audio_end<R: Read + Seek>(reader: &mut R, file_len: u64) -> ParseResult<u64> {
+    if let Some(tag) = apetag::read(reader)? {
+        return Ok(tag.offset);
+    }
+    if file_len >= 128 {
+        reader.seek(SeekFrom::Start(file_len - 128))?;
+        let tag: [u8; 3] = read_array(reader)?;
+        if &tag == b"TAG" {
+            return Ok(file_len - 128);
+        }
+    }
+    Ok(file_len)
+}
+
+/// 連続する2フレームが整合する最初の位置を探す
+fn find_first_frame<R: Read + Seek>(
+    window: &mut Window<R>,
+    start: u64,
+    end: u64,
+) -> ParseResult<Option<(u64, FrameHeader)>> {
+    let limit = end.min(start + MAX_SYNC_SEARCH);
+    let mut offset = start;
+    while offset + 4 <= limit {
+        if let Some(header) = header_at(window, offset)? {
+            let next = offset + header.frame_length();
+            // 次のフレームがファイル終端を超える場合は単一フレームとして受け入れる
+            if next + 4 > end {
+                return Ok(Some((offset, header)));
+            }
+            if let Some(next_header) = header_at(window, next)? {
+                if header.is_compatible(&next_header) {
+                    return Ok(Some((offset, header)));
+                }
+            }
+        }
+        offset += 1;
+    }
+    Ok(None)
+}
+
+/// 先頭フレーム内のXing/Info/VBRIヘッダーからフレーム数と音声バイト数を読む
+fn read_vbr_header<R: Read + Seek>(
+    window: &mut Window<R>,
+    offset: u64,
+    header: &FrameHeader,
+) -> ParseResult<Option<(DurationSource, u64, Option<u64>)>> {
+    let xing_offset = offset + header.xing_offset() as u64;
+    if let Some(bytes) = window.get(xing_offset, 16)? {
+        let source = match &bytes[0..4] {
+            b"Xing" => Some(DurationSource::Xing),
+            b"Info" => Some(DurationSource::Info),
+            _ => None,
+        };
+        if let Some(source) = source {
+            let flags = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            if flags & 0x1 != 0 {
+                let frames = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+                let audio_bytes = if flags & 0x2 != 0 {
+                    Some(u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]) as u64)
+                } else {
+                    None
+                };
+                return Ok(Some((source, frames as u64, audio_bytes)));
+            }
+        }
+    }
+
+    // VBRIはフレーム先頭から36バイト目に固定
+    if let Some(bytes) = window.get(offset + 36, 18)? {
+        if &bytes[0..4] == b"VBRI" {
+            let audio_bytes = u32::from_be_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]);
+            let frames = u32::from_be_bytes([bytes[14], bytes[15], bytes[16], bytes[17]]);
+            return Ok(Some((
+                DurationSource::Vbri,
+                frames as u64,
+                Some(audio_bytes as u64),
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
+/// 全フレームを数える（同期が外れた場合は次の整合するヘッダーまで読み飛ばす）
+fn count_frames<R: Read + Seek>(
+    window: &mut Window<R>,
+    first_offset: u64,
+    first_header: &FrameHeader,
+    end: u64,
+) -> ParseResult<u64> {
+    let mut frames = 0;
+    let mut offset = first_offset;
+    while offset + 4 <= end {
+        match header_at(window, offset)? {
+            Some(header) if header.is_compatible(first_header) => {
+                frames += 1;
+                offset += header.frame_length();
+            }
+            _ => offset += 1,
+        }
+    }
+    Ok(frames)
+}
+
+/// MPEGオーディオストリームを解析する
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<MpegStream> {
+    let file_len = stream_len(reader)?;
+    let start = id3::skip_v2(reader, file_len)?;
+    let end = audio_end(reader, file_len)?;
+
+    let mut window = Window::new(reader);
+    let (first_offset, first_header) = find_first_frame(&mut window, start, end)?
+        .ok_or_else(|| ParseError::Invalid("no MPEG audio frame sync found".to_string()))?;
+
+    // 書き込みが中断されたファイルではVBRヘッダーのフレーム数が0のままのことがあるため、
+    // その場合はヘッダーがないものとしてフレームを数える
+    let vbr_header = read_vbr_header(&mut window, first_offset, &first_header)?
+        .filter(|(_, frames, _)| *frames > 0);
+    let (duration_source, frame_count, audio_size) = match vbr_header {
+        Some((source, frames, audio_bytes)) => {
+            // Xing/Infoフレーム自体は音声を含まない
+            let audio_start = first_offset + first_header.frame_length();
+            let audio_size = audio_bytes
+                .filter(|bytes| *bytes > 0 && *bytes <= end - first_offset)
+                .unwrap_or(end.saturating_sub(audio_start));
+            (source, frames, audio_size)
+        }
+        None => {
+            let frames = count_frames(&mut window, first_offset, &first_header, end)?;
+            (DurationSource::FrameCount, frames, end - first_offset)
+        }
+    };
+
+    if frame_count == 0 {
+        return Err(ParseError::Invalid("MPEG stream has no frames".to_string()));
+    }
+
+    Ok(MpegStream {
+        first_header,
+        audio_offset: first_offset,
+        audio_size,
+        frame_count,
+        duration_source,
+    })
+}
+
+/// MPEGオーディオファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let stream = read(reader)?;
+    let header = &stream.first_header;
+    let (codec_name, codec_long_name) = header.codec_names();
+
+    audio_info.format_name = "mp3".to_string();
+    audio_info.format_long_name = "MP2/3 (MPEG audio layer 2/3)".to_string();
+    audio_info.codec_name = codec_name.to_string();
+    audio_info.codec_long_name = codec_long_name.to_string();
+    audio_info.sample_rate = header.sample_rate as i32;
+    audio_info.channels = header.channels() as i32;
+    audio_info.channel_layout = super::default_channel_layout(header.channels());
+    audio_info.duration_seconds = stream.duration_seconds();
+    audio_info.bit_rate = stream.average_bit_rate();
+    audio_info.metadata = id3::read_metadata(reader)?;
+    if let Some(tag) = id3::read_v2_at(reader, 0)? {
+        audio_info.chapters = tag.chapter_list();
+        audio_info.artwork = tag.artwork();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_frame_header_parsing() {
+        // MPEG-1 Layer III, 128 kbps, 44.1 kHz, joint stereo
+        let header = FrameHeader::parse([0xFF, 0xFB, 0x90, 0x64]).unwrap();
+        assert_eq!(header.version, MpegVersion::Mpeg1);
+        assert_eq!(header.layer, 3);
+        assert_eq!(header.bit_rate, 128_000);
+        assert_eq!(header.sample_rate, 44100);
+        assert_eq!(header.channel_mode, ChannelMode::JointStereo);
+        assert_eq!(header.frame_length(), 417);
+
+        // MPEG-2 Layer III, 64 kbps, 22.05 kHz, mono
+        let header = FrameHeader::parse([0xFF, 0xF3, 0x80, 0xC0]).unwrap();
+        assert_eq!(header.version, MpegVersion::Mpeg2);
+        assert_eq!(header.sample_rate, 22050);
+        assert_eq!(header.samples_per_frame(), 576);
+        assert_eq!(header.channels(), 1);
+
+        assert!(FrameHeader::parse([0xFF, 0xFB, 0xF0, 0x64]).is_none());
+        assert!(FrameHeader::parse([0x49, 0x44, 0x33, 0x04]).is_none());
+    }
+
+    #[test]
+    fn test_counts_frames_after_id3v2() {
+        let frame_header = [0xFF, 0xFB, 0x90, 0x64];
+        let mut bytes = b"ID3\x04\x00\x00\x00\x00\x00\x05hello".to_vec();
+        for _ in 0..10 {
+            let mut frame = vec![0u8; 417];
+            frame[..4].copy_from_slice(&frame_header);
+            bytes.extend_from_slice(&frame);
+        }
+
+        let stream = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(stream.audio_offset, 15);
+        assert_eq!(stream.frame_count, 10);
+        assert_eq!(stream.duration_source, DurationSource::FrameCount);
+        assert!((stream.duration_seconds() - 11520.0 / 44100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zero_frame_vbr_header_falls_back_to_counting() {
+        let frame_header = [0xFF, 0xFB, 0x90, 0x64];
+        let mut bytes = Vec::new();
+        for i in 0..10 {
+            let mut frame = vec![0u8; 417];
+            frame[..4].copy_from_slice(&frame_header);
+            if i == 0 {
+                // フレーム数のフラグは立っているが値が0のInfoヘッダー
+                frame[36..44].copy_from_slice(b"Info\x00\x00\x00\x01");
+            }
+            bytes.extend_from_slice(&frame);
+        }
+
+        let stream = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(stream.duration_source, DurationSource::FrameCount);
+        assert_eq!(stream.frame_count, 10);
+    }
+
+    #[test]
+    fn test_info_header_sample() {
+        let stream = read(&mut File::open("data/sample-3s.mp3").unwrap()).unwrap();
+        assert_eq!(stream.duration_source, DurationSource::Info);
+        assert_eq!(stream.first_header.sample_rate, 44100);
+        assert_eq!(stream.frame_count, 124);
+        assert!((stream.duration_seconds() - 3.239).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_xing_header_sample() {
+        let stream = read(&mut File::open("data/file_example_MP3_700KB.mp3").unwrap()).unwrap();
+        assert_eq!(stream.duration_source, DurationSource::Xing);
+        // Xingヘッダーのフレーム数は実際に数えた値（Xingフレーム自体を除く）と一致する
+        assert_eq!(stream.frame_count, 1610);
+    }
+
+    #[test]
+    fn test_frame_count_matches_file_size() {
+        let mut file = File::open("data/jupiter.mp3").unwrap();
+        let stream = read(&mut file).unwrap();
+        assert_eq!(stream.duration_source, DurationSource::FrameCount);
+        // CBRなので平均ビットレートは先頭フレームのビットレートと一致する
+        let diff = stream.average_bit_rate() - stream.first_header.bit_rate as i64;
+        assert!(diff.abs() < 1000, "average bit rate off by {}", diff);
+    }
+
+    #[test]
+    fn test_all_sample_mp3s_parse() {
+        for entry in std::fs::read_dir("data").unwrap().flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("mp3") {
+                continue;
+            }
+            let mut info = AudioInfo::new(path.clone());
+            probe(&mut File::open(&path).unwrap(), &mut info)
+                .unwrap_or_else(|e| panic!("{:?}: {}", path, e));
+            assert!(info.duration_seconds > 0.0, "{:?}", path);
+            assert!(info.bit_rate > 0, "{:?}", path);
+        }
+    }
+}
@@ -0,0 +1,668 @@
+// Ogg コンテナ（Vorbis / Opus / FLAC-in-Ogg / Speex）
+
+use super::{
+    artwork,
+    flac::StreamInfo,
+    merge_metadata, stream_len,
+    vorbis::{self, VorbisComments},
+    ParseError, ParseResult, Window,
+};
+use crate::{AudioInfo, StreamDisposition};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+/// ヘッダーパケット（画像入りのコメントなど）の組み立て上限
+const MAX_PACKET_SIZE: usize = 16 * 1024 * 1024;
+
+/// ページ同期が外れた場合に次の"OggS"を探す最大範囲
+const MAX_RESYNC: u64 = 1024 * 1024;
+
+/// Opusのグラニュール位置は常に48kHz
+const OPUS_GRANULE_RATE: u32 = 48000;
+
+const PAGE_CONTINUED: u8 = 0x01;
+const PAGE_BOS: u8 = 0x02;
+const PAGE_EOS: u8 = 0x04;
+
+/// 先頭パケットから判別したコーデック
+#[derive(Debug, Clone, PartialEq)]
+pub enum OggCodec {
+    Vorbis {
+        channels: u8,
+        sample_rate: u32,
+        nominal_bit_rate: i32,
+    },
+    Opus {
+        channels: u8,
+        pre_skip: u16,
+        input_sample_rate: u32,
+        mapping_family: u8,
+    },
+    Flac(StreamInfo),
+    Speex {
+        channels: u8,
+        sample_rate: u32,
+    },
+    Theora,
+    Unknown,
+}
+
+impl OggCodec {
+    fn identify(packet: &[u8]) -> ParseResult<Self> {
+        let truncated = || ParseError::Invalid("truncated Ogg identification header".to_string());
+        let u32_at = |offset: usize| -> ParseResult<u32> {
+            let bytes = packet.get(offset..offset + 4).ok_or_else(truncated)?;
+            Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+
+        if packet.starts_with(b"\x01vorbis") {
+            return Ok(OggCodec::Vorbis {
+                channels: *packet.get(11).ok_or_else(truncated)?,
+                sample_rate: u32_at(12)?,
+                nominal_bit_rate: u32_at(20)? as i32,
+            });
+        }
+        if packet.starts_with(b"OpusHead") {
+            let pre_skip = packet.get(10..12).ok_or_else(truncated)?;
+            return Ok(OggCodec::Opus {
+                channels: *packet.get(9).ok_or_else(truncated)?,
+                pre_skip: u16::from_le_bytes([pre_skip[0], pre_skip[1]]),
+                input_sample_rate: u32_at(12)?,
+                mapping_family: *packet.get(18).ok_or_else(truncated)?,
+            });
+        }
+        // 0x7F "FLAC" major minor ヘッダー数(2) "fLaC" STREAMINFOブロック
+        if packet.starts_with(b"\x7fFLAC") {
+            let block = packet.get(9..).ok_or_else(truncated)?;
+            if !block.starts_with(b"fLaC") || block.len() < 8 {
+                return Err(truncated());
+            }
+            return Ok(OggCodec::Flac(StreamInfo::parse(&block[8..])?));
+        }
+        if packet.starts_with(b"Speex   ") {
+            return Ok(OggCodec::Speex {
+                sample_rate: u32_at(36)?,
+                channels: u32_at(48)? as u8,
+            });
+        }
+        if packet.starts_with(b"\x80theora") {
+            return Ok(OggCodec::Theora);
+        }
+        Ok(OggCodec::Unknown)
+    }
+
+    pub fn codec_names(&self) -> (&'static str, &'static str) {
+        match self {
+            OggCodec::Vorbis { .. } => ("vorbis", "Vorbis"),
+            OggCodec::Opus { .. } => ("opus", "Opus (Opus Interactive Audio Codec)"),
+            OggCodec::Flac(_) => ("flac", "FLAC (Free Lossless Audio Codec)"),
+            OggCodec::Speex { .. } => ("speex", "Speex"),
+            OggCodec::Theora => ("theora", "Theora"),
+            OggCodec::Unknown => ("unknown", "Unknown Ogg codec"),
+        }
+    }
+
+    pub fn is_audio(&self) -> bool {
+        !matches!(self, OggCodec::Theora | OggCodec::Unknown)
+    }
+
+    pub fn codec_type(&self) -> &'static str {
+        match self {
+            OggCodec::Theora => "video",
+            OggCodec::Unknown => "data",
+            _ => "audio",
+        }
+    }
+
+    /// Opusのマッピングファミリー2以上（アンビソニックスなど）はレイアウトを決めない
+    pub fn channel_layout(&self) -> String {
+        match self {
+            OggCodec::Opus { mapping_family, .. } if *mapping_family > 1 => String::new(),
+            codec if codec.is_audio() => vorbis::channel_layout(codec.channels()),
+            _ => String::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            OggCodec::Vorbis { sample_rate, .. } | OggCodec::Speex { sample_rate, .. } => {
+                *sample_rate
+            }
+            OggCodec::Opus { .. } => OPUS_GRANULE_RATE,
+            OggCodec::Flac(info) => info.sample_rate,
+            OggCodec::Theora | OggCodec::Unknown => 0,
+        }
+    }
+
+    pub fn channels(&self) -> u8 {
+        match self {
+            OggCodec::Vorbis { channels, .. }
+            | OggCodec::Opus { channels, .. }
+            | OggCodec::Speex { channels, .. } => *channels,
+            OggCodec::Flac(info) => info.channels,
+            OggCodec::Theora | OggCodec::Unknown => 0,
+        }
+    }
+
+    /// 最終グラニュール位置から再生時間を算出する
+    fn duration_seconds(&self, last_granule: u64) -> f64 {
+        let samples = match self {
+            OggCodec::Opus { pre_skip, .. } => last_granule.saturating_sub(*pre_skip as u64),
+            _ => last_granule,
+        };
+        match self.sample_rate() {
+            0 => 0.0,
+            rate => samples as f64 / rate as f64,
+        }
+    }
+
+    /// 2番目のヘッダーパケットからコメントを取り出す
+    fn parse_comments(&self, packet: &[u8]) -> ParseResult<Option<VorbisComments>> {
+        let body = match self {
+            OggCodec::Vorbis { .. } if packet.starts_with(b"\x03vorbis") => &packet[7..],
+            OggCodec::Opus { .. } if packet.starts_with(b"OpusTags") => &packet[8..],
+            // FLACはメタデータブロックヘッダー(4バイト)付き、種別4がVORBIS_COMMENT
+            OggCodec::Flac(_) if packet.len() >= 4 && packet[0] & 0x7F == 4 => &packet[4..],
+            OggCodec::Speex { .. } => packet,
+            _ => return Ok(None),
+        };
+        vorbis::parse_comments(body).map(Some)
+    }
+}
+
+/// 論理ストリーム
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicalStream {
+    pub serial: u32,
+    pub codec: OggCodec,
+    pub comments: Option<VorbisComments>,
+    /// 連結（chained）ストリームの何番目のリンクに属するか
+    pub link: usize,
+    pub last_granule: Option<u64>,
+    /// このストリームのページの合計バイト数
+    pub byte_size: u64,
+}
+
+impl LogicalStream {
+    pub fn duration_seconds(&self) -> f64 {
+        self.last_granule
+            .map(|granule| self.codec.duration_seconds(granule))
+            .unwrap_or(0.0)
+    }
+
+    pub fn bit_rate(&self) -> i64 {
+        let duration = self.duration_seconds();
+        match &self.codec {
+            _ if duration > 0.0 => (self.byte_size as f64 * 8.0 / duration).round() as i64,
+            OggCodec::Vorbis {
+                nominal_bit_rate, ..
+            } => (*nominal_bit_rate).max(0) as i64,
+            _ => 0,
+        }
+    }
+
+    /// ストリーム一覧の1項目（indexは最初のページが現れた順、idはシリアル番号）
+    pub fn stream_info(&self, index: usize, default: bool) -> crate::StreamInfo {
+        let (codec_name, codec_long_name) = self.codec.codec_names();
+        let mut tags: HashMap<String, String> = self
+            .comments
+            .iter()
+            .flat_map(|comments| comments.entries.iter().cloned())
+            .collect();
+        let mut stream = crate::StreamInfo {
+            index: index as u32,
+            id: Some(format!("0x{:x}", self.serial)),
+            codec_type: self.codec.codec_type().to_string(),
+            codec_name: codec_name.to_string(),
+            codec_long_name: codec_long_name.to_string(),
+            sample_rate: self.codec.sample_rate() as i32,
+            channels: self.codec.channels() as i32,
+            channel_layout: self.codec.channel_layout(),
+            bit_rate: self.bit_rate(),
+            language: tags.remove("language"),
+            title: tags.remove("title"),
+            disposition: StreamDisposition {
+                default,
+                ..Default::default()
+            },
+            tags,
+            ..Default::default()
+        };
+        if let OggCodec::Flac(info) = &self.codec {
+            stream.bits_per_sample = info.bits_per_sample as i32;
+        }
+        stream
+    }
+}
+
+/// ヘッダーパケットを組み立て中のストリーム状態
+struct StreamState {
+    serial: u32,
+    link: usize,
+    packets: Vec<Vec<u8>>,
+    partial: Vec<u8>,
+    last_granule: Option<u64>,
+    byte_size: u64,
+    ended: bool,
+}
+
+impl StreamState {
+    fn needs_packets(&self) -> bool {
+        self.packets.len() < 2
+    }
+
+    fn push_page_body(&mut self, lacing: &[u8], body: &[u8], continued: bool) {
+        if continued && self.partial.is_empty() && self.packets.is_empty() {
+            // 先頭パケットの途中から始まることはない
+            return;
+        }
+        let mut pos = 0;
+        for &segment in lacing {
+            let end = (pos + segment as usize).min(body.len());
+            if self.partial.len() + (end - pos) <= MAX_PACKET_SIZE {
+                self.partial.extend_from_slice(&body[pos..end]);
+            }
+            pos = end;
+            if segment < 255 {
+                self.packets.push(std::mem::take(&mut self.partial));
+                if !self.needs_packets() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> ParseResult<LogicalStream> {
+        let codec = match self.packets.first() {
+            Some(packet) => OggCodec::identify(packet)?,
+            None => OggCodec::Unknown,
+        };
+        let comments = match self.packets.get(1) {
+            Some(packet) => codec.parse_comments(packet)?,
+            None => None,
+        };
+        Ok(LogicalStream {
+            serial: self.serial,
+            codec,
+            comments,
+            link: self.link,
+            last_granule: self.last_granule,
+            byte_size: self.byte_size,
+        })
+    }
+}
+
+struct PageHeader {
+    header_type: u8,
+    granule: u64,
+    serial: u32,
+    lacing: Vec<u8>,
+}
+
+impl PageHeader {
+    fn header_len(&self) -> u64 {
+        27 + self.lacing.len() as u64
+    }
+
+    fn body_len(&self) -> u64 {
+        self.lacing.iter().map(|l| *l as u64).sum()
+    }
+}
+
+fn read_page_header<R: Read + Seek>(
+    window: &mut Window<R>,
+    offset: u64,
+) -> ParseResult<Option<PageHeader>> {
+    let Some(fixed) = window.get(offset, 27)? else {
+        return Ok(None);
+    };
+    if &fixed[0..4] != b"OggS" || fixed[4] != 0 {
+        return Ok(None);
+    }
+    let header_type = fixed[5];
+    let granule = u64::from_le_bytes(fixed[6..14].try_into().unwrap());
+    let serial = u32::from_le_bytes(fixed[14..18].try_into().unwrap());
+    let segment_count = fixed[26] as usize;
+
+    let Some(lacing) = window.get(offset + 27, segment_count)? else {
+        return Ok(None);
+    };
+    Ok(Some(PageHeader {
+        header_type,
+        granule,
+        serial,
+        lacing: lacing.to_vec(),
+    }))
+}
+
+/// 次の"OggS"の位置を探す
+fn resync<R: Read + Seek>(
+    window: &mut Window<R>,
+    start: u64,
+    file_len: u64,
+) -> ParseResult<Option<u64>> {
+    let limit = file_len.min(start + MAX_RESYNC);
+    let mut offset = start;
+    while offset + 4 <= limit {
+        if window.get(offset, 4)? == Some(b"OggS".as_slice()) {
+            return Ok(Some(offset));
+        }
+        offset += 1;
+    }
+    Ok(None)
+}
+
+/// Oggファイル全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct OggFile {
+    /// 最初のページが現れた順の論理ストリーム
+    pub streams: Vec<LogicalStream>,
+    /// 連結されたリンクの数（通常は1）
+    pub link_count: usize,
+}
+
+impl OggFile {
+    pub fn audio_streams(&self) -> impl Iterator<Item = &LogicalStream> {
+        self.streams.iter().filter(|s| s.codec.is_audio())
+    }
+
+    /// 各リンクの中で最も長い音声ストリームの再生時間の合計
+    pub fn duration_seconds(&self) -> f64 {
+        (0..self.link_count)
+            .map(|link| {
+                self.audio_streams()
+                    .filter(|s| s.link == link)
+                    .map(|s| s.duration_seconds())
+                    .fold(0.0, f64::max)
+            })
+            .sum()
+    }
+}
+
+/// 全ページを走査して論理ストリームを収集する
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<OggFile> {
+    let file_len = stream_len(reader)?;
+    let mut window = Window::new(reader);
+
+    if window.get(0, 4)? != Some(b"OggS".as_slice()) {
+        return Err(ParseError::Invalid(
+            "missing OggS capture pattern".to_string(),
+        ));
+    }
+
+    let mut states: Vec<StreamState> = Vec::new();
+    let mut link = 0;
+    let mut offset = 0;
+
+    while offset < file_len {
+        let page = match read_page_header(&mut window, offset)? {
+            Some(page) => page,
+            None => match resync(&mut window, offset + 1, file_len)? {
+                Some(next) => {
+                    offset = next;
+                    continue;
+                }
+                None => break,
+            },
+        };
+        let page_len = page.header_len() + page.body_len();
+
+        let index = match states.iter().position(|s| s.serial == page.serial) {
+            Some(index) => index,
+            None => {
+                if page.header_type & PAGE_BOS == 0 {
+                    // BOSなしで現れたストリームは無視する
+                    offset += page_len;
+                    continue;
+                }
+                // 既存のストリームが全て終了していれば新しいリンク（chained）
+                if !states.is_empty() && states.iter().all(|s| s.ended) {
+                    link += 1;
+                }
+                states.push(StreamState {
+                    serial: page.serial,
+                    link,
+                    packets: Vec::new(),
+                    partial: Vec::new(),
+                    last_granule: None,
+                    byte_size: 0,
+                    ended: false,
+                });
+                states.len() - 1
+            }
+        };
+
+        let state = &mut states[index];
+        state.byte_size += page_len;
+        // -1はこのページでパケットが完結しないことを示す
+        if page.granule != u64::MAX {
+            state.last_granule = Some(page.granule);
+        }
+        if page.header_type & PAGE_EOS != 0 {
+            state.ended = true;
+        }
+        if state.needs_packets() {
+            if let Some(body) = window.get(offset + page.header_len(), page.body_len() as usize)? {
+                state.push_page_body(&page.lacing, body, page.header_type & PAGE_CONTINUED != 0);
+            }
+        }
+
+        offset += page_len;
+    }
+
+    if states.is_empty() {
+        return Err(ParseError::Invalid(
+            "no Ogg logical streams found".to_string(),
+        ));
+    }
+
+    let streams = states
+        .into_iter()
+        .map(StreamState::finish)
+        .collect::<ParseResult<Vec<_>>>()?;
+
+    Ok(OggFile {
+        streams,
+        link_count: link + 1,
+    })
+}
+
+/// Oggファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let ogg = read(reader)?;
+    let primary = ogg
+        .audio_streams()
+        .next()
+        .ok_or_else(|| ParseError::Invalid("Ogg file has no audio stream".to_string()))?;
+    let (codec_name, codec_long_name) = primary.codec.codec_names();
+
+    audio_info.format_name = "ogg".to_string();
+    audio_info.format_long_name = "Ogg".to_string();
+    audio_info.codec_name = codec_name.to_string();
+    audio_info.codec_long_name = codec_long_name.to_string();
+    audio_info.sample_rate = primary.codec.sample_rate() as i32;
+    audio_info.channels = primary.codec.channels() as i32;
+    audio_info.channel_layout = primary.codec.channel_layout();
+    if let OggCodec::Flac(info) = &primary.codec {
+        audio_info.bits_per_sample = info.bits_per_sample as i32;
+        audio_info.audio_md5 = info.md5_hex();
+    }
+    audio_info.has_video = ogg.streams.iter().any(|s| s.codec == OggCodec::Theora);
+    audio_info.duration_seconds = ogg.duration_seconds();
+
+    let audio_bytes: u64 = ogg.audio_streams().map(|s| s.byte_size).sum();
+    if audio_info.duration_seconds > 0.0 {
+        audio_info.bit_rate =
+            (audio_bytes as f64 * 8.0 / audio_info.duration_seconds).round() as i64;
+    } else if let OggCodec::Vorbis {
+        nominal_bit_rate, ..
+    } = primary.codec
+    {
+        audio_info.bit_rate = nominal_bit_rate.max(0) as i64;
+    }
+
+    if let Some(comments) = &primary.comments {
+        merge_metadata(&mut audio_info.metadata, comments.entries.clone());
+        audio_info.chapters = comments.chapters.clone();
+        audio_info.artwork = comments
+            .pictures
+            .iter()
+            .map(|picture| artwork::from_picture(picture.clone(), "vorbis_picture"))
+            .collect();
+    }
+
+    // 多重化・連結されたストリームも含め、論理ストリームごとに1項目
+    audio_info.streams = ogg
+        .streams
+        .iter()
+        .enumerate()
+        .map(|(index, stream)| stream.stream_info(index, stream.serial == primary.serial))
+        .collect();
+    if ogg.link_count > 1 {
+        audio_info.warnings.push(format!(
+            "Chained Ogg file with {} links; duration is the total of all links, other fields describe the first link",
+            ogg.link_count
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// 1ページ1パケットのページを作る（CRCは検証しないため0のまま）
+    fn page(serial: u32, header_type: u8, granule: u64, packet: &[u8]) -> Vec<u8> {
+        let mut lacing = vec![255u8; packet.len() / 255];
+        lacing.push((packet.len() % 255) as u8);
+
+        let mut out = b"OggS\x00".to_vec();
+        out.push(header_type);
+        out.extend_from_slice(&granule.to_le_bytes());
+        out.extend_from_slice(&serial.to_le_bytes());
+        out.extend_from_slice(&[0; 8]);
+        out.push(lacing.len() as u8);
+        out.extend_from_slice(&lacing);
+        out.extend_from_slice(packet);
+        out
+    }
+
+    fn opus_head(channels: u8, pre_skip: u16) -> Vec<u8> {
+        let mut head = b"OpusHead\x01".to_vec();
+        head.push(channels);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&44100u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        head
+    }
+
+    fn vorbis_id(channels: u8, sample_rate: u32) -> Vec<u8> {
+        let mut id = b"\x01vorbis".to_vec();
+        id.extend_from_slice(&0u32.to_le_bytes());
+        id.push(channels);
+        id.extend_from_slice(&sample_rate.to_le_bytes());
+        id.extend_from_slice(&0i32.to_le_bytes());
+        id.extend_from_slice(&128000i32.to_le_bytes());
+        id.extend_from_slice(&0i32.to_le_bytes());
+        id.push(0xB8);
+        id.push(1);
+        id
+    }
+
+    #[test]
+    fn test_opus_pre_skip_and_tags() {
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend(vorbis::build_comments("libopus", &["ARTIST=Someone"]));
+
+        let mut bytes = page(7, PAGE_BOS, 0, &opus_head(2, 312));
+        bytes.extend(page(7, 0, 0, &tags));
+        bytes.extend(page(7, 0, 48000, &[0u8; 400]));
+        bytes.extend(page(7, PAGE_EOS, 48000 * 3 + 312, &[0u8; 400]));
+
+        let mut info = AudioInfo::new("test.opus".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "opus");
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.channel_layout, "stereo");
+        assert_eq!(info.duration_seconds, 3.0);
+        assert_eq!(info.metadata["artist"], "Someone");
+        assert!(info.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_chained_vorbis_links_are_reported() {
+        let mut comment = b"\x03vorbis".to_vec();
+        comment.extend(vorbis::build_comments("Xiph", &["TITLE=First"]));
+
+        let mut bytes = Vec::new();
+        for (serial, seconds) in [(1u32, 2u64), (2, 3)] {
+            bytes.extend(page(serial, PAGE_BOS, 0, &vorbis_id(1, 22050)));
+            bytes.extend(page(serial, 0, 0, &comment));
+            bytes.extend(page(serial, PAGE_EOS, 22050 * seconds, &[0u8; 100]));
+        }
+
+        let ogg = read(&mut Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(ogg.link_count, 2);
+
+        let mut info = AudioInfo::new("test.ogg".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "vorbis");
+        assert_eq!(info.duration_seconds, 5.0);
+        assert_eq!(info.metadata["title"], "First");
+        assert_eq!(info.warnings.len(), 1);
+        assert!(info.warnings[0].contains("2 links"));
+        let ids: Vec<_> = info.streams.iter().map(|s| s.id.as_deref()).collect();
+        assert_eq!(ids, [Some("0x1"), Some("0x2")]);
+        assert!(info.streams[0].disposition.default);
+        assert!(!info.streams[1].disposition.default);
+    }
+
+    #[test]
+    fn test_multiplexed_streams_are_listed() {
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend(vorbis::build_comments("libopus", &["LANGUAGE=jpn"]));
+
+        let mut bytes = page(0x1f, PAGE_BOS, 0, b"\x80theora");
+        bytes.extend(page(0x2a, PAGE_BOS, 0, &opus_head(1, 0)));
+        bytes.extend(page(0x2a, 0, 0, &tags));
+        bytes.extend(page(0x1f, PAGE_EOS, 10, &[0u8; 50]));
+        bytes.extend(page(0x2a, PAGE_EOS, 96000, &[0u8; 50]));
+
+        let mut info = AudioInfo::new("test.ogg".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert!(info.has_video);
+        assert_eq!(info.channels, 1);
+        assert_eq!(info.duration_seconds, 2.0);
+        assert!(info.warnings.is_empty());
+
+        assert_eq!(info.streams.len(), 2);
+        let video = &info.streams[0];
+        assert_eq!(video.id.as_deref(), Some("0x1f"));
+        assert_eq!(video.codec_type, "video");
+        assert_eq!(video.codec_name, "theora");
+        assert!(!video.disposition.default);
+        let audio = &info.streams[1];
+        assert_eq!(audio.index, 1);
+        assert_eq!(audio.id.as_deref(), Some("0x2a"));
+        assert_eq!(audio.codec_name, "opus");
+        assert_eq!((audio.sample_rate, audio.channels), (48000, 1));
+        assert_eq!(audio.channel_layout, "mono");
+        assert_eq!(audio.language.as_deref(), Some("jpn"));
+        assert!(audio.disposition.default);
+    }
+
+    #[test]
+    fn test_flac_in_ogg() {
+        let mut head = b"\x7fFLAC\x01\x00\x00\x01fLaC\x00\x00\x00\x22".to_vec();
+        head.extend(super::super::flac::build_streaminfo(44100, 2, 16, 0));
+        let mut bytes = page(3, PAGE_BOS, 0, &head);
+        bytes.extend(page(3, PAGE_EOS, 88200, &[0u8; 10]));
+
+        let ogg = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(ogg.streams[0].codec.codec_names().0, "flac");
+        assert_eq!(ogg.duration_seconds(), 2.0);
+    }
+}
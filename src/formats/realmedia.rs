@@ -0,0 +1,460 @@
+// RealMedia (.rm) / RealAudio (.ra) パーサー
+//
+// .RMFファイルはPROP（全体情報）・CONT（タイトルなど）・MDPR（ストリーム）チャンクを読み、
+// 音声ストリームの型固有データ（".ra\xfd"で始まるRealAudioヘッダー）から音声情報を得る。
+// ".ra\xfd"で始まる単体のRealAudioファイルにも対応する。
+
+use super::{
+    default_channel_layout, read_array, read_prefixed_string, read_u16_be, read_u32_be, read_vec,
+    skip_bytes, stream_len, ParseError, ParseResult,
+};
+use crate::AudioInfo;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+const RMF_MAGIC: &[u8; 4] = b".RMF";
+const RA_MAGIC: &[u8; 4] = b".ra\xfd";
+
+/// ヘッダーチャンクの読み込み上限
+const MAX_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// 単体.raファイルのヘッダーとして読む範囲
+const RA_HEADER_READ: u64 = 4096;
+
+/// バイト/分をビット/秒に変換する（破損したヘッダーでも桁あふれしないよう64ビットで計算）
+fn bit_rate_from_bytes_per_minute(bytes_per_minute: u32) -> Option<u32> {
+    u32::try_from(u64::from(bytes_per_minute) * 8 / 60)
+        .ok()
+        .filter(|rate| *rate > 0)
+}
+
+/// CONTチャンク・v3ヘッダーの文字列をメタデータに変換する
+///
+/// FFmpegは作成者を"author"として出力するが、他フォーマットと揃えて"artist"にする。
+fn content_entries<R: Read>(reader: &mut R, len_bytes: usize) -> io::Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+    for key in ["title", "artist", "copyright", "comment"] {
+        let value = read_prefixed_string(reader, len_bytes)?;
+        if !value.is_empty() {
+            entries.push((key.to_string(), value));
+        }
+    }
+    Ok(entries)
+}
+
+/// RealAudioストリームヘッダー（".ra\xfd"）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaHeader {
+    pub version: u16,
+    pub fourcc: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bit_rate: Option<u32>,
+    /// 音声データのバイト数（v4のみ）
+    pub data_size: Option<u32>,
+    /// 音声データの開始位置（v3のみ、ヘッダー先頭からの相対位置）
+    pub data_offset: Option<u64>,
+    /// v3ヘッダーに含まれるタイトルなど
+    pub metadata: Vec<(String, String)>,
+}
+
+impl RaHeader {
+    pub fn parse(data: &[u8]) -> ParseResult<Self> {
+        let mut reader = Cursor::new(data);
+        let magic: [u8; 4] = read_array(&mut reader)?;
+        if &magic != RA_MAGIC {
+            return Err(ParseError::Invalid("missing RealAudio header".to_string()));
+        }
+        let version = read_u16_be(&mut reader)?;
+        match version {
+            3 => Self::parse_v3(&mut reader),
+            4 | 5 => Self::parse_v4(&mut reader, version),
+            _ => Err(ParseError::Invalid(format!(
+                "unsupported RealAudio version {}",
+                version
+            ))),
+        }
+    }
+
+    /// 14.4K（lpcJ）専用の旧形式
+    fn parse_v3(reader: &mut Cursor<&[u8]>) -> ParseResult<Self> {
+        let header_size = read_u16_be(reader)? as u64;
+        let start = reader.position();
+        skip_bytes(reader, 8)?;
+        let bytes_per_minute = read_u16_be(reader)? as u32;
+        skip_bytes(reader, 4)?;
+        let metadata = content_entries(reader, 1)?;
+
+        Ok(Self {
+            version: 3,
+            fourcc: "lpcJ".to_string(),
+            sample_rate: 8000,
+            channels: 1,
+            bit_rate: bit_rate_from_bytes_per_minute(bytes_per_minute),
+            data_size: None,
+            data_offset: Some(start + header_size),
+            metadata,
+        })
+    }
+
+    fn parse_v4(reader: &mut Cursor<&[u8]>, version: u16) -> ParseResult<Self> {
+        skip_bytes(reader, 2 + 4)?; // revision, ".ra4"
+        let data_size = read_u32_be(reader)?;
+        skip_bytes(reader, 2 + 4 + 2 + 4 + 4)?; // version2, header_size, flavor, coded_frame_size, ???
+        let bytes_per_minute = read_u32_be(reader)?;
+        skip_bytes(reader, 4 + 2 + 2 + 2 + 2)?; // ???, sub_packet_h, frame_size, sub_packet_size, ???
+        if version == 5 {
+            skip_bytes(reader, 6)?;
+        }
+        let sample_rate = read_u16_be(reader)? as u32;
+        skip_bytes(reader, 4)?; // ???, sample_size
+        let channels = read_u16_be(reader)?;
+
+        let fourcc = if version == 5 {
+            skip_bytes(reader, 4)?; // インターリーバー
+            let fourcc: [u8; 4] = read_array(reader)?;
+            String::from_utf8_lossy(&fourcc).to_string()
+        } else {
+            read_prefixed_string(reader, 1)?; // インターリーバー
+            read_prefixed_string(reader, 1)?
+        };
+
+        Ok(Self {
+            version,
+            fourcc,
+            sample_rate,
+            channels,
+            // v5のヘッダーにはビットレートがない（MDPRの平均ビットレートを使う）
+            bit_rate: bit_rate_from_bytes_per_minute(bytes_per_minute).filter(|_| version == 4),
+            data_size: (version == 4).then_some(data_size),
+            data_offset: None,
+            metadata: Vec::new(),
+        })
+    }
+
+    /// FourCCに対応するffprobeのコーデック名
+    pub fn codec_names(&self) -> Option<(&'static str, &'static str)> {
+        Some(match self.fourcc.as_str() {
+            "lpcJ" => ("real_144", "RealAudio 1.0 (14.4K)"),
+            "28_8" => ("real_288", "RealAudio 2.0 (28.8K)"),
+            "cook" => ("cook", "Cook / Cooker / Gecko (RealAudio G2)"),
+            "sipr" => ("sipr", "RealAudio SIPR / ACELP.NET"),
+            "atrc" => ("atrac3", "ATRAC3 (Adaptive TRansform Acoustic Coding 3)"),
+            "raac" | "racp" => ("aac", "AAC (Advanced Audio Coding)"),
+            "ralf" => ("ralf", "RealAudio Lossless"),
+            "dnet" => ("ac3", "ATSC A/52A (AC-3)"),
+            _ => return None,
+        })
+    }
+}
+
+/// MDPR（メディアプロパティ）チャンク
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaProperties {
+    pub stream_number: u16,
+    pub avg_bit_rate: u32,
+    pub duration_ms: u32,
+    pub mime_type: String,
+    pub type_specific: Vec<u8>,
+}
+
+impl MediaProperties {
+    fn parse(body: &[u8]) -> io::Result<Self> {
+        let mut reader = Cursor::new(body);
+        let stream_number = read_u16_be(&mut reader)?;
+        skip_bytes(&mut reader, 4)?; // max_bit_rate
+        let avg_bit_rate = read_u32_be(&mut reader)?;
+        skip_bytes(&mut reader, 4 + 4 + 4 + 4)?; // max/avg_packet_size, start_time, preroll
+        let duration_ms = read_u32_be(&mut reader)?;
+        read_prefixed_string(&mut reader, 1)?; // stream_name
+        let mime_type = read_prefixed_string(&mut reader, 1)?;
+        let len = read_u32_be(&mut reader)? as usize;
+        if len > body.len() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let mut type_specific = vec![0u8; len];
+        reader.read_exact(&mut type_specific)?;
+        Ok(Self {
+            stream_number,
+            avg_bit_rate,
+            duration_ms,
+            mime_type,
+            type_specific,
+        })
+    }
+}
+
+/// RealMedia/RealAudioファイル全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealMediaFile {
+    /// 単体の.raファイル（.RMFコンテナなし）
+    pub bare_audio: bool,
+    pub audio: RaHeader,
+    pub duration_seconds: f64,
+    pub bit_rate: Option<u32>,
+    pub has_video: bool,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl RealMediaFile {
+    fn read_rmf<R: Read + Seek>(reader: &mut R, file_len: u64) -> ParseResult<Self> {
+        let mut offset = 0;
+        let mut prop = None;
+        let mut metadata = Vec::new();
+        let mut audio = None;
+        let mut has_video = false;
+
+        while offset + 10 <= file_len {
+            reader.seek(SeekFrom::Start(offset))?;
+            let id: [u8; 4] = read_array(reader)?;
+            let size = read_u32_be(reader)? as u64;
+            if size < 10 || &id == b"DATA" {
+                break;
+            }
+            skip_bytes(reader, 2)?; // object_version
+            let body_len = size - 10;
+
+            match &id {
+                b"PROP" => {
+                    let body = read_vec(reader, body_len, MAX_CHUNK_SIZE)?;
+                    let mut body = Cursor::new(body.as_slice());
+                    skip_bytes(&mut body, 4)?; // max_bit_rate
+                    let avg_bit_rate = read_u32_be(&mut body)?;
+                    skip_bytes(&mut body, 12)?; // max/avg_packet_size, num_packets
+                    let duration_ms = read_u32_be(&mut body)?;
+                    prop = Some((avg_bit_rate, duration_ms));
+                }
+                b"CONT" => {
+                    let body = read_vec(reader, body_len, MAX_CHUNK_SIZE)?;
+                    metadata = content_entries(&mut Cursor::new(body.as_slice()), 2)?;
+                }
+                b"MDPR" => {
+                    let body = read_vec(reader, body_len, MAX_CHUNK_SIZE)?;
+                    let mdpr = MediaProperties::parse(&body)?;
+                    if mdpr.mime_type.starts_with("video/") {
+                        has_video = true;
+                    } else if audio.is_none() && mdpr.type_specific.starts_with(RA_MAGIC) {
+                        audio = Some((RaHeader::parse(&mdpr.type_specific)?, mdpr));
+                    }
+                }
+                _ => {}
+            }
+            offset += size;
+        }
+
+        let (header, mdpr) =
+            audio.ok_or_else(|| ParseError::Invalid("no RealAudio stream found".to_string()))?;
+        let duration_ms = prop
+            .map(|(_, duration)| duration)
+            .filter(|duration| *duration > 0)
+            .unwrap_or(mdpr.duration_ms);
+        let bit_rate = [
+            header.bit_rate,
+            Some(mdpr.avg_bit_rate),
+            prop.map(|(rate, _)| rate),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|rate| *rate > 0);
+
+        Ok(Self {
+            bare_audio: false,
+            audio: header,
+            duration_seconds: duration_ms as f64 / 1000.0,
+            bit_rate,
+            has_video,
+            metadata,
+        })
+    }
+
+    fn read_bare<R: Read + Seek>(reader: &mut R, file_len: u64) -> ParseResult<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let data = read_vec(reader, file_len.min(RA_HEADER_READ), RA_HEADER_READ)?;
+        let header = RaHeader::parse(&data)?;
+
+        let data_size = match (header.data_size, header.data_offset) {
+            (Some(size), _) => size as u64,
+            (None, Some(offset)) => file_len.saturating_sub(offset),
+            (None, None) => 0,
+        };
+        let duration_seconds = match header.bit_rate {
+            Some(rate) if rate > 0 => data_size as f64 * 8.0 / rate as f64,
+            _ => 0.0,
+        };
+
+        Ok(Self {
+            bare_audio: true,
+            duration_seconds,
+            bit_rate: header.bit_rate,
+            has_video: false,
+            metadata: header.metadata.clone(),
+            audio: header,
+        })
+    }
+
+    pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<Self> {
+        let file_len = stream_len(reader)?;
+        reader.seek(SeekFrom::Start(0))?;
+        let magic: [u8; 4] = read_array(reader)?;
+        match &magic {
+            RMF_MAGIC => Self::read_rmf(reader, file_len),
+            RA_MAGIC => Self::read_bare(reader, file_len),
+            _ => Err(ParseError::Invalid("not a RealMedia file".to_string())),
+        }
+    }
+}
+
+/// RealMedia/RealAudioファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let rm = RealMediaFile::read(reader)?;
+    let (codec_name, codec_long_name) = rm.audio.codec_names().ok_or_else(|| {
+        ParseError::Invalid(format!("unknown RealAudio codec '{}'", rm.audio.fourcc))
+    })?;
+
+    audio_info.format_name = "rm".to_string();
+    audio_info.format_long_name = "RealMedia".to_string();
+    audio_info.codec_name = codec_name.to_string();
+    audio_info.codec_long_name = codec_long_name.to_string();
+    audio_info.sample_rate = rm.audio.sample_rate as i32;
+    audio_info.channels = rm.audio.channels as i32;
+    audio_info.channel_layout = default_channel_layout(rm.audio.channels);
+    audio_info.duration_seconds = rm.duration_seconds;
+    audio_info.bit_rate = rm.bit_rate.unwrap_or(0) as i64;
+    audio_info.has_video = rm.has_video;
+    audio_info
+        .codec_params
+        .insert("ra_version".to_string(), rm.audio.version.to_string());
+    audio_info
+        .codec_params
+        .insert("fourcc".to_string(), rm.audio.fourcc.clone());
+    audio_info.metadata = rm.metadata.into_iter().collect();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(body.len() as u32 + 10).to_be_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn str16(value: &str) -> Vec<u8> {
+        let mut out = (value.len() as u16).to_be_bytes().to_vec();
+        out.extend_from_slice(value.as_bytes());
+        out
+    }
+
+    /// 44.1kHzステレオのcook（RealAudio v5）ヘッダー
+    fn ra5_header() -> Vec<u8> {
+        let mut out = RA_MAGIC.to_vec();
+        out.extend_from_slice(&5u16.to_be_bytes());
+        out.extend_from_slice(&[0; 2]);
+        out.extend_from_slice(b".ra5");
+        out.extend_from_slice(&[0; 4 + 2 + 4 + 2 + 4 + 4 + 4 + 4 + 8 + 6]);
+        out.extend_from_slice(&44100u16.to_be_bytes());
+        out.extend_from_slice(&[0, 0, 0, 16]);
+        out.extend_from_slice(&2u16.to_be_bytes());
+        out.extend_from_slice(b"genr");
+        out.extend_from_slice(b"cook");
+        out
+    }
+
+    #[test]
+    fn test_rmf_with_cook_stream() {
+        let mut prop = vec![0u8; 40];
+        prop[4..8].copy_from_slice(&64_000u32.to_be_bytes());
+        prop[20..24].copy_from_slice(&183_500u32.to_be_bytes());
+
+        let mut cont = str16("Radio Drama");
+        cont.extend(str16("Broadcaster"));
+        cont.extend(str16(""));
+        cont.extend(str16(""));
+
+        let type_specific = ra5_header();
+        let mut mdpr = vec![0u8; 30];
+        mdpr[6..10].copy_from_slice(&64_000u32.to_be_bytes());
+        mdpr.push(0); // stream_name
+        let mime = b"audio/x-pn-realaudio";
+        mdpr.push(mime.len() as u8);
+        mdpr.extend_from_slice(mime);
+        mdpr.extend_from_slice(&(type_specific.len() as u32).to_be_bytes());
+        mdpr.extend(type_specific);
+
+        let mut file = RMF_MAGIC.to_vec();
+        file.extend_from_slice(&18u32.to_be_bytes());
+        file.extend_from_slice(&[0; 10]);
+        file.extend(chunk(b"PROP", &prop));
+        file.extend(chunk(b"CONT", &cont));
+        file.extend(chunk(b"MDPR", &mdpr));
+        file.extend(chunk(b"DATA", &[0; 8]));
+
+        let mut info = AudioInfo::new("drama.ra".into());
+        probe(&mut Cursor::new(file), &mut info).unwrap();
+        assert_eq!(info.format_name, "rm");
+        assert_eq!(info.codec_name, "cook");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.duration_seconds, 183.5);
+        assert_eq!(info.bit_rate, 64_000);
+        assert_eq!(info.metadata["title"], "Radio Drama");
+        assert_eq!(info.metadata["artist"], "Broadcaster");
+        assert!(!info.metadata.contains_key("copyright"));
+    }
+
+    #[test]
+    fn test_ra_v4_bytes_per_minute_near_max() {
+        let bytes_per_minute = u32::MAX - 1;
+        let mut header = RA_MAGIC.to_vec();
+        header.extend_from_slice(&4u16.to_be_bytes());
+        header.extend_from_slice(&[0; 2]);
+        header.extend_from_slice(b".ra4");
+        header.extend_from_slice(&1000u32.to_be_bytes()); // data_size
+        header.extend_from_slice(&[0; 2 + 4 + 2 + 4 + 4]);
+        header.extend_from_slice(&bytes_per_minute.to_be_bytes());
+        header.extend_from_slice(&[0; 4 + 2 + 2 + 2 + 2]);
+        header.extend_from_slice(&8000u16.to_be_bytes());
+        header.extend_from_slice(&[0, 0, 0, 16]);
+        header.extend_from_slice(&1u16.to_be_bytes());
+        header.extend_from_slice(b"\x04Int4\x0428_8");
+
+        let ra = RaHeader::parse(&header).unwrap();
+        assert_eq!(ra.fourcc, "28_8");
+        assert_eq!(
+            ra.bit_rate,
+            Some((u64::from(bytes_per_minute) * 8 / 60) as u32)
+        );
+    }
+
+    #[test]
+    fn test_bare_ra_v3() {
+        let mut header = vec![0u8; 8];
+        header.extend_from_slice(&1500u16.to_be_bytes()); // 200 bps
+        header.extend_from_slice(&[0; 4]);
+        for value in ["Old Clip", "Someone", "", ""] {
+            header.push(value.len() as u8);
+            header.extend_from_slice(value.as_bytes());
+        }
+
+        let mut file = RA_MAGIC.to_vec();
+        file.extend_from_slice(&3u16.to_be_bytes());
+        file.extend_from_slice(&(header.len() as u16).to_be_bytes());
+        file.extend(header);
+        file.resize(file.len() + 250, 0);
+
+        let rm = RealMediaFile::read(&mut Cursor::new(file)).unwrap();
+        assert!(rm.bare_audio);
+        assert_eq!(rm.audio.codec_names().unwrap().0, "real_144");
+        assert_eq!(rm.audio.sample_rate, 8000);
+        assert_eq!(rm.bit_rate, Some(200));
+        assert_eq!(rm.duration_seconds, 10.0);
+        assert_eq!(
+            rm.metadata[0],
+            ("title".to_string(), "Old Clip".to_string())
+        );
+    }
+}
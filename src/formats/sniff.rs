@@ -0,0 +1,306 @@
+// マジックバイトによるコンテナ判定
+
+use super::{ac3, dts, id3, mpeg::FrameHeader, stream_len};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// 判定に使う先頭バイト数
+const SNIFF_SIZE: u64 = 4096;
+
+/// ASFヘッダーオブジェクトのGUID
+pub(crate) const ASF_HEADER_GUID: [u8; 16] = [
+    0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+
+/// 内容から判定したコンテナ形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    Wave,
+    Mpeg,
+    Adts,
+    Ac3,
+    Dts,
+    Flac,
+    Ogg,
+    Mp4,
+    Aiff,
+    Asf,
+    Matroska,
+    Amr,
+    Au,
+    RealMedia,
+    Ape,
+    WavPack,
+    Dsf,
+    Dsdiff,
+}
+
+impl Container {
+    /// 表示用の名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Container::Wave => "RIFF/WAVE",
+            Container::Mpeg => "MPEG audio",
+            Container::Adts => "ADTS AAC",
+            Container::Ac3 => "AC-3",
+            Container::Dts => "DTS",
+            Container::Flac => "FLAC",
+            Container::Ogg => "Ogg",
+            Container::Mp4 => "ISO-BMFF (MP4)",
+            Container::Aiff => "AIFF",
+            Container::Asf => "ASF",
+            Container::Matroska => "Matroska/WebM",
+            Container::Amr => "AMR",
+            Container::Au => "Sun AU",
+            Container::RealMedia => "RealMedia",
+            Container::Ape => "Monkey's Audio",
+            Container::WavPack => "WavPack",
+            Container::Dsf => "DSF",
+            Container::Dsdiff => "DSDIFF",
+        }
+    }
+
+    /// この形式で一般的な拡張子（先頭が代表）
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Container::Wave => &["wav", "wave", "bwf", "rf64", "bw64"],
+            Container::Mpeg => &["mp3", "mp2", "mp1", "mpga"],
+            Container::Adts => &["aac", "adts"],
+            Container::Ac3 => &["ac3", "eac3", "ec3"],
+            Container::Dts => &["dts", "cpt"],
+            Container::Flac => &["flac", "fla"],
+            Container::Ogg => &["ogg", "oga", "opus", "spx", "ogx"],
+            Container::Mp4 => &["m4a", "m4b", "m4p", "mp4", "mov", "3gp", "3g2"],
+            Container::Aiff => &["aiff", "aif", "aifc"],
+            Container::Asf => &["wma", "asf", "wmv"],
+            Container::Matroska => &["mka", "mkv", "webm", "mk3d"],
+            Container::Amr => &["amr", "awb"],
+            Container::Au => &["au", "snd"],
+            Container::RealMedia => &["rm", "ra", "rmvb"],
+            Container::Ape => &["ape"],
+            Container::WavPack => &["wv"],
+            Container::Dsf => &["dsf"],
+            Container::Dsdiff => &["dff"],
+        }
+    }
+
+    pub fn primary_extension(&self) -> &'static str {
+        self.extensions()[0]
+    }
+
+    pub fn matches_extension(&self, extension: &str) -> bool {
+        let extension = extension.to_ascii_lowercase();
+        self.extensions().contains(&extension.as_str())
+    }
+}
+
+/// MPEGオーディオのフレーム同期（次のフレームも確認できる場合は確認する）
+fn is_mpeg_frame(data: &[u8]) -> bool {
+    let Some(header) = data
+        .get(0..4)
+        .and_then(|b| FrameHeader::parse(b.try_into().unwrap()))
+    else {
+        return false;
+    };
+    let next = header.frame_length() as usize;
+    match data.get(next..next + 4) {
+        Some(bytes) => FrameHeader::parse(bytes.try_into().unwrap()).is_some(),
+        None => true,
+    }
+}
+
+/// ADTSヘッダー（同期ワード0xFFF + layer 00）
+fn is_adts_frame(data: &[u8]) -> bool {
+    let is_header = |b: &[u8]| b.len() >= 7 && b[0] == 0xFF && b[1] & 0xF6 == 0xF0;
+    if !is_header(data) {
+        return false;
+    }
+    let frame_length =
+        ((data[3] as usize & 0x3) << 11) | ((data[4] as usize) << 3) | (data[5] as usize >> 5);
+    if frame_length < 7 {
+        return false;
+    }
+    match data.get(frame_length..) {
+        Some(rest) if rest.len() >= 7 => is_header(rest),
+        _ => true,
+    }
+}
+
+/// AC-3/E-AC-3の同期フレーム（次のフレームも確認できる場合は確認する）
+fn is_ac3_frame(data: &[u8]) -> bool {
+    let Some(frame) = ac3::SyncFrame::parse(data) else {
+        return false;
+    };
+    match data.get(frame.frame_size as usize..) {
+        Some(rest) if rest.len() >= 8 => ac3::SyncFrame::parse(rest).is_some(),
+        _ => true,
+    }
+}
+
+/// 先頭バイト列からコンテナを判定する
+pub fn sniff_bytes(data: &[u8]) -> Option<Container> {
+    let starts = |magic: &[u8]| data.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| data.get(offset..offset + magic.len()) == Some(magic);
+
+    if (starts(b"RIFF") || starts(b"RF64") || starts(b"BW64")) && at(8, b"WAVE") {
+        Some(Container::Wave)
+    } else if starts(b"fLaC") {
+        Some(Container::Flac)
+    } else if starts(b"OggS") {
+        Some(Container::Ogg)
+    } else if at(4, b"ftyp") || at(4, b"moov") {
+        Some(Container::Mp4)
+    } else if starts(b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+        Some(Container::Aiff)
+    } else if starts(&ASF_HEADER_GUID) {
+        Some(Container::Asf)
+    } else if starts(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some(Container::Matroska)
+    } else if starts(b"#!AMR") {
+        Some(Container::Amr)
+    } else if starts(b".snd") {
+        Some(Container::Au)
+    } else if starts(b".RMF") || starts(b".ra\xfd") {
+        Some(Container::RealMedia)
+    } else if starts(b"MAC ") {
+        Some(Container::Ape)
+    } else if starts(b"wvpk") {
+        Some(Container::WavPack)
+    } else if starts(b"DSD ") {
+        Some(Container::Dsf)
+    } else if starts(b"FRM8") && at(12, b"DSD ") {
+        Some(Container::Dsdiff)
+    } else if dts::CoreFrame::parse(data).is_some() {
+        Some(Container::Dts)
+    } else if is_ac3_frame(data) {
+        Some(Container::Ac3)
+    } else if is_adts_frame(data) {
+        Some(Container::Adts)
+    } else if is_mpeg_frame(data) {
+        Some(Container::Mpeg)
+    } else {
+        None
+    }
+}
+
+/// 先頭のID3v2タグを読み飛ばしてからコンテナを判定する
+pub fn sniff<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Container>> {
+    let file_len = stream_len(reader)?;
+
+    let mut offset = 0;
+    let mut has_id3 = false;
+    loop {
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        reader.by_ref().take(SNIFF_SIZE).read_to_end(&mut data)?;
+
+        let tag_len = data
+            .get(0..10)
+            .and_then(|header| id3::v2_tag_len(header.try_into().unwrap()));
+        match tag_len {
+            Some(len) if offset + len < file_len => {
+                has_id3 = true;
+                offset += len;
+            }
+            _ => {
+                // ID3v2タグだけが判別できる場合はMP3とみなす（ffprobeと同じ扱い）
+                let container = sniff_bytes(&data);
+                return Ok(container.or(has_id3.then_some(Container::Mpeg)));
+            }
+        }
+    }
+}
+
+/// ファイルの内容からコンテナを判定する
+pub fn sniff_path(path: &Path) -> io::Result<Option<Container>> {
+    let mut file = File::open(path)?;
+    sniff(&mut file)
+}
+
+/// 拡張子と内容が一致しない場合の警告文
+pub fn extension_mismatch(path: &Path, container: Option<Container>) -> Option<String> {
+    let container = container?;
+    let extension = path.extension()?.to_str()?;
+    if container.matches_extension(extension) {
+        return None;
+    }
+    Some(format!(
+        "File extension '.{}' does not match detected content ({})",
+        extension,
+        container.name()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_sniff_magic_bytes() {
+        let cases: [(&[u8], Container); 12] = [
+            (b"RIFF\x24\x00\x00\x00WAVEfmt ", Container::Wave),
+            (b"fLaC\x00\x00\x00\x22", Container::Flac),
+            (b"OggS\x00\x02", Container::Ogg),
+            (b"\x00\x00\x00\x20ftypM4A ", Container::Mp4),
+            (b"FORM\x00\x00\x00\x00AIFC", Container::Aiff),
+            (&ASF_HEADER_GUID, Container::Asf),
+            (b"\x1A\x45\xDF\xA3\x01", Container::Matroska),
+            (b"#!AMR-WB\n", Container::Amr),
+            (b".snd\x00\x00\x00\x18", Container::Au),
+            (b"wvpk\x00\x00\x00\x00", Container::WavPack),
+            (b".ra\xfd\x00\x04", Container::RealMedia),
+            (
+                b"FRM8\x00\x00\x00\x00\x00\x00\x00\x00DSD ",
+                Container::Dsdiff,
+            ),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(sniff_bytes(data), Some(expected));
+        }
+        assert_eq!(sniff_bytes(b"This is a test MP3 file\n"), None);
+    }
+
+    #[test]
+    fn test_sniff_adts_after_id3() {
+        // ID3v2タグ（本体なし）+ ADTSフレーム2つ
+        let mut data = vec![b'I', b'D', b'3', 4, 0, 0, 0, 0, 0, 0];
+        let frame = [0xFF, 0xF1, 0x50, 0x80, 0x01, 0x1F, 0xFC, 0x00];
+        data.extend_from_slice(&frame);
+        data.extend_from_slice(&frame);
+
+        assert_eq!(
+            sniff(&mut Cursor::new(data)).unwrap(),
+            Some(Container::Adts)
+        );
+        let warning = extension_mismatch(Path::new("renamed.mp3"), Some(Container::Adts));
+        assert_eq!(
+            warning.as_deref(),
+            Some("File extension '.mp3' does not match detected content (ADTS AAC)")
+        );
+        assert_eq!(
+            extension_mismatch(Path::new("song.AAC"), Some(Container::Adts)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sniff_sample_files() {
+        assert_eq!(
+            sniff_path(Path::new("data/bird.wav")).unwrap(),
+            Some(Container::Wave)
+        );
+        for entry in std::fs::read_dir("data").unwrap().flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("mp3") {
+                assert_eq!(
+                    sniff_path(&path).unwrap(),
+                    Some(Container::Mpeg),
+                    "{:?}",
+                    path
+                );
+            }
+        }
+    }
+}
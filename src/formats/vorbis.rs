@@ -0,0 +1,229 @@
+// Vorbisコメント（FLAC, Ogg Vorbis/Opus/FLAC共通）
+
+use super::{
+    channel_layout_from_mask,
+    flac::{self, Picture},
+    le_prefixed_string_at, le_u32_at, ParseError, ParseResult,
+};
+use crate::Chapter;
+
+/// 解析済みのVorbisコメント
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VorbisComments {
+    pub vendor: String,
+    /// 出現順の(キー, 値)。キーはffprobe互換の小文字に変換済み
+    pub entries: Vec<(String, String)>,
+    /// CHAPTERxxx形式のコメントから組み立てたチャプター（該当キーはentriesから除く）
+    pub chapters: Vec<Chapter>,
+    /// METADATA_BLOCK_PICTURE（Base64のFLAC PICTUREブロック）。該当キーはentriesから除く
+    pub pictures: Vec<Picture>,
+}
+
+/// Vorbisコメントのフィールド名をffprobe互換のキーに変換する
+pub(crate) fn comment_key(field: &str) -> String {
+    match field.to_ascii_uppercase().as_str() {
+        "ALBUMARTIST" | "ALBUM ARTIST" => "album_artist".to_string(),
+        "TRACKNUMBER" => "track".to_string(),
+        "DISCNUMBER" => "disc".to_string(),
+        "DESCRIPTION" => "comment".to_string(),
+        _ => field.to_lowercase(),
+    }
+}
+
+/// Vorbis I仕様のチャンネル割り当て（FLAC・Opusマッピングファミリー0/1と共通）
+pub(crate) fn channel_layout(channels: u8) -> String {
+    let mask = match channels {
+        1 => 0x4,
+        2 => 0x3,
+        3 => 0x7,
+        4 => 0x33,
+        5 => 0x37,
+        6 => 0x3F,
+        7 => 0x70F,
+        8 => 0x63F,
+        _ => 0,
+    };
+    channel_layout_from_mask(mask, channels as u16)
+}
+
+/// 標準のBase64（パディングは省略可）をデコードする
+fn decode_base64(value: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(value.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in value
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | digit as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// "HH:MM:SS.mmm"形式の時刻を秒に変換する
+fn parse_chapter_time(value: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in value.trim().split(':') {
+        let part: f64 = part.parse().ok()?;
+        seconds = seconds * 60.0 + part;
+    }
+    Some(seconds).filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+}
+
+/// "CHAPTER001"・"CHAPTER001NAME"・"CHAPTER001URL"などを(番号, 接尾辞)に分ける
+fn chapter_field(field: &str) -> Option<(u32, String)> {
+    let rest = field
+        .get(..7)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("CHAPTER"))
+        .map(|_| &field[7..])?;
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    let number = rest[..digits].parse().ok()?;
+    Some((number, rest[digits..].to_lowercase()))
+}
+
+/// CHAPTERxxxコメント（OggComment/Chapter Extension形式）をチャプターに変換する
+fn build_chapters(fields: Vec<(u32, String, String)>) -> Vec<Chapter> {
+    let mut numbers: Vec<u32> = fields
+        .iter()
+        .filter(|(_, suffix, value)| suffix.is_empty() && parse_chapter_time(value).is_some())
+        .map(|(number, _, _)| *number)
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+
+    numbers
+        .into_iter()
+        .enumerate()
+        .map(|(id, number)| {
+            let mut chapter = Chapter {
+                id: id as i64,
+                ..Default::default()
+            };
+            for (_, suffix, value) in fields.iter().filter(|(n, _, _)| *n == number) {
+                match suffix.as_str() {
+                    "" => chapter.start_time = parse_chapter_time(value).unwrap_or(0.0),
+                    "name" => chapter.title = Some(value.clone()),
+                    _ => {
+                        chapter.tags.insert(suffix.clone(), value.clone());
+                    }
+                }
+            }
+            chapter
+        })
+        .collect()
+}
+
+/// ベンダー文字列 + コメント件数 + "KEY=value" 列を解析する
+pub fn parse_comments(data: &[u8]) -> ParseResult<VorbisComments> {
+    let truncated = || ParseError::Invalid("truncated Vorbis comment".to_string());
+    let mut pos = 0;
+    let vendor = le_prefixed_string_at(data, &mut pos).ok_or_else(truncated)?;
+    let count = le_u32_at(data, pos).ok_or_else(truncated)?;
+    pos += 4;
+
+    let mut entries = Vec::new();
+    let mut chapter_fields = Vec::new();
+    let mut pictures = Vec::new();
+    for _ in 0..count {
+        let comment = le_prefixed_string_at(data, &mut pos).ok_or_else(truncated)?;
+        if let Some((field, value)) = comment.split_once('=') {
+            if value.is_empty() {
+                continue;
+            }
+            if field.eq_ignore_ascii_case("METADATA_BLOCK_PICTURE") {
+                let picture =
+                    decode_base64(value).and_then(|block| flac::parse_picture(&block).ok());
+                pictures.extend(picture);
+                continue;
+            }
+            match chapter_field(field) {
+                Some((number, suffix)) => chapter_fields.push((number, suffix, value.to_string())),
+                None => entries.push((comment_key(field), value.to_string())),
+            }
+        }
+    }
+
+    Ok(VorbisComments {
+        vendor,
+        entries,
+        chapters: build_chapters(chapter_fields),
+        pictures,
+    })
+}
+
+#[cfg(test)]
+pub(crate) fn build_comments(vendor: &str, comments: &[&str]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    out.extend_from_slice(vendor.as_bytes());
+    out.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in comments {
+        out.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        out.extend_from_slice(comment.as_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chapter_comments() {
+        let data = build_comments(
+            "Lavf",
+            &[
+                "TITLE=Audiobook",
+                "CHAPTER002=00:10:00.500",
+                "CHAPTER001=00:00:00.000",
+                "CHAPTER001NAME=Opening",
+                "CHAPTER002NAME=Part 2",
+                "CHAPTER002URL=https://example.com/2",
+                "CHAPTER003NAME=No start time",
+            ],
+        );
+        let comments = parse_comments(&data).unwrap();
+        assert_eq!(
+            comments.entries,
+            vec![("title".to_string(), "Audiobook".to_string())]
+        );
+        assert_eq!(comments.chapters.len(), 2);
+        assert_eq!(comments.chapters[0].title.as_deref(), Some("Opening"));
+        assert_eq!(comments.chapters[1].id, 1);
+        assert_eq!(comments.chapters[1].start_time, 600.5);
+        assert_eq!(comments.chapters[1].tags["url"], "https://example.com/2");
+
+        let mut chapters = comments.chapters;
+        super::super::complete_chapters(&mut chapters, 900.0);
+        assert_eq!(chapters[0].end_time, 600.5);
+        assert_eq!(chapters[1].end_time, 900.0);
+    }
+
+    #[test]
+    fn test_metadata_block_picture() {
+        // picture_type=3, mime="image/png", 説明なし, 1x1, データ4バイト
+        let block = "AAAAAwAAAAlpbWFnZS9wbmcAAAAAAAAAAQAAAAEAAAAAAAAAAAAAAASJUE5H";
+        let data = build_comments("Xiph", &[&format!("METADATA_BLOCK_PICTURE={}", block)]);
+        let comments = parse_comments(&data).unwrap();
+        assert!(comments.entries.is_empty());
+        assert_eq!(comments.pictures.len(), 1);
+        assert_eq!(comments.pictures[0].picture_type, 3);
+        assert_eq!(comments.pictures[0].mime_type, "image/png");
+        assert_eq!(comments.pictures[0].data, b"\x89PNG");
+        assert_eq!(decode_base64("TWFu"), Some(b"Man".to_vec()));
+        assert_eq!(decode_base64("TWE="), Some(b"Ma".to_vec()));
+    }
+}
@@ -0,0 +1,747 @@
+// RIFF/WAVE パーサー
+//
+// 4GBを超えるRF64/BW64は`ds64`チャンクの64ビットサイズで各チャンクの0xFFFFFFFFを置き換える。
+
+use super::bwf::{self, AdmInfo, BextChunk, BroadcastWave, CueMarker, IxmlInfo};
+use super::{
+    channel_layout_from_mask, default_channel_layout, merge_metadata, read_array, read_u32_le,
+    read_vec, stream_len, ParseError, ParseResult,
+};
+use crate::AudioInfo;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_ALAW: u16 = 0x0006;
+const WAVE_FORMAT_MULAW: u16 = 0x0007;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// `fmt `チャンクの最大サイズ（通常は16〜40バイト）
+const MAX_FMT_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// RF64/BW64でサイズが`ds64`にあることを示す値
+const SIZE_IN_DS64: u32 = u32::MAX;
+
+/// `bext`・`iXML`・`LIST`などメタデータチャンクの読み込み上限（超えた場合は読み飛ばす）
+const MAX_METADATA_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+/// `fmt `チャンクの内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveFormat {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    /// WAVE_FORMAT_EXTENSIBLEの場合のみ
+    pub valid_bits_per_sample: Option<u16>,
+    pub channel_mask: Option<u32>,
+    pub sub_format: Option<u16>,
+}
+
+impl WaveFormat {
+    /// WAVEFORMATEX / WAVEFORMATEXTENSIBLE（ASFのStream Propertiesでも使われる）
+    pub(crate) fn parse(data: &[u8]) -> ParseResult<Self> {
+        if data.len() < 16 {
+            return Err(ParseError::Invalid(format!(
+                "fmt chunk too short ({} bytes)",
+                data.len()
+            )));
+        }
+
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes([
+                data[offset],
+                data[offset + 1],
+                data[offset + 2],
+                data[offset + 3],
+            ])
+        };
+
+        let mut format = WaveFormat {
+            format_tag: u16_at(0),
+            channels: u16_at(2),
+            sample_rate: u32_at(4),
+            byte_rate: u32_at(8),
+            block_align: u16_at(12),
+            bits_per_sample: u16_at(14),
+            valid_bits_per_sample: None,
+            channel_mask: None,
+            sub_format: None,
+        };
+
+        // WAVE_FORMAT_EXTENSIBLE: cbSize(2) + wValidBitsPerSample(2) + dwChannelMask(4) + SubFormat GUID(16)
+        if format.format_tag == WAVE_FORMAT_EXTENSIBLE && data.len() >= 40 && u16_at(16) >= 22 {
+            format.valid_bits_per_sample = Some(u16_at(18));
+            format.channel_mask = Some(u32_at(20));
+            // GUIDの先頭2バイトが従来のフォーマットタグに相当する
+            format.sub_format = Some(u16_at(24));
+        }
+
+        if format.channels == 0 {
+            return Err(ParseError::Invalid(
+                "fmt chunk has zero channels".to_string(),
+            ));
+        }
+        if format.sample_rate == 0 {
+            return Err(ParseError::Invalid(
+                "fmt chunk has zero sample rate".to_string(),
+            ));
+        }
+
+        Ok(format)
+    }
+
+    /// EXTENSIBLEの場合はSubFormatを解決した実際のフォーマットタグ
+    pub fn effective_format_tag(&self) -> u16 {
+        match (self.format_tag, self.sub_format) {
+            (WAVE_FORMAT_EXTENSIBLE, Some(sub_format)) => sub_format,
+            (tag, _) => tag,
+        }
+    }
+
+    fn is_uncompressed(&self) -> bool {
+        matches!(
+            self.effective_format_tag(),
+            WAVE_FORMAT_PCM | WAVE_FORMAT_IEEE_FLOAT | WAVE_FORMAT_ALAW | WAVE_FORMAT_MULAW
+        )
+    }
+
+    /// FFmpeg互換のコーデック名と正式名称
+    pub fn codec_names(&self) -> (String, String) {
+        let bits = self.bits_per_sample;
+        let (name, long_name) = match (self.effective_format_tag(), bits) {
+            (WAVE_FORMAT_PCM, 8) => ("pcm_u8", "PCM unsigned 8-bit"),
+            (WAVE_FORMAT_PCM, 16) => ("pcm_s16le", "PCM signed 16-bit little-endian"),
+            (WAVE_FORMAT_PCM, 24) => ("pcm_s24le", "PCM signed 24-bit little-endian"),
+            (WAVE_FORMAT_PCM, 32) => ("pcm_s32le", "PCM signed 32-bit little-endian"),
+            (WAVE_FORMAT_PCM, 64) => ("pcm_s64le", "PCM signed 64-bit little-endian"),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => {
+                ("pcm_f32le", "PCM 32-bit floating point little-endian")
+            }
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => {
+                ("pcm_f64le", "PCM 64-bit floating point little-endian")
+            }
+            (WAVE_FORMAT_ALAW, _) => ("pcm_alaw", "PCM A-law / G.711 A-law"),
+            (WAVE_FORMAT_MULAW, _) => ("pcm_mulaw", "PCM mu-law / G.711 mu-law"),
+            (0x0002, _) => ("adpcm_ms", "ADPCM Microsoft"),
+            (0x0011, _) => ("adpcm_ima_wav", "ADPCM IMA WAV"),
+            (0x0031, _) => ("gsm_ms", "GSM Microsoft variant"),
+            (0x0050, _) => ("mp2", "MP2 (MPEG audio layer 2)"),
+            (0x0055, _) => ("mp3", "MP3 (MPEG audio layer 3)"),
+            (0x00FF, _) | (0x1610, _) => ("aac", "AAC (Advanced Audio Coding)"),
+            (0x000A, _) => ("wmavoice", "Windows Media Audio Voice"),
+            (0x0160, _) => ("wmav1", "Windows Media Audio 1"),
+            (0x0161, _) => ("wmav2", "Windows Media Audio 2"),
+            (0x0162, _) => ("wmapro", "Windows Media Audio 9 Professional"),
+            (0x0163, _) => ("wmalossless", "Windows Media Audio Lossless"),
+            (0x2000, _) => ("ac3", "ATSC A/52A (AC-3)"),
+            (0x2001, _) => ("dts", "DCA (DTS Coherent Acoustics)"),
+            (tag, _) => {
+                return (
+                    format!("unknown_0x{:04x}", tag),
+                    format!("WAVE format tag 0x{:04x} ({}-bit)", tag, bits),
+                )
+            }
+        };
+        (name.to_string(), long_name.to_string())
+    }
+
+    pub fn channel_layout(&self) -> String {
+        match self.channel_mask {
+            Some(mask) => channel_layout_from_mask(mask, self.channels),
+            None => default_channel_layout(self.channels),
+        }
+    }
+}
+
+/// RIFFヘッダーの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiffForm {
+    Riff,
+    /// EBU Tech 3306
+    Rf64,
+    /// ITU-R BS.2088
+    Bw64,
+}
+
+impl RiffForm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RiffForm::Riff => "RIFF",
+            RiffForm::Rf64 => "RF64",
+            RiffForm::Bw64 => "BW64",
+        }
+    }
+}
+
+/// `ds64`チャンク
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ds64 {
+    pub riff_size: u64,
+    pub data_size: u64,
+    pub sample_count: u64,
+    /// `data`以外で4GBを超えるチャンクのサイズ
+    pub table: Vec<([u8; 4], u64)>,
+}
+
+impl Ds64 {
+    fn parse(data: &[u8]) -> ParseResult<Self> {
+        if data.len() < 28 {
+            return Err(ParseError::Invalid(format!(
+                "ds64 chunk too short ({} bytes)",
+                data.len()
+            )));
+        }
+        let u64_at = |i: usize| u64::from_le_bytes(data[i..i + 8].try_into().unwrap());
+        let table_len = u32::from_le_bytes(data[24..28].try_into().unwrap()) as usize;
+        let table = data[28..]
+            .chunks_exact(12)
+            .take(table_len)
+            .map(|entry| {
+                let id: [u8; 4] = entry[0..4].try_into().unwrap();
+                (id, u64::from_le_bytes(entry[4..12].try_into().unwrap()))
+            })
+            .collect();
+
+        Ok(Self {
+            riff_size: u64_at(0),
+            data_size: u64_at(8),
+            sample_count: u64_at(16),
+            table,
+        })
+    }
+
+    /// 32ビットのサイズ欄に収まらないチャンクの実サイズ
+    fn chunk_size(&self, chunk_id: &[u8; 4]) -> Option<u64> {
+        if chunk_id == b"data" {
+            return Some(self.data_size);
+        }
+        self.table
+            .iter()
+            .find(|(id, _)| id == chunk_id)
+            .map(|(_, size)| *size)
+    }
+}
+
+/// WAVEファイル全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveFile {
+    pub form: RiffForm,
+    pub ds64: Option<Ds64>,
+    pub format: WaveFormat,
+    pub data_offset: u64,
+    pub data_size: u64,
+    /// `fact`（RF64では`ds64`）のサンプルフレーム数（圧縮フォーマット用）
+    pub sample_frames: Option<u64>,
+    pub bext: Option<BextChunk>,
+    pub ixml: Option<IxmlInfo>,
+    /// `chna`・`axml`のADM（Audio Definition Model）情報
+    pub adm: Option<AdmInfo>,
+    /// `cue `と`LIST/adtl`から組み立てたマーカー（位置は秒に未換算）
+    pub markers: Vec<CueMarker>,
+    /// `LIST/INFO`のタグ
+    pub info: HashMap<String, String>,
+}
+
+impl WaveFile {
+    pub fn duration_seconds(&self) -> f64 {
+        let format = &self.format;
+        if format.is_uncompressed() && format.block_align > 0 {
+            let frames = self.data_size / format.block_align as u64;
+            frames as f64 / format.sample_rate as f64
+        } else if let Some(frames) = self.sample_frames {
+            frames as f64 / format.sample_rate as f64
+        } else if format.byte_rate > 0 {
+            self.data_size as f64 / format.byte_rate as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn bit_rate(&self) -> i64 {
+        self.format.byte_rate as i64 * 8
+    }
+
+    /// BWF関連のチャンクをまとめる（いずれも無ければNone）
+    pub fn broadcast(&self) -> Option<BroadcastWave> {
+        if self.bext.is_none()
+            && self.ixml.is_none()
+            && self.adm.is_none()
+            && self.markers.is_empty()
+        {
+            return None;
+        }
+        let sample_rate = self.format.sample_rate;
+        let timecode_start = self.bext.as_ref().map(|bext| {
+            let ixml = self.ixml.as_ref();
+            bwf::timecode(
+                bext.time_reference,
+                sample_rate,
+                ixml.and_then(IxmlInfo::frame_rate),
+                ixml.is_some_and(IxmlInfo::drop_frame),
+            )
+        });
+        let markers = self
+            .markers
+            .iter()
+            .cloned()
+            .map(|mut marker| {
+                if sample_rate > 0 {
+                    marker.time_seconds = marker.position as f64 / sample_rate as f64;
+                }
+                marker
+            })
+            .collect();
+
+        Some(BroadcastWave {
+            bext: self.bext.clone(),
+            timecode_start: timecode_start.filter(|tc| !tc.is_empty()),
+            ixml: self.ixml.clone(),
+            adm: self.adm.clone(),
+            markers,
+        })
+    }
+}
+
+/// RIFF/WAVEのチャンクを走査する
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<WaveFile> {
+    let file_len = stream_len(reader)?;
+
+    let riff_header: [u8; 12] = read_array(reader)?;
+    let form = match (&riff_header[0..4], &riff_header[8..12]) {
+        (b"RIFF", b"WAVE") => RiffForm::Riff,
+        (b"RF64", b"WAVE") => RiffForm::Rf64,
+        (b"BW64", b"WAVE") => RiffForm::Bw64,
+        _ => return Err(ParseError::Invalid("not a RIFF/WAVE file".to_string())),
+    };
+
+    let mut ds64: Option<Ds64> = None;
+    let mut format = None;
+    let mut data = None;
+    let mut sample_frames = None;
+    let mut bext = None;
+    let mut ixml = None;
+    let mut chna = None;
+    let mut axml = None;
+    let mut cue_points = Vec::new();
+    let mut adtl = Vec::new();
+    let mut info = HashMap::new();
+
+    loop {
+        let chunk_start = reader.stream_position()?;
+        if chunk_start + 8 > file_len {
+            break;
+        }
+
+        let chunk_id: [u8; 4] = match read_array(reader) {
+            Ok(id) => id,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        let chunk_size = match read_u32_le(reader)? {
+            SIZE_IN_DS64 => ds64
+                .as_ref()
+                .and_then(|ds64| ds64.chunk_size(&chunk_id))
+                .unwrap_or(SIZE_IN_DS64 as u64),
+            size => size as u64,
+        };
+        let body_start = chunk_start + 8;
+        // 実ファイルを超えるサイズ（切り詰められたファイルやストリーミング書き出し、
+        // 壊れた`ds64`）は残りに丸める
+        let chunk_size = chunk_size.min(file_len - body_start);
+
+        match &chunk_id {
+            // `ds64`はWAVE直後の最初のチャンクでなければならない
+            b"ds64" if form != RiffForm::Riff && chunk_start == 12 => {
+                let body = read_vec(reader, chunk_size, MAX_FMT_CHUNK_SIZE)?;
+                ds64 = Some(Ds64::parse(&body)?);
+            }
+            b"fmt " => {
+                let body = read_vec(reader, chunk_size, MAX_FMT_CHUNK_SIZE)?;
+                format = Some(WaveFormat::parse(&body)?);
+            }
+            b"data" => {
+                data = Some((body_start, chunk_size));
+            }
+            b"fact" if chunk_size >= 4 => {
+                sample_frames = Some(read_u32_le(reader)? as u64);
+            }
+            b"bext" | b"iXML" | b"chna" | b"axml" | b"cue " | b"LIST"
+                if chunk_size <= MAX_METADATA_CHUNK_SIZE =>
+            {
+                let body = read_vec(reader, chunk_size, MAX_METADATA_CHUNK_SIZE)?;
+                match &chunk_id {
+                    b"bext" => bext = BextChunk::parse(&body),
+                    b"iXML" => ixml = IxmlInfo::parse(&body),
+                    b"chna" => chna = Some(body),
+                    b"axml" => axml = Some(body),
+                    b"cue " => cue_points = bwf::parse_cue(&body),
+                    _ => match body.get(0..4) {
+                        Some(b"INFO") => info = bwf::parse_info(&body[4..]),
+                        Some(b"adtl") => adtl = body[4..].to_vec(),
+                        _ => {}
+                    },
+                }
+            }
+            _ => {}
+        }
+
+        // チャンクは偶数バイト境界にパディングされる
+        let Some(next) = body_start
+            .checked_add(chunk_size)
+            .and_then(|end| end.checked_add(chunk_size & 1))
+            .filter(|next| *next < file_len)
+        else {
+            break;
+        };
+        reader.seek(SeekFrom::Start(next))?;
+    }
+
+    let format = format.ok_or_else(|| ParseError::Invalid("missing fmt chunk".to_string()))?;
+    let (data_offset, data_size) =
+        data.ok_or_else(|| ParseError::Invalid("missing data chunk".to_string()))?;
+
+    // RF64の`fact`は-1で、実際のサンプル数は`ds64`にある
+    if let Some(ds64) = ds64.as_ref().filter(|ds64| ds64.sample_count > 0) {
+        if sample_frames.is_none_or(|frames| frames == SIZE_IN_DS64 as u64) {
+            sample_frames = Some(ds64.sample_count);
+        }
+    }
+
+    let adm = (chna.is_some() || axml.is_some())
+        .then(|| AdmInfo::parse(chna.as_deref(), axml.as_deref()));
+
+    // `adtl`は`cue `より前に置かれることもあるので、走査後にまとめて対応付ける
+    let mut markers: Vec<CueMarker> = cue_points
+        .into_iter()
+        .map(|(id, position)| CueMarker {
+            id,
+            position,
+            ..Default::default()
+        })
+        .collect();
+    bwf::apply_adtl(&adtl, &mut markers);
+
+    Ok(WaveFile {
+        form,
+        ds64,
+        format,
+        data_offset,
+        data_size,
+        sample_frames,
+        bext,
+        ixml,
+        adm,
+        markers,
+        info,
+    })
+}
+
+/// WAVEファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let wave = read(reader)?;
+    let (codec_name, codec_long_name) = wave.format.codec_names();
+
+    audio_info.format_name = "wav".to_string();
+    audio_info.format_long_name = "WAV / WAVE (Waveform Audio)".to_string();
+    audio_info.codec_name = codec_name;
+    audio_info.codec_long_name = codec_long_name;
+    audio_info.sample_rate = wave.format.sample_rate as i32;
+    audio_info.channels = wave.format.channels as i32;
+    audio_info.bits_per_sample = wave.format.bits_per_sample as i32;
+    audio_info.channel_layout = wave.format.channel_layout();
+    audio_info.bit_rate = wave.bit_rate();
+    audio_info.duration_seconds = wave.duration_seconds();
+    audio_info.broadcast = wave.broadcast();
+
+    if wave.form != RiffForm::Riff {
+        audio_info
+            .codec_params
+            .insert("riff_form".to_string(), wave.form.name().to_string());
+        if wave.ds64.is_none() {
+            audio_info.warnings.push(format!(
+                "{} file has no ds64 chunk; sizes are limited to 4 GB",
+                wave.form.name()
+            ));
+        }
+    }
+    if let Some(adm) = &wave.adm {
+        if adm.track_count > 0 && adm.track_count != wave.format.channels {
+            audio_info.warnings.push(format!(
+                "ADM chna declares {} tracks but fmt has {} channels",
+                adm.track_count, wave.format.channels
+            ));
+        }
+    }
+    merge_metadata(&mut audio_info.metadata, wave.info);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(id);
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    fn riff(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+        out.extend_from_slice(b"WAVE");
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn pcm_fmt(channels: u16, sample_rate: u32, bits: u16) -> Vec<u8> {
+        let block_align = channels * bits / 8;
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&sample_rate.to_le_bytes());
+        fmt.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        fmt.extend_from_slice(&block_align.to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+        fmt
+    }
+
+    #[test]
+    fn test_pcm_16bit_stereo() {
+        let data = vec![0u8; 44100 * 4 * 2];
+        let bytes = riff(&[
+            chunk(b"fmt ", &pcm_fmt(2, 44100, 16)),
+            chunk(b"data", &data),
+        ]);
+
+        let wave = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(wave.format.codec_names().0, "pcm_s16le");
+        assert_eq!(wave.format.channel_layout(), "stereo");
+        assert_eq!(wave.data_offset, 44);
+        assert_eq!(wave.duration_seconds(), 2.0);
+        assert_eq!(wave.bit_rate(), 1_411_200);
+    }
+
+    #[test]
+    fn test_extensible_24bit_mono_with_odd_unknown_chunk() {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&WAVE_FORMAT_EXTENSIBLE.to_le_bytes());
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&48000u32.to_le_bytes());
+        fmt.extend_from_slice(&(48000u32 * 3).to_le_bytes());
+        fmt.extend_from_slice(&3u16.to_le_bytes());
+        fmt.extend_from_slice(&24u16.to_le_bytes());
+        fmt.extend_from_slice(&22u16.to_le_bytes());
+        fmt.extend_from_slice(&24u16.to_le_bytes());
+        fmt.extend_from_slice(&0x4u32.to_le_bytes());
+        fmt.extend_from_slice(&[
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38,
+            0x9B, 0x71,
+        ]);
+
+        let data = vec![0u8; 48000 * 3 / 2];
+        let bytes = riff(&[
+            chunk(b"fmt ", &fmt),
+            chunk(b"junk", &[1, 2, 3]),
+            chunk(b"data", &data),
+        ]);
+
+        let mut info = AudioInfo::new("test.wav".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "pcm_s24le");
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 1);
+        assert_eq!(info.bits_per_sample, 24);
+        assert_eq!(info.channel_layout, "mono");
+        assert_eq!(info.duration_seconds, 0.5);
+    }
+
+    #[test]
+    fn test_broadcast_wave_chunks() {
+        // TimeReference = 10:00:00（48kHz）、25fps
+        let mut bext = vec![0u8; 602];
+        bext[..7].copy_from_slice(b"Take 3!");
+        bext[338..346].copy_from_slice(&(48000u64 * 36000).to_le_bytes());
+        bext[346] = 1;
+        let ixml = b"<BWFXML><SCENE>4</SCENE><TAKE>3</TAKE><SPEED>\
+                     <TIMECODE_RATE>25/1</TIMECODE_RATE></SPEED></BWFXML>";
+
+        let mut cue = 1u32.to_le_bytes().to_vec();
+        cue.extend_from_slice(&7u32.to_le_bytes());
+        cue.extend_from_slice(&[0; 4]);
+        cue.extend_from_slice(b"data");
+        cue.extend_from_slice(&[0; 8]);
+        cue.extend_from_slice(&12000u32.to_le_bytes());
+
+        let mut adtl = b"adtl".to_vec();
+        adtl.extend(chunk(b"labl", b"\x07\x00\x00\x00Slate\0"));
+        let mut info = b"INFO".to_vec();
+        info.extend(chunk(b"INAM", b"Dialogue\0"));
+
+        let bytes = riff(&[
+            chunk(b"bext", &bext),
+            chunk(b"iXML", ixml),
+            chunk(b"fmt ", &pcm_fmt(1, 48000, 24)),
+            chunk(b"LIST", &adtl),
+            chunk(b"cue ", &cue),
+            chunk(b"LIST", &info),
+            chunk(b"data", &vec![0u8; 48000 * 3]),
+        ]);
+
+        let mut info = AudioInfo::new("take3.wav".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.duration_seconds, 1.0);
+        assert_eq!(info.metadata["title"], "Dialogue");
+
+        let broadcast = info.broadcast.unwrap();
+        assert_eq!(broadcast.bext.unwrap().description, "Take 3!");
+        assert_eq!(broadcast.timecode_start.as_deref(), Some("10:00:00:00"));
+        assert_eq!(broadcast.ixml.unwrap().scene.as_deref(), Some("4"));
+        assert_eq!(broadcast.markers.len(), 1);
+        assert_eq!(broadcast.markers[0].label.as_deref(), Some("Slate"));
+        assert_eq!(broadcast.markers[0].time_seconds, 0.25);
+    }
+
+    fn ds64(data_size: u64, sample_count: u64) -> Vec<u8> {
+        let mut body = 0u64.to_le_bytes().to_vec();
+        body.extend_from_slice(&data_size.to_le_bytes());
+        body.extend_from_slice(&sample_count.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body
+    }
+
+    /// サイズ欄を0xFFFFFFFFにしたチャンク
+    fn chunk_in_ds64(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = chunk(id, body);
+        out[4..8].copy_from_slice(&SIZE_IN_DS64.to_le_bytes());
+        out
+    }
+
+    fn rf64(magic: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = riff(chunks);
+        out[0..4].copy_from_slice(magic);
+        out[4..8].copy_from_slice(&SIZE_IN_DS64.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn test_rf64_sizes_from_ds64() {
+        let data = vec![0u8; 48000 * 12 * 2];
+        let bytes = rf64(
+            b"RF64",
+            &[
+                chunk(b"ds64", &ds64(data.len() as u64, 96000)),
+                chunk(b"fmt ", &pcm_fmt(6, 48000, 16)),
+                chunk_in_ds64(b"data", &data),
+                chunk(b"LIST", b"INFOINAM\x04\x00\x00\x00Take"),
+            ],
+        );
+
+        let wave = read(&mut Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(wave.form, RiffForm::Rf64);
+        assert_eq!(wave.data_size, 1_152_000);
+        assert_eq!(wave.duration_seconds(), 2.0);
+        // dataの後ろのチャンクもds64のサイズで辿れる
+        assert_eq!(wave.info["title"], "Take");
+
+        let mut info = AudioInfo::new("long.wav".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.codec_params["riff_form"], "RF64");
+        assert!(info.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_bw64_with_adm_and_ds64_sample_count() {
+        let mut chna = 2u16.to_le_bytes().to_vec();
+        chna.extend_from_slice(&1u16.to_le_bytes());
+        chna.extend_from_slice(&1u16.to_le_bytes());
+        chna.extend_from_slice(b"ATU_00000001AT_00031001_01AP_00031001\0");
+        let axml = br#"<audioObject audioObjectID="AO_1001" audioObjectName="Bird">"#;
+
+        // 圧縮フォーマット（factは-1）のサンプル数はds64から取る
+        let mut fmt = pcm_fmt(2, 48000, 16);
+        fmt[0..2].copy_from_slice(&0x2000u16.to_le_bytes());
+        let bytes = rf64(
+            b"BW64",
+            &[
+                chunk(b"ds64", &ds64(1000, 48000 * 3)),
+                chunk(b"fmt ", &fmt),
+                chunk(b"fact", &SIZE_IN_DS64.to_le_bytes()),
+                chunk(b"chna", &chna),
+                chunk(b"axml", axml),
+                chunk(b"data", &[0u8; 1000]),
+            ],
+        );
+
+        let mut info = AudioInfo::new("objects.wav".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.codec_name, "ac3");
+        assert_eq!(info.duration_seconds, 3.0);
+        assert_eq!(info.codec_params["riff_form"], "BW64");
+        let adm = info.broadcast.unwrap().adm.unwrap();
+        assert_eq!(adm.tracks.len(), 1);
+        assert_eq!(adm.objects, vec!["Bird"]);
+        assert!(info.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_rf64_without_ds64_warns() {
+        let bytes = rf64(
+            b"RF64",
+            &[
+                chunk(b"fmt ", &pcm_fmt(1, 8000, 8)),
+                chunk(b"data", &[0x80; 8000]),
+            ],
+        );
+        let mut info = AudioInfo::new("odd.wav".into());
+        probe(&mut Cursor::new(bytes), &mut info).unwrap();
+        assert_eq!(info.duration_seconds, 1.0);
+        assert_eq!(info.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_corrupt_ds64_data_size() {
+        let bytes = rf64(
+            b"RF64",
+            &[
+                chunk(b"ds64", &ds64(u64::MAX - 7, 8000)),
+                chunk(b"fmt ", &pcm_fmt(1, 8000, 8)),
+                chunk_in_ds64(b"data", &[0x80; 8000]),
+            ],
+        );
+        let wave = read(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(wave.data_size, 8000);
+        assert_eq!(wave.duration_seconds(), 1.0);
+    }
+
+    #[test]
+    fn test_not_a_wave_file() {
+        let result = read(&mut Cursor::new(b"Test WAV file content\n".to_vec()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_sample_wav_file() {
+        let mut file = std::fs::File::open("data/bird.wav").unwrap();
+        let wave = read(&mut file).unwrap();
+        assert_eq!(wave.format.sample_rate, 44100);
+        assert_eq!(wave.format.channels, 2);
+        assert_eq!(wave.data_size, 894_976);
+        assert!((wave.duration_seconds() - 5.0736).abs() < 0.001);
+    }
+}
@@ -0,0 +1,340 @@
+// WavPack ブロックヘッダーパーサー
+//
+// 各ブロックは32バイトの"wvpk"ヘッダーで始まる。マルチチャンネル音声は
+// INITIAL_BLOCKからFINAL_BLOCKまでの連続したブロック（モノラルまたはステレオ）で構成される。
+
+use super::{
+    apetag, channel_layout_from_mask, default_channel_layout, id3, stream_len, ParseError,
+    ParseResult, Window,
+};
+use crate::AudioInfo;
+use std::io::{Read, Seek};
+
+const HEADER_LEN: usize = 32;
+
+/// 最初のブロックを探す範囲
+const MAX_SYNC_SEARCH: u64 = 1024 * 1024;
+
+/// ブロックフラグ
+const FLAG_BYTES_PER_SAMPLE: u32 = 0x3;
+const FLAG_MONO: u32 = 0x4;
+const FLAG_HYBRID: u32 = 0x8;
+const FLAG_FLOAT: u32 = 0x80;
+const FLAG_INITIAL_BLOCK: u32 = 0x800;
+const FLAG_FINAL_BLOCK: u32 = 0x1000;
+const FLAG_DSD: u32 = 0x8000_0000;
+const SAMPLE_RATE_SHIFT: u32 = 23;
+
+/// サンプルレートインデックス（15はメタデータで指定）
+const SAMPLE_RATES: [u32; 15] = [
+    6000, 8000, 9600, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000, 64000, 88200, 96000,
+    192000,
+];
+
+/// メタデータサブブロックID
+const ID_CHANNEL_INFO: u8 = 0x0D;
+const ID_SAMPLE_RATE: u8 = 0x27;
+const ID_LARGE: u8 = 0x80;
+const ID_ODD_SIZE: u8 = 0x40;
+
+/// WavPackブロックヘッダー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// "wvpk"と自身を除いたブロックサイズ
+    pub block_size: u32,
+    pub version: u16,
+    /// 全サンプル数（不明な場合は`None`）
+    pub total_samples: Option<u64>,
+    pub block_samples: u32,
+    pub flags: u32,
+}
+
+impl BlockHeader {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_LEN || &bytes[0..4] != b"wvpk" {
+            return None;
+        }
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+        if !(0x402..=0x410).contains(&version) {
+            return None;
+        }
+        let total_samples = match u32_at(12) {
+            u32::MAX => None,
+            low => Some(((bytes[11] as u64) << 32) | low as u64),
+        };
+        Some(Self {
+            block_size: u32_at(4),
+            version,
+            total_samples,
+            block_samples: u32_at(20),
+            flags: u32_at(24),
+        })
+    }
+
+    pub fn total_len(&self) -> u64 {
+        self.block_size as u64 + 8
+    }
+
+    pub fn is_initial(&self) -> bool {
+        self.flags & FLAG_INITIAL_BLOCK != 0
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.flags & FLAG_FINAL_BLOCK != 0
+    }
+
+    pub fn channels(&self) -> u16 {
+        if self.flags & FLAG_MONO != 0 {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        ((self.flags & FLAG_BYTES_PER_SAMPLE) as u16 + 1) * 8
+    }
+
+    /// ヘッダーのサンプルレート（15はメタデータで指定）
+    pub fn sample_rate(&self) -> Option<u32> {
+        SAMPLE_RATES
+            .get(((self.flags >> SAMPLE_RATE_SHIFT) & 0xF) as usize)
+            .copied()
+    }
+}
+
+/// メタデータサブブロックを走査する（ID, データ）
+fn sub_blocks(data: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let id = *data.get(pos)?;
+        let (size, header_len) = if id & ID_LARGE != 0 {
+            let bytes = data.get(pos + 1..pos + 4)?;
+            (
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]) as usize * 2,
+                4,
+            )
+        } else {
+            (*data.get(pos + 1)? as usize * 2, 2)
+        };
+        let start = pos + header_len;
+        let body = data.get(start..start + size)?;
+        pos = start + size;
+        let len = if id & ID_ODD_SIZE != 0 {
+            size.saturating_sub(1)
+        } else {
+            size
+        };
+        Some((id & !(ID_LARGE | ID_ODD_SIZE), &body[..len]))
+    })
+}
+
+/// WavPackストリーム全体の解析結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavPackStream {
+    pub first_block: BlockHeader,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub channel_mask: Option<u32>,
+    pub total_samples: u64,
+}
+
+impl WavPackStream {
+    pub fn duration_seconds(&self) -> f64 {
+        if self.sample_rate == 0 {
+            0.0
+        } else {
+            self.total_samples as f64 / self.sample_rate as f64
+        }
+    }
+
+    pub fn is_lossless(&self) -> bool {
+        self.first_block.flags & FLAG_HYBRID == 0
+    }
+}
+
+/// 最初のブロック列からストリーム情報を読み、必要ならブロックを数えて全サンプル数を求める
+pub fn read<R: Read + Seek>(reader: &mut R) -> ParseResult<WavPackStream> {
+    let file_len = stream_len(reader)?;
+    let start = id3::skip_v2(reader, file_len)?;
+    let mut window = Window::new(reader);
+
+    let limit = file_len.min(start + MAX_SYNC_SEARCH);
+    let mut offset = start;
+    let first_block = loop {
+        if offset + HEADER_LEN as u64 > limit {
+            return Err(ParseError::Invalid("no WavPack block found".to_string()));
+        }
+        if let Some(block) = window.get(offset, HEADER_LEN)?.and_then(BlockHeader::parse) {
+            break block;
+        }
+        offset += 1;
+    };
+    if first_block.flags & FLAG_DSD != 0 {
+        return Err(ParseError::Invalid(
+            "WavPack DSD streams are not supported".to_string(),
+        ));
+    }
+
+    let mut sample_rate = first_block.sample_rate();
+    let mut block_channels = 0;
+    let mut channel_info = None;
+    let mut counted_samples = 0;
+    let mut in_first_sequence = true;
+
+    let mut block_offset = offset;
+    while let Some(block) = window
+        .get(block_offset, HEADER_LEN)?
+        .and_then(BlockHeader::parse)
+    {
+        if in_first_sequence {
+            block_channels += block.channels();
+            let body_len = block.total_len().saturating_sub(HEADER_LEN as u64) as usize;
+            if let Some(body) = window.get(block_offset + HEADER_LEN as u64, body_len)? {
+                for (id, data) in sub_blocks(body) {
+                    match id {
+                        ID_CHANNEL_INFO if !data.is_empty() => {
+                            let mut mask = [0u8; 4];
+                            let len = (data.len() - 1).min(4);
+                            mask[..len].copy_from_slice(&data[1..1 + len]);
+                            channel_info = Some((data[0] as u16, u32::from_le_bytes(mask)));
+                        }
+                        ID_SAMPLE_RATE if data.len() >= 3 => {
+                            sample_rate = Some(u32::from_le_bytes([data[0], data[1], data[2], 0]));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            in_first_sequence = !block.is_final();
+            // ヘッダーに全サンプル数がある場合は最初のブロック列だけ読めば十分
+            if !in_first_sequence && first_block.total_samples.is_some() {
+                break;
+            }
+        }
+        // 全サンプル数が不明な場合は各ブロック列の先頭ブロックのサンプル数を合計する
+        if block.is_initial() {
+            counted_samples += block.block_samples as u64;
+        }
+        block_offset += block.total_len();
+    }
+
+    let sample_rate = sample_rate
+        .ok_or_else(|| ParseError::Invalid("WavPack sample rate not specified".to_string()))?;
+
+    Ok(WavPackStream {
+        first_block,
+        sample_rate,
+        channels: channel_info.map_or(block_channels, |(channels, _)| channels),
+        channel_mask: channel_info.map(|(_, mask)| mask),
+        total_samples: first_block.total_samples.unwrap_or(counted_samples),
+    })
+}
+
+/// WavPackファイルを解析して`AudioInfo`に反映する
+pub fn probe<R: Read + Seek>(reader: &mut R, audio_info: &mut AudioInfo) -> ParseResult<()> {
+    let stream = read(reader)?;
+    let block = &stream.first_block;
+
+    audio_info.format_name = "wv".to_string();
+    audio_info.format_long_name = "WavPack".to_string();
+    audio_info.codec_name = "wavpack".to_string();
+    audio_info.codec_long_name = "WavPack".to_string();
+    audio_info.sample_rate = stream.sample_rate as i32;
+    audio_info.channels = stream.channels as i32;
+    audio_info.bits_per_sample = block.bits_per_sample() as i32;
+    audio_info.channel_layout = match stream.channel_mask {
+        Some(mask) if mask != 0 => channel_layout_from_mask(mask, stream.channels),
+        _ => default_channel_layout(stream.channels),
+    };
+    audio_info.duration_seconds = stream.duration_seconds();
+    if audio_info.duration_seconds > 0.0 {
+        audio_info.bit_rate =
+            (audio_info.file_size as f64 * 8.0 / audio_info.duration_seconds).round() as i64;
+    }
+
+    let params = &mut audio_info.codec_params;
+    params.insert("version".to_string(), format!("0x{:03x}", block.version));
+    params.insert(
+        "mode".to_string(),
+        if stream.is_lossless() {
+            "lossless"
+        } else {
+            "hybrid"
+        }
+        .to_string(),
+    );
+    if block.flags & FLAG_FLOAT != 0 {
+        params.insert("float".to_string(), "1".to_string());
+    }
+
+    audio_info.metadata = apetag::read_metadata(reader)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn block(flags: u32, total_samples: u32, block_samples: u32, body: &[u8]) -> Vec<u8> {
+        let mut out = b"wvpk".to_vec();
+        out.extend_from_slice(&(body.len() as u32 + 24).to_le_bytes());
+        out.extend_from_slice(&0x410u16.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&total_samples.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&block_samples.to_le_bytes());
+        out.extend_from_slice(&flags.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    #[test]
+    fn test_wavpack_multichannel_blocks() {
+        // 5.1ch = ステレオ + ステレオ(C/LFE) + ステレオ（各ブロック列は3ブロック）
+        let rate = 9 << SAMPLE_RATE_SHIFT; // 44100
+        let channel_info = [ID_CHANNEL_INFO, 1, 6, 0x3F];
+        let mut file = Vec::new();
+        for _ in 0..10 {
+            file.extend(block(
+                1 | rate | FLAG_INITIAL_BLOCK,
+                u32::MAX,
+                22050,
+                &channel_info,
+            ));
+            file.extend(block(1 | rate, u32::MAX, 22050, &[]));
+            file.extend(block(1 | rate | FLAG_FINAL_BLOCK, u32::MAX, 22050, &[]));
+        }
+        file.extend(apetag::build_tag(&[("Title", b"Surround", 0)], false));
+
+        let mut info = AudioInfo::new("surround.wv".into());
+        probe(&mut Cursor::new(file), &mut info).unwrap();
+        assert_eq!(info.codec_name, "wavpack");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.channel_layout, "5.1");
+        assert_eq!(info.bits_per_sample, 16);
+        assert_eq!(info.duration_seconds, 5.0);
+        assert_eq!(info.codec_params["mode"], "lossless");
+        assert_eq!(info.metadata["title"], "Surround");
+    }
+
+    #[test]
+    fn test_wavpack_total_samples_and_custom_rate() {
+        let rate = 15 << SAMPLE_RATE_SHIFT;
+        let sample_rate = [ID_SAMPLE_RATE | ID_ODD_SIZE, 2, 0x80, 0x38, 0x01, 0];
+        let flags = FLAG_INITIAL_BLOCK | FLAG_FINAL_BLOCK | FLAG_MONO | FLAG_HYBRID | rate | 2;
+        let file = block(flags, 80_000, 80_000, &sample_rate);
+
+        let stream = read(&mut Cursor::new(file)).unwrap();
+        assert_eq!(stream.sample_rate, 80_000);
+        assert_eq!(stream.channels, 1);
+        assert_eq!(stream.first_block.bits_per_sample(), 24);
+        assert_eq!(stream.duration_seconds(), 1.0);
+        assert!(!stream.is_lossless());
+    }
+}
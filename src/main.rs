@@ -3,30 +3,163 @@ use clap::Parser;
 use futures::stream::{self, StreamExt};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::process::Command;
 use tokio::sync::Semaphore;
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
+mod backend;
+mod config;
+mod ffprobe;
+mod formats;
+mod process;
+
+use backend::{BackendAttempt, BackendChain, BackendKind, ProbeRequest};
+use config::Config;
+use ffprobe::FFprobeVersion;
+use process::ResourceLimits;
+
+/// ffprobeの標準エラー出力をエラーに含める上限（バイト）
+const STDERR_EXCERPT_LEN: usize = 2000;
+
+/// 解析の失敗（すべてのバリアントが対象ファイルのパスを持つ）
+///
+/// JSON出力では`kind`・`path`・`message`・`backend`などを持つオブジェクトになる
 #[derive(Debug, Error)]
 pub enum AudioProbeError {
     #[error("File not found: {path}")]
     FileNotFound { path: PathBuf },
+    /// ネイティブパーサーが解析できなかった
     #[error("Invalid audio file: {path} - {reason}")]
     InvalidAudioFile { path: PathBuf, reason: String },
-    #[error("FFprobe not found. Please install FFmpeg.")]
-    FFprobeNotFound,
-    #[error("FFprobe execution error: {0}")]
-    FFprobeError(String),
-    #[error("I/O error: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("Processing error: {0}")]
-    Processing(String),
+    #[error("FFprobe execution error: {path} - {message}")]
+    FFprobeError {
+        path: PathBuf,
+        message: String,
+        /// 終了コード（起動できなかった場合やシグナルで終了した場合は`None`）
+        exit_code: Option<i32>,
+        /// 標準エラー出力の先頭部分
+        stderr: Option<String>,
+    },
+    #[error("{backend} analysis timed out after {elapsed:.1?}: {path}")]
+    Timeout {
+        path: PathBuf,
+        elapsed: Duration,
+        backend: BackendKind,
+    },
+    #[error("I/O error: {path} - {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+        backend: Option<BackendKind>,
+    },
+    #[error("Processing error: {path} - {message}")]
+    Processing {
+        path: PathBuf,
+        message: String,
+        backend: Option<BackendKind>,
+    },
+}
+
+impl AudioProbeError {
+    /// 機械処理用の安定した種類名
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AudioProbeError::FileNotFound { .. } => "file_not_found",
+            AudioProbeError::InvalidAudioFile { .. } => "invalid_audio_file",
+            AudioProbeError::FFprobeError { .. } => "ffprobe_failed",
+            AudioProbeError::Timeout { .. } => "timeout",
+            AudioProbeError::Io { .. } => "io",
+            AudioProbeError::Processing { .. } => "processing",
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            AudioProbeError::FileNotFound { path }
+            | AudioProbeError::InvalidAudioFile { path, .. }
+            | AudioProbeError::FFprobeError { path, .. }
+            | AudioProbeError::Timeout { path, .. }
+            | AudioProbeError::Io { path, .. }
+            | AudioProbeError::Processing { path, .. } => path,
+        }
+    }
+
+    /// エラーを出したバックエンド
+    pub fn backend(&self) -> Option<BackendKind> {
+        match self {
+            AudioProbeError::FileNotFound { .. } => None,
+            AudioProbeError::InvalidAudioFile { .. } => Some(BackendKind::Native),
+            AudioProbeError::FFprobeError { .. } => Some(BackendKind::Ffprobe),
+            AudioProbeError::Timeout { backend, .. } => Some(*backend),
+            AudioProbeError::Io { backend, .. } | AudioProbeError::Processing { backend, .. } => {
+                *backend
+            }
+        }
+    }
+
+    /// 実行に失敗したffprobeの結果からエラーを作る
+    fn ffprobe_failed(path: &Path, output: &std::process::Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        let mut end = stderr.len().min(STDERR_EXCERPT_LEN);
+        while !stderr.is_char_boundary(end) {
+            end -= 1;
+        }
+        AudioProbeError::FFprobeError {
+            path: path.to_path_buf(),
+            message: format!("ffprobe exited with {}", output.status),
+            exit_code: output.status.code(),
+            stderr: Some(stderr[..end].to_string()).filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+impl Serialize for AudioProbeError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct ErrorRecord<'a> {
+            kind: &'static str,
+            path: &'a Path,
+            message: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            backend: Option<BackendKind>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            exit_code: Option<i32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            stderr: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            elapsed_seconds: Option<f64>,
+        }
+
+        let mut record = ErrorRecord {
+            kind: self.kind(),
+            path: self.path(),
+            message: self.to_string(),
+            backend: self.backend(),
+            exit_code: None,
+            stderr: None,
+            elapsed_seconds: None,
+        };
+        match self {
+            AudioProbeError::FFprobeError {
+                exit_code, stderr, ..
+            } => {
+                record.exit_code = *exit_code;
+                record.stderr = stderr.as_deref();
+            }
+            AudioProbeError::Timeout { elapsed, .. } => {
+                record.elapsed_seconds = Some(elapsed.as_secs_f64());
+            }
+            _ => {}
+        }
+        record.serialize(serializer)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,20 +170,223 @@ pub struct AudioInfo {
     pub bit_rate: i64,
     pub sample_rate: i32,
     pub channels: i32,
+    /// ffprobeのbits_per_sampleが0の場合はbits_per_raw_sampleで補った実効ビット深度
+    pub bits_per_sample: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits_per_raw_sample: Option<i32>,
+    /// FFmpegのサンプルフォーマット（s16・s32p・fltpなど）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_fmt: Option<String>,
+    pub channel_layout: String,
     pub codec_name: String,
     pub codec_long_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
     pub format_name: String,
     pub format_long_name: String,
+    /// 開始時刻（秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>,
+    /// time_base単位の再生時間
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ts: Option<i64>,
+    /// "1/44100"など
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nb_frames: Option<u64>,
+    /// ffprobeのフォーマット判定スコア（0〜100）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probe_score: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nb_streams: Option<u32>,
     pub has_video: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_md5: Option<String>,
+    /// コーデック固有のパラメーター（AC-3のdialnormなど）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub codec_params: BTreeMap<String, String>,
+    /// コンテナ内の全ストリーム（要約フィールドは主音声ストリームのもの）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub streams: Vec<StreamInfo>,
+    /// Broadcast Wave（bext・iXML・マーカー）の情報
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broadcast: Option<formats::bwf::BroadcastWave>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    /// 埋め込みアートワーク（カバー画像など）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub artwork: Vec<Artwork>,
     pub metadata: HashMap<String, String>,
+    /// ffprobe・ネイティブパーサーで解析できず、拡張子などから推定した値を含む
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+    /// 値の出所（"metadata.title"などのメタデータキー、または"sample_rate"などのフィールド名）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub provenance: BTreeMap<String, FieldSource>,
+    /// 値を出したバックエンド
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<BackendKind>,
+    /// 先に試して失敗したバックエンドとそのエラー
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_attempts: Vec<BackendAttempt>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
     pub processing_time_ms: u64,
 }
 
+/// 値の出所
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldSource {
+    /// ファイル内のタグ・ヘッダーから読んだ値
+    Tag,
+    /// ファイル名・拡張子から補った値
+    Filename,
+    /// 固定の既定値
+    Default,
+    /// ファイルサイズなどからの推定値
+    Estimated,
+}
+
+impl FieldSource {
+    fn label(self) -> &'static str {
+        match self {
+            FieldSource::Tag => "tag",
+            FieldSource::Filename => "filename",
+            FieldSource::Default => "default",
+            FieldSource::Estimated => "estimated",
+        }
+    }
+}
+
+/// ストリームの用途を示すフラグ（ffprobeのdisposition）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamDisposition {
+    pub default: bool,
+    pub forced: bool,
+    pub commentary: bool,
+    /// カバー画像などの添付画像（動画ストリームとして扱わない）
+    pub attached_pic: bool,
+}
+
+/// コンテナ内の1ストリーム
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamInfo {
+    pub index: u32,
+    /// コンテナ内のストリームID（Oggのシリアル番号など、ffprobeと同じ"0x1f"形式）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// "audio"・"video"・"subtitle"など
+    pub codec_type: String,
+    pub codec_name: String,
+    pub codec_long_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub sample_rate: i32,
+    pub channels: i32,
+    pub channel_layout: String,
+    pub bits_per_sample: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits_per_raw_sample: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_fmt: Option<String>,
+    pub bit_rate: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ts: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nb_frames: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub disposition: StreamDisposition,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tags: HashMap<String, String>,
+}
+
+impl StreamInfo {
+    /// 単一ストリームのフォーマット向けに要約フィールドからストリームを作る
+    pub fn from_summary(audio_info: &AudioInfo) -> Self {
+        Self {
+            index: 0,
+            codec_type: "audio".to_string(),
+            codec_name: audio_info.codec_name.clone(),
+            codec_long_name: audio_info.codec_long_name.clone(),
+            profile: audio_info.profile.clone(),
+            sample_rate: audio_info.sample_rate,
+            channels: audio_info.channels,
+            channel_layout: audio_info.channel_layout.clone(),
+            bits_per_sample: audio_info.bits_per_sample,
+            bits_per_raw_sample: audio_info.bits_per_raw_sample,
+            sample_fmt: audio_info.sample_fmt.clone(),
+            bit_rate: audio_info.bit_rate,
+            start_time: audio_info.start_time,
+            duration_ts: audio_info.duration_ts,
+            time_base: audio_info.time_base.clone(),
+            nb_frames: audio_info.nb_frames,
+            disposition: StreamDisposition {
+                default: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.codec_type == "audio"
+    }
+}
+
+/// チャプター（ffprobeの-show_chapters相当）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub id: i64,
+    /// 開始時刻（秒）
+    pub start_time: f64,
+    /// 終了時刻（秒）。記録がない場合は次のチャプターの開始時刻か再生時間で補う
+    pub end_time: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tags: HashMap<String, String>,
+}
+
+/// 埋め込みアートワーク
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Artwork {
+    /// "Cover (front)"などのピクチャータイプ（ffprobeのcommentタグと同じ表記）
+    pub picture_type: String,
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// 画像データのバイト数
+    pub size: u64,
+    /// "id3_apic"・"flac_picture"・"mp4_covr"・"vorbis_picture"・"ffprobe"
+    pub source: String,
+    /// --extract-artで書き出したファイル
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extracted_path: Option<PathBuf>,
+    /// 画像データ（書き出し後は破棄する）
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
 // FFprobeのJSON出力構造
 #[derive(Debug, Deserialize)]
 struct FFProbeOutput {
     format: Option<FFProbeFormat>,
     streams: Vec<FFProbeStream>,
+    #[serde(default)]
+    chapters: Vec<FFProbeChapter>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,20 +396,163 @@ struct FFProbeFormat {
     format_name: String,
     format_long_name: String,
     duration: Option<String>,
+    start_time: Option<String>,
     #[allow(dead_code)]
     size: Option<String>,
     bit_rate: Option<String>,
+    nb_streams: Option<u32>,
+    probe_score: Option<i32>,
     tags: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize)]
 struct FFProbeStream {
+    #[serde(default)]
+    index: u32,
+    id: Option<String>,
     codec_name: Option<String>,
     codec_long_name: Option<String>,
     codec_type: String,
+    profile: Option<String>,
     sample_rate: Option<String>,
     channels: Option<i32>,
+    channel_layout: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    bits_per_sample: Option<i32>,
+    bits_per_raw_sample: Option<String>,
+    sample_fmt: Option<String>,
     bit_rate: Option<String>,
+    start_time: Option<String>,
+    duration_ts: Option<i64>,
+    time_base: Option<String>,
+    nb_frames: Option<String>,
+    #[serde(default)]
+    disposition: HashMap<String, i32>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+impl FFProbeStream {
+    fn flag(&self, name: &str) -> bool {
+        self.disposition.get(name).is_some_and(|v| *v != 0)
+    }
+
+    /// attached_picストリーム（MP3のAPICやMP4のcovrなど）をアートワークとして返す
+    ///
+    /// ffprobeは画像データを出力しないため、sizeは0のまま
+    fn attached_picture(&self) -> Option<Artwork> {
+        if !self.flag("attached_pic") {
+            return None;
+        }
+        let tag = |name: &str| {
+            self.tags
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        };
+        let mime_type = match self.codec_name.as_deref() {
+            Some("mjpeg") => "image/jpeg",
+            Some("png") => "image/png",
+            Some("bmp") => "image/bmp",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            _ => "application/octet-stream",
+        };
+        Some(Artwork {
+            picture_type: tag("comment").unwrap_or_else(|| "Other".to_string()),
+            mime_type: mime_type.to_string(),
+            description: tag("title"),
+            width: self.width,
+            height: self.height,
+            source: "ffprobe".to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn into_stream_info(self) -> StreamInfo {
+        let disposition = StreamDisposition {
+            default: self.flag("default"),
+            forced: self.flag("forced"),
+            commentary: self.flag("comment"),
+            attached_pic: self.flag("attached_pic"),
+        };
+        // PCMなどはbits_per_sample、FLACなどはbits_per_raw_sampleにビット深度が入る
+        let bits_per_raw_sample = self
+            .bits_per_raw_sample
+            .as_deref()
+            .and_then(|bits| bits.parse().ok())
+            .filter(|bits| *bits > 0);
+        let bits_per_sample = self
+            .bits_per_sample
+            .filter(|bits| *bits > 0)
+            .or(bits_per_raw_sample)
+            .unwrap_or(0);
+
+        let mut tags: HashMap<String, String> = self
+            .tags
+            .into_iter()
+            .map(|(key, value)| (key.to_lowercase(), value))
+            .collect();
+        let language = tags.remove("language").filter(|lang| lang != "und");
+        let title = tags.remove("title");
+
+        StreamInfo {
+            index: self.index,
+            id: self.id,
+            codec_type: self.codec_type,
+            codec_name: self.codec_name.unwrap_or_default(),
+            codec_long_name: self.codec_long_name.unwrap_or_default(),
+            profile: self.profile,
+            sample_rate: self
+                .sample_rate
+                .and_then(|rate| rate.parse().ok())
+                .unwrap_or(0),
+            channels: self.channels.unwrap_or(0),
+            channel_layout: self.channel_layout.unwrap_or_default(),
+            bits_per_sample,
+            bits_per_raw_sample,
+            sample_fmt: self.sample_fmt,
+            bit_rate: self
+                .bit_rate
+                .and_then(|rate| rate.parse().ok())
+                .unwrap_or(0),
+            start_time: self.start_time.and_then(|time| time.parse().ok()),
+            duration_ts: self.duration_ts,
+            time_base: self.time_base,
+            nb_frames: self.nb_frames.and_then(|frames| frames.parse().ok()),
+            language,
+            title,
+            disposition,
+            tags,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FFProbeChapter {
+    id: i64,
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+impl FFProbeChapter {
+    fn into_chapter(self) -> Chapter {
+        let mut tags: HashMap<String, String> = self
+            .tags
+            .into_iter()
+            .map(|(key, value)| (key.to_lowercase(), value))
+            .collect();
+        Chapter {
+            id: self.id,
+            start_time: self.start_time.parse().unwrap_or(0.0),
+            end_time: self.end_time.parse().unwrap_or(0.0),
+            title: tags.remove("title"),
+            tags,
+        }
+    }
 }
 
 impl AudioInfo {
@@ -85,12 +564,34 @@ impl AudioInfo {
             bit_rate: 0,
             sample_rate: 0,
             channels: 0,
+            bits_per_sample: 0,
+            bits_per_raw_sample: None,
+            sample_fmt: None,
+            channel_layout: String::new(),
             codec_name: String::new(),
             codec_long_name: String::new(),
+            profile: None,
             format_name: String::new(),
             format_long_name: String::new(),
+            start_time: None,
+            duration_ts: None,
+            time_base: None,
+            nb_frames: None,
+            probe_score: None,
+            nb_streams: None,
             has_video: false,
+            audio_md5: None,
+            codec_params: BTreeMap::new(),
+            streams: Vec::new(),
+            broadcast: None,
+            chapters: Vec::new(),
+            artwork: Vec::new(),
             metadata: HashMap::new(),
+            estimated: false,
+            provenance: BTreeMap::new(),
+            backend: None,
+            failed_attempts: Vec::new(),
+            warnings: Vec::new(),
             processing_time_ms: 0,
         }
     }
@@ -99,28 +600,105 @@ impl AudioInfo {
 pub struct AudioProbe {
     semaphore: Arc<Semaphore>,
     max_concurrent: usize,
-    use_ffprobe: bool,
+    ffprobe: Option<FFprobeVersion>,
+    backends: BackendChain,
+    include_by_content: bool,
+    art_dir: Option<PathBuf>,
+    placeholder_metadata: bool,
+    timeout: Option<Duration>,
+    resource_limits: ResourceLimits,
 }
 
 impl AudioProbe {
+    /// 拡張子で音声ファイルとみなす一覧
+    const AUDIO_EXTENSIONS: [&'static str; 30] = [
+        "mp3", "wav", "rf64", "bw64", "flac", "aac", "ogg", "m4a", "wma", "opus", "mp2", "ac3",
+        "eac3", "ec3", "dts", "ape", "wv", "aiff", "aif", "aifc", "au", "snd", "ra", "amr", "webm",
+        "mkv", "m4b", "m4p", "dsf", "dff",
+    ];
+
+    /// 1ファイルあたりのデフォルトのタイムアウト
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
     pub async fn new(max_concurrent: usize) -> Result<Self> {
-        // ffprobeが利用可能かチェック
-        let use_ffprobe = Self::check_ffprobe().await;
+        Self::new_with_ffprobe(max_concurrent, Path::new("ffprobe")).await
+    }
 
-        Ok(Self {
+    /// PATH上の"ffprobe"ではなく指定したffprobeを使う
+    pub async fn new_with_ffprobe(max_concurrent: usize, ffprobe_path: &Path) -> Result<Self> {
+        // ffprobeが利用可能かチェックし、バージョンを記録
+        let ffprobe = ffprobe::detect(ffprobe_path).await;
+
+        let probe = Self {
             semaphore: Arc::new(Semaphore::new(max_concurrent)),
             max_concurrent,
-            use_ffprobe,
-        })
+            ffprobe,
+            backends: BackendChain::default(),
+            include_by_content: false,
+            art_dir: None,
+            placeholder_metadata: false,
+            timeout: Some(Self::DEFAULT_TIMEOUT),
+            resource_limits: ResourceLimits::default(),
+        };
+        Ok(probe.with_backends(&BackendKind::DEFAULT_CHAIN))
     }
 
-    async fn check_ffprobe() -> bool {
-        Command::new("ffprobe")
-            .arg("-version")
-            .output()
-            .await
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+    /// 解析バックエンドを試す順序（ffprobeが利用できない場合は除く）
+    pub fn with_backends(mut self, kinds: &[BackendKind]) -> Self {
+        self.backends = BackendChain::new(kinds, self.ffprobe.as_ref());
+        self
+    }
+
+    /// 実際に使うバックエンドの順序
+    pub fn backend_kinds(&self) -> Vec<BackendKind> {
+        self.backends.kinds()
+    }
+
+    /// 拡張子が一覧にないファイルも内容（マジックバイト）が音声なら対象にする
+    pub fn with_content_detection(mut self, enabled: bool) -> Self {
+        self.include_by_content = enabled;
+        self
+    }
+
+    /// タグのないtitle・artist・albumをファイル名や"Unknown Artist"などで補う
+    ///
+    /// 補った値と元からある値の区別は`provenance`に記録する
+    pub fn with_placeholder_metadata(mut self, enabled: bool) -> Self {
+        self.placeholder_metadata = enabled;
+        self
+    }
+
+    /// 埋め込みアートワークを指定ディレクトリに書き出す
+    pub fn with_art_extraction(mut self, dir: Option<PathBuf>) -> Self {
+        self.art_dir = dir;
+        self
+    }
+
+    /// ネイティブパーサー・ffprobeの1ファイルあたりのタイムアウト（`None`で無制限）
+    ///
+    /// 超過した場合は`AudioProbeError::Timeout`とする。ffprobeの子プロセスはプロセスグループごと
+    /// 強制終了するが、ネイティブパーサーのブロッキングスレッドは中断できず、結果を待たずに進む
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// ffprobe子プロセスのCPU時間・アドレス空間の上限（Unixのみ）
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
+
+    /// 拡張子、または内容判定が有効な場合はマジックバイトで音声ファイルかを判定する
+    pub fn is_audio_candidate(&self, path: &Path) -> bool {
+        let by_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| Self::AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false);
+        by_extension
+            || (self.include_by_content
+                && formats::sniff::sniff_path(path).ok().flatten().is_some())
     }
 
     pub async fn analyze_file(&self, path: PathBuf) -> Result<AudioInfo, AudioProbeError> {
@@ -133,49 +711,41 @@ impl AudioProbe {
             return Err(AudioProbeError::FileNotFound { path });
         }
 
-        let mut audio_info = AudioInfo::new(path.clone());
-
         // ファイルサイズ取得
-        if let Ok(metadata) = std::fs::metadata(&path) {
-            audio_info.file_size = metadata.len();
-        }
+        let file_size = std::fs::metadata(&path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        // 拡張子ではなく先頭バイトから実際のコンテナを判定
+        let container = formats::sniff::sniff_path(&path).unwrap_or_else(|e| {
+            debug!("Content sniffing failed for {:?}: {}", path, e);
+            None
+        });
 
-        if self.use_ffprobe {
-            // FFprobeを使用して実際の解析
-            match self.analyze_with_ffprobe(&path).await {
-                Ok(info) => {
-                    audio_info = info;
-                }
-                Err(e) => {
-                    warn!("FFprobe analysis failed for {:?}: {}", path, e);
-                    // フォールバック：基本的な推定
-                    self.fallback_analysis(&mut audio_info, &path);
-                }
-            }
-        } else {
-            // FFprobeが利用できない場合の推定
-            self.fallback_analysis(&mut audio_info, &path);
+        // 設定した順にバックエンドを試し、最初に成功した結果を使う
+        let request = ProbeRequest {
+            path: &path,
+            container,
+            file_size,
+            timeout: self.timeout,
+            resource_limits: self.resource_limits,
+        };
+        let mut audio_info = self.backends.probe(&request).await?;
+
+        if let Some(warning) = formats::sniff::extension_mismatch(&path, container) {
+            audio_info.warnings.push(warning);
         }
 
-        // デフォルトメタデータの設定
-        if audio_info.metadata.get("title").is_none() {
-            if let Some(file_stem) = path.file_stem() {
-                if let Some(name) = file_stem.to_str() {
-                    audio_info
-                        .metadata
-                        .insert("title".to_string(), name.to_string());
-                }
-            }
+        if let Some(dir) = &self.art_dir {
+            formats::artwork::extract(&mut audio_info, dir);
         }
-        if audio_info.metadata.get("artist").is_none() {
-            audio_info
-                .metadata
-                .insert("artist".to_string(), "Unknown Artist".to_string());
+        // 画像データは出力しないため、結果を保持する間のメモリを解放する
+        for artwork in &mut audio_info.artwork {
+            artwork.data = Vec::new();
         }
-        if audio_info.metadata.get("album").is_none() {
-            audio_info
-                .metadata
-                .insert("album".to_string(), "Unknown Album".to_string());
+
+        if self.placeholder_metadata {
+            Self::fill_placeholder_metadata(&mut audio_info, &path);
         }
 
         audio_info.processing_time_ms = start_time.elapsed().as_millis() as u64;
@@ -183,150 +753,39 @@ impl AudioProbe {
         Ok(audio_info)
     }
 
-    async fn analyze_with_ffprobe(&self, path: &Path) -> Result<AudioInfo, AudioProbeError> {
-        let output = Command::new("ffprobe")
-            .args(&[
-                "-v",
-                "quiet",
-                "-print_format",
-                "json",
-                "-show_format",
-                "-show_streams",
-            ])
-            .arg(path)
-            .output()
-            .await
-            .map_err(|e| {
-                AudioProbeError::FFprobeError(format!("Failed to execute ffprobe: {}", e))
-            })?;
-
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(AudioProbeError::FFprobeError(format!(
-                "FFprobe failed: {}",
-                error_msg
-            )));
-        }
-
-        let json_str = String::from_utf8_lossy(&output.stdout);
-        let probe_data: FFProbeOutput = serde_json::from_str(&json_str).map_err(|e| {
-            AudioProbeError::Processing(format!("Failed to parse ffprobe output: {}", e))
-        })?;
-
-        let mut audio_info = AudioInfo::new(path.to_path_buf());
-
-        // ファイルサイズ取得
-        if let Ok(metadata) = std::fs::metadata(path) {
-            audio_info.file_size = metadata.len();
-        }
-
-        // フォーマット情報
-        if let Some(format) = probe_data.format {
-            audio_info.format_name = format.format_name;
-            audio_info.format_long_name = format.format_long_name;
-
-            if let Some(duration_str) = format.duration {
-                audio_info.duration_seconds = duration_str.parse::<f64>().unwrap_or(0.0);
-            }
-
-            if let Some(bit_rate_str) = format.bit_rate {
-                audio_info.bit_rate = bit_rate_str.parse::<i64>().unwrap_or(0);
-            }
-
-            // メタデータ
-            if let Some(tags) = format.tags {
-                for (key, value) in tags {
-                    audio_info.metadata.insert(key.to_lowercase(), value);
-                }
-            }
-        }
-
-        // ストリーム情報
-        let mut audio_stream = None;
-        for stream in probe_data.streams {
-            if stream.codec_type == "audio" && audio_stream.is_none() {
-                audio_stream = Some(stream);
-            } else if stream.codec_type == "video" {
-                audio_info.has_video = true;
-            }
-        }
-
-        if let Some(stream) = audio_stream {
-            if let Some(codec_name) = stream.codec_name {
-                audio_info.codec_name = codec_name;
-            }
-            if let Some(codec_long_name) = stream.codec_long_name {
-                audio_info.codec_long_name = codec_long_name;
-            }
-            if let Some(sample_rate_str) = stream.sample_rate {
-                audio_info.sample_rate = sample_rate_str.parse::<i32>().unwrap_or(0);
-            }
-            if let Some(channels) = stream.channels {
-                audio_info.channels = channels;
-            }
-
-            // ストリームのビットレートがある場合、フォーマットのビットレートよりも優先
-            if let Some(bit_rate_str) = stream.bit_rate {
-                if let Ok(stream_bit_rate) = bit_rate_str.parse::<i64>() {
-                    if stream_bit_rate > 0 && audio_info.bit_rate == 0 {
-                        audio_info.bit_rate = stream_bit_rate;
-                    }
-                }
-            }
+    /// デフォルトメタデータの設定（各キーの出所をprovenanceに記録する）
+    fn fill_placeholder_metadata(audio_info: &mut AudioInfo, path: &Path) {
+        for key in audio_info.metadata.keys() {
+            audio_info
+                .provenance
+                .insert(format!("metadata.{}", key), FieldSource::Tag);
         }
-
-        Ok(audio_info)
-    }
-
-    fn fallback_analysis(&self, audio_info: &mut AudioInfo, path: &Path) {
-        // 基本的な情報を設定（実際のFFmpeg解析の代わり）
-        if let Some(extension) = path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                audio_info.format_name = ext_str.to_lowercase();
-                audio_info.codec_name = ext_str.to_lowercase();
-
-                // 拡張子に基づく基本情報の推定
-                match ext_str.to_lowercase().as_str() {
-                    "mp3" => {
-                        audio_info.codec_long_name = "MP3 (MPEG audio layer 3)".to_string();
-                        audio_info.format_long_name = "MP2/3 (MPEG audio layer 2/3)".to_string();
-                        audio_info.sample_rate = 44100;
-                        audio_info.channels = 2;
-                        audio_info.bit_rate = 320000;
-                    }
-                    "wav" => {
-                        audio_info.codec_name = "pcm_s16le".to_string();
-                        audio_info.codec_long_name = "PCM signed 16-bit little-endian".to_string();
-                        audio_info.format_long_name = "WAV / WAVE (Waveform Audio)".to_string();
-                        audio_info.sample_rate = 44100;
-                        audio_info.channels = 2;
-                        audio_info.bit_rate = 44100 * 2 * 16; // 1411200
-                    }
-                    "flac" => {
-                        audio_info.codec_long_name = "FLAC (Free Lossless Audio Codec)".to_string();
-                        audio_info.format_long_name = "raw FLAC".to_string();
-                        audio_info.sample_rate = 44100;
-                        audio_info.channels = 2;
-                    }
-                    _ => {
-                        audio_info.codec_long_name = format!("{} audio", ext_str.to_uppercase());
-                        audio_info.format_long_name = format!("{} format", ext_str.to_uppercase());
-                        audio_info.sample_rate = 44100;
-                        audio_info.channels = 2;
-                        audio_info.bit_rate = 320000;
-                    }
-                }
+        let title = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|name| (name.to_string(), FieldSource::Filename));
+        let placeholders = [
+            ("title", title),
+            (
+                "artist",
+                Some(("Unknown Artist".to_string(), FieldSource::Default)),
+            ),
+            (
+                "album",
+                Some(("Unknown Album".to_string(), FieldSource::Default)),
+            ),
+        ];
+        for (key, placeholder) in placeholders {
+            let Some((value, source)) = placeholder else {
+                continue;
+            };
+            if !audio_info.metadata.contains_key(key) {
+                audio_info.metadata.insert(key.to_string(), value);
+                audio_info
+                    .provenance
+                    .insert(format!("metadata.{}", key), source);
             }
         }
-
-        // ファイルサイズに基づく継続時間の推定
-        if audio_info.bit_rate > 0 {
-            audio_info.duration_seconds =
-                (audio_info.file_size * 8) as f64 / audio_info.bit_rate as f64;
-        } else {
-            // デフォルトの継続時間（5分）
-            audio_info.duration_seconds = 300.0;
-        }
     }
 
     pub async fn process_files(
@@ -370,23 +829,12 @@ impl AudioProbe {
     }
 
     pub fn collect_audio_files<P: AsRef<Path>>(&self, root_path: P) -> Result<Vec<PathBuf>> {
-        let audio_extensions = [
-            "mp3", "wav", "flac", "aac", "ogg", "m4a", "wma", "opus", "mp2", "ac3", "dts", "ape",
-            "aiff", "au", "ra", "amr", "webm", "mkv", "m4b", "m4p",
-        ];
-
         let mut audio_files = Vec::new();
 
         for entry in WalkDir::new(root_path).follow_links(false) {
             let entry = entry?;
-            if entry.file_type().is_file() {
-                if let Some(extension) = entry.path().extension() {
-                    if let Some(ext_str) = extension.to_str() {
-                        if audio_extensions.contains(&ext_str.to_lowercase().as_str()) {
-                            audio_files.push(entry.path().to_path_buf());
-                        }
-                    }
-                }
+            if entry.file_type().is_file() && self.is_audio_candidate(entry.path()) {
+                audio_files.push(entry.path().to_path_buf());
             }
         }
 
@@ -399,7 +847,13 @@ impl Clone for AudioProbe {
         Self {
             semaphore: Arc::clone(&self.semaphore),
             max_concurrent: self.max_concurrent,
-            use_ffprobe: self.use_ffprobe,
+            ffprobe: self.ffprobe.clone(),
+            backends: self.backends.clone(),
+            include_by_content: self.include_by_content,
+            art_dir: self.art_dir.clone(),
+            placeholder_metadata: self.placeholder_metadata,
+            timeout: self.timeout,
+            resource_limits: self.resource_limits,
         }
     }
 }
@@ -434,6 +888,42 @@ struct Args {
     /// 出力ファイル（指定しない場合は標準出力）
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// 拡張子に関係なく、内容（マジックバイト）が音声のファイルも対象にする
+    #[arg(long)]
+    detect_by_content: bool,
+
+    /// タグのないtitle・artist・albumをファイル名や"Unknown Artist"などで補う
+    #[arg(long)]
+    fill_missing_tags: bool,
+
+    /// 埋め込みアートワークを指定ディレクトリに書き出す
+    #[arg(long, value_name = "DIR")]
+    extract_art: Option<PathBuf>,
+
+    /// ネイティブパーサー・ffprobeの1ファイルあたりのタイムアウト秒数（0で無制限）
+    #[arg(long, value_name = "SECS", default_value = "30")]
+    timeout: u64,
+
+    /// ffprobe子プロセスのCPU時間の上限（秒、Unixのみ）
+    #[arg(long, value_name = "SECS")]
+    ffprobe_cpu_limit: Option<u64>,
+
+    /// ffprobe子プロセスのアドレス空間の上限（MB、Unixのみ）
+    #[arg(long, value_name = "MB")]
+    ffprobe_memory_limit: Option<u64>,
+
+    /// 使用するffprobeのパス（指定しない場合は設定ファイル、なければPATH上のffprobe）
+    #[arg(long, value_name = "PATH", env = "AUDIO_PROBE_FFPROBE")]
+    ffprobe_path: Option<PathBuf>,
+
+    /// 解析バックエンドを試す順序（カンマ区切り、1つだけ指定するとそのバックエンドに固定）
+    #[arg(long, value_name = "BACKEND", value_delimiter = ',')]
+    backend: Vec<BackendKind>,
+
+    /// 設定ファイル（JSON、指定しない場合は~/.config/audio-probe/config.json）
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -460,17 +950,70 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     }
 
-    let probe = AudioProbe::new(args.max_concurrent)
+    if let Some(dir) = &args.extract_art {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create artwork directory {:?}", dir))?;
+    }
+
+    let config = Config::discover(args.config.as_deref())?;
+    let ffprobe_path = args
+        .ffprobe_path
+        .clone()
+        .or(config.ffprobe_path)
+        .unwrap_or_else(|| PathBuf::from("ffprobe"));
+    let backends = BackendKind::resolve_chain(&args.backend, config.backends.as_deref());
+
+    let probe = AudioProbe::new_with_ffprobe(args.max_concurrent, &ffprobe_path)
         .await
-        .context("Failed to initialize AudioProbe")?;
+        .context("Failed to initialize AudioProbe")?
+        .with_content_detection(args.detect_by_content)
+        .with_placeholder_metadata(args.fill_missing_tags)
+        .with_art_extraction(args.extract_art.clone())
+        .with_timeout(Some(Duration::from_secs(args.timeout)).filter(|t| !t.is_zero()))
+        .with_resource_limits(ResourceLimits {
+            cpu_seconds: args.ffprobe_cpu_limit,
+            address_space_bytes: args
+                .ffprobe_memory_limit
+                .map(|mb| mb.saturating_mul(1024 * 1024)),
+        })
+        .with_backends(&backends);
 
-    if probe.use_ffprobe {
-        println!("FFprobeを使用して実際の音声ファイル情報を解析します");
+    if let Some(ffprobe) = &probe.ffprobe {
+        println!(
+            "FFprobe {} ({}) を使用して実際の音声ファイル情報を解析します",
+            ffprobe.display_version(),
+            ffprobe.path.display()
+        );
+        for (field, (major, minor)) in ffprobe.unsupported_fields() {
+            println!(
+                "警告: このFFprobeは{}を出力しません（{}.{}以降が必要です）",
+                field, major, minor
+            );
+        }
     } else {
-        println!("警告: FFprobeが見つかりません。基本的な情報推定を行います");
+        println!(
+            "警告: FFprobe（{}）が見つかりません。基本的な情報推定を行います",
+            ffprobe_path.display()
+        );
         println!("FFmpegをインストールすることで、より正確な解析が可能になります");
     }
 
+    let backend_kinds = probe.backend_kinds();
+    if backend_kinds.is_empty() {
+        anyhow::bail!(
+            "No usable analysis backend in {:?} (ffprobe is not available)",
+            backends
+        );
+    }
+    println!(
+        "解析バックエンド: {}",
+        backend_kinds
+            .iter()
+            .map(|kind| kind.name())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    );
+
     let mut all_files = Vec::new();
 
     // パス処理
@@ -489,17 +1032,8 @@ async fn main() -> Result<()> {
                     for entry in entries.flatten() {
                         if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
                             let file_path = entry.path();
-                            if let Some(extension) = file_path.extension() {
-                                if let Some(ext_str) = extension.to_str() {
-                                    let audio_extensions = [
-                                        "mp3", "wav", "flac", "aac", "ogg", "m4a", "wma", "opus",
-                                        "mp2", "ac3", "dts", "ape", "aiff", "au", "ra", "amr",
-                                        "webm", "mkv", "m4b", "m4p",
-                                    ];
-                                    if audio_extensions.contains(&ext_str.to_lowercase().as_str()) {
-                                        all_files.push(file_path);
-                                    }
-                                }
+                            if probe.is_audio_candidate(&file_path) {
+                                all_files.push(file_path);
                             }
                         }
                     }
@@ -554,97 +1088,201 @@ async fn main() -> Result<()> {
                 "processing_time_seconds": total_time.as_secs_f64(),
                 "total_duration_seconds": total_duration,
                 "total_size_bytes": total_size,
+                "ffprobe": probe.ffprobe,
             },
             "successful_files": successful,
-            "errors": errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+            "errors": errors
         });
         serde_json::to_string_pretty(&output_data)?
     } else {
         // 標準出力フォーマット
-        let mut output = String::new();
+        format_text_report(&successful, &errors, total_time, total_duration, total_size)
+    };
+
+    // 出力先の決定
+    if let Some(output_path) = args.output {
+        std::fs::write(output_path, output_content)?;
+    } else {
+        print!("{}", output_content);
+    }
+
+    Ok(())
+}
 
-        output.push_str(&format!("=== 音声ファイル分析結果 ===\n"));
-        output.push_str(&format!("処理時間: {:.2}秒\n", total_time.as_secs_f64()));
+/// テキスト形式の分析結果
+fn format_text_report(
+    successful: &[AudioInfo],
+    errors: &[AudioProbeError],
+    total_time: Duration,
+    total_duration: f64,
+    total_size: u64,
+) -> String {
+    let mut output = String::new();
+
+    output.push_str("=== 音声ファイル分析結果 ===\n");
+    output.push_str(&format!("処理時間: {:.2}秒\n", total_time.as_secs_f64()));
+    output.push_str(&format!(
+        "成功: {}, 失敗: {}\n",
+        successful.len(),
+        errors.len()
+    ));
+    output.push_str(&format!(
+        "総継続時間: {}\n",
+        format_duration(total_duration)
+    ));
+    output.push_str(&format!("総サイズ: {}\n\n", format_bytes(total_size)));
+
+    for audio_info in successful {
+        output.push_str(&format!("📁 ファイル: {:?}\n", audio_info.file_path));
         output.push_str(&format!(
-            "成功: {}, 失敗: {}\n",
-            successful.len(),
-            errors.len()
+            "   サイズ: {}\n",
+            format_bytes(audio_info.file_size)
         ));
         output.push_str(&format!(
-            "総継続時間: {}\n",
-            format_duration(total_duration)
+            "   継続時間: {}\n",
+            format_duration(audio_info.duration_seconds)
         ));
-        output.push_str(&format!("総サイズ: {}\n\n", format_bytes(total_size)));
-
-        for audio_info in &successful {
-            output.push_str(&format!("📁 ファイル: {:?}\n", audio_info.file_path));
-            output.push_str(&format!(
-                "   サイズ: {}\n",
-                format_bytes(audio_info.file_size)
-            ));
-            output.push_str(&format!(
-                "   継続時間: {}\n",
-                format_duration(audio_info.duration_seconds)
-            ));
-            output.push_str(&format!(
-                "   ビットレート: {}\n",
-                format_bitrate(audio_info.bit_rate)
-            ));
-            output.push_str(&format!(
-                "   サンプルレート: {} Hz\n",
-                audio_info.sample_rate
-            ));
-            output.push_str(&format!("   チャンネル数: {}\n", audio_info.channels));
+        output.push_str(&format!(
+            "   ビットレート: {}\n",
+            format_bitrate(audio_info.bit_rate)
+        ));
+        output.push_str(&format!(
+            "   サンプルレート: {} Hz\n",
+            audio_info.sample_rate
+        ));
+        output.push_str(&format!("   チャンネル数: {}\n", audio_info.channels));
+        if !audio_info.channel_layout.is_empty() {
             output.push_str(&format!(
-                "   コーデック: {} ({})\n",
-                audio_info.codec_name, audio_info.codec_long_name
+                "   チャンネルレイアウト: {}\n",
+                audio_info.channel_layout
             ));
+        }
+        if audio_info.bits_per_sample > 0 {
             output.push_str(&format!(
-                "   フォーマット: {} ({})\n",
-                audio_info.format_name, audio_info.format_long_name
+                "   ビット深度: {} bit\n",
+                audio_info.bits_per_sample
             ));
+        }
+        if let Some(sample_fmt) = &audio_info.sample_fmt {
+            output.push_str(&format!("   サンプルフォーマット: {}\n", sample_fmt));
+        }
+        output.push_str(&format!(
+            "   コーデック: {} ({})\n",
+            audio_info.codec_name, audio_info.codec_long_name
+        ));
+        if let Some(profile) = &audio_info.profile {
+            output.push_str(&format!("   プロファイル: {}\n", profile));
+        }
+        output.push_str(&format!(
+            "   フォーマット: {} ({})\n",
+            audio_info.format_name, audio_info.format_long_name
+        ));
+        if let Some(score) = audio_info.probe_score {
+            output.push_str(&format!("   判定スコア: {}\n", score));
+        }
+        if let Some(start_time) = audio_info.start_time.filter(|time| *time != 0.0) {
+            output.push_str(&format!("   開始時間: {:.6}秒\n", start_time));
+        }
+        if let (Some(duration_ts), Some(time_base)) =
+            (audio_info.duration_ts, &audio_info.time_base)
+        {
             output.push_str(&format!(
-                "   動画含む: {}\n",
-                if audio_info.has_video {
-                    "はい"
-                } else {
-                    "いいえ"
-                }
+                "   タイムベース: {} (duration_ts: {})\n",
+                time_base, duration_ts
             ));
+        }
+        if let Some(nb_frames) = audio_info.nb_frames {
+            output.push_str(&format!("   フレーム数: {}\n", nb_frames));
+        }
+        output.push_str(&format!(
+            "   動画含む: {}\n",
+            if audio_info.has_video {
+                "はい"
+            } else {
+                "いいえ"
+            }
+        ));
+        if audio_info.streams.len() > 1 {
+            output.push_str("   ストリーム:\n");
+            for stream in &audio_info.streams {
+                output.push_str(&format_stream(stream));
+            }
+        }
+        if let Some(md5) = &audio_info.audio_md5 {
+            output.push_str(&format!("   音声MD5: {}\n", md5));
+        }
+        if !audio_info.codec_params.is_empty() {
+            output.push_str("   コーデック詳細:\n");
+            for (key, value) in &audio_info.codec_params {
+                output.push_str(&format!("     {}: {}\n", key, value));
+            }
+        }
+        if let Some(broadcast) = &audio_info.broadcast {
+            output.push_str(&format_broadcast(broadcast));
+        }
+        if !audio_info.artwork.is_empty() {
+            output.push_str("   アートワーク:\n");
+            for (index, artwork) in audio_info.artwork.iter().enumerate() {
+                output.push_str(&format_artwork(index, artwork));
+            }
+        }
+        if !audio_info.chapters.is_empty() {
+            output.push_str("   チャプター:\n");
+            for chapter in &audio_info.chapters {
+                output.push_str(&format_chapter(chapter));
+            }
+        }
+        if let Some(backend) = audio_info.backend {
+            output.push_str(&format!("   バックエンド: {}\n", backend));
+        }
+        for attempt in &audio_info.failed_attempts {
             output.push_str(&format!(
-                "   処理時間: {}ms\n",
-                audio_info.processing_time_ms
+                "   失敗したバックエンド: {} ({})\n",
+                attempt.backend, attempt.error
             ));
+        }
+        if audio_info.estimated {
+            let fields: Vec<String> = audio_info
+                .provenance
+                .iter()
+                .filter(|(key, _)| !key.starts_with("metadata."))
+                .map(|(key, source)| format!("{} ({})", key, source.label()))
+                .collect();
+            output.push_str(&format!("   推定値: {}\n", fields.join(", ")));
+        }
+        for warning in &audio_info.warnings {
+            output.push_str(&format!("   ⚠️ 警告: {}\n", warning));
+        }
+        output.push_str(&format!(
+            "   処理時間: {}ms\n",
+            audio_info.processing_time_ms
+        ));
 
-            if !audio_info.metadata.is_empty() {
-                output.push_str("   メタデータ:\n");
-                for (key, value) in &audio_info.metadata {
-                    if !value.is_empty() {
-                        output.push_str(&format!("     {}: {}\n", key, value));
+        if !audio_info.metadata.is_empty() {
+            output.push_str("   メタデータ:\n");
+            for (key, value) in &audio_info.metadata {
+                if value.is_empty() {
+                    continue;
+                }
+                match audio_info.provenance.get(&format!("metadata.{}", key)) {
+                    Some(source) if *source != FieldSource::Tag => {
+                        output.push_str(&format!("     {}: {} ({})\n", key, value, source.label()))
                     }
+                    _ => output.push_str(&format!("     {}: {}\n", key, value)),
                 }
             }
-            output.push_str("\n");
         }
+        output.push('\n');
+    }
 
-        if !errors.is_empty() {
-            output.push_str("=== エラー ===\n");
-            for error in &errors {
-                output.push_str(&format!("❌ {}\n", error));
-            }
+    if !errors.is_empty() {
+        output.push_str("=== エラー ===\n");
+        for error in errors {
+            output.push_str(&format!("❌ {}\n", error));
         }
-
-        output
-    };
-
-    // 出力先の決定
-    if let Some(output_path) = args.output {
-        std::fs::write(output_path, output_content)?;
-    } else {
-        print!("{}", output_content);
     }
 
-    Ok(())
+    output
 }
 
 fn format_bytes(bytes: u64) -> String {
@@ -689,6 +1327,182 @@ fn format_bitrate(bitrate: i64) -> String {
     }
 }
 
+/// ストリーム一覧のテキスト出力（1ストリーム分）
+fn format_stream(stream: &StreamInfo) -> String {
+    let mut parts = vec![format!(
+        "#{} {}: {}",
+        stream.index, stream.codec_type, stream.codec_name
+    )];
+    if let Some(profile) = &stream.profile {
+        parts.push(format!("({})", profile));
+    }
+    if stream.sample_rate > 0 {
+        parts.push(format!("{} Hz", stream.sample_rate));
+    }
+    if !stream.channel_layout.is_empty() {
+        parts.push(stream.channel_layout.clone());
+    } else if stream.channels > 0 {
+        parts.push(format!("{}ch", stream.channels));
+    }
+    if stream.bits_per_sample > 0 {
+        parts.push(format!("{} bit", stream.bits_per_sample));
+    }
+    if let Some(sample_fmt) = &stream.sample_fmt {
+        parts.push(sample_fmt.clone());
+    }
+    if stream.bit_rate > 0 {
+        parts.push(format_bitrate(stream.bit_rate));
+    }
+    if let Some(language) = &stream.language {
+        parts.push(format!("[{}]", language));
+    }
+    if let Some(title) = &stream.title {
+        parts.push(format!("\"{}\"", title));
+    }
+    let flags: Vec<&str> = [
+        ("default", stream.disposition.default),
+        ("forced", stream.disposition.forced),
+        ("commentary", stream.disposition.commentary),
+    ]
+    .into_iter()
+    .filter_map(|(name, set)| set.then_some(name))
+    .collect();
+    if !flags.is_empty() {
+        parts.push(format!("({})", flags.join(", ")));
+    }
+
+    let mut output = format!("     {}\n", parts.join(" "));
+    let mut tags: Vec<_> = stream.tags.iter().collect();
+    tags.sort();
+    for (key, value) in tags {
+        output.push_str(&format!("       {}: {}\n", key, value));
+    }
+    output
+}
+
+/// アートワーク一覧のテキスト出力（1画像分）
+fn format_artwork(index: usize, artwork: &Artwork) -> String {
+    let mut parts = vec![
+        format!("#{}", index),
+        artwork.picture_type.clone(),
+        artwork.mime_type.clone(),
+    ];
+    if let (Some(width), Some(height)) = (artwork.width, artwork.height) {
+        parts.push(format!("{}x{}", width, height));
+    }
+    if artwork.size > 0 {
+        parts.push(format_bytes(artwork.size));
+    }
+    parts.push(format!("[{}]", artwork.source));
+    if let Some(description) = &artwork.description {
+        parts.push(format!("\"{}\"", description));
+    }
+    if let Some(path) = &artwork.extracted_path {
+        parts.push(format!("-> {}", path.display()));
+    }
+    format!("     {}\n", parts.join(" "))
+}
+
+/// チャプター位置の表示（HH:MM:SS.mmm）
+fn format_timestamp(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// チャプター一覧のテキスト出力（1チャプター分）
+fn format_chapter(chapter: &Chapter) -> String {
+    let mut output = format!(
+        "     #{} {} - {}",
+        chapter.id,
+        format_timestamp(chapter.start_time),
+        format_timestamp(chapter.end_time)
+    );
+    if let Some(title) = &chapter.title {
+        output.push_str(&format!(" {}", title));
+    }
+    output.push('\n');
+    let mut tags: Vec<_> = chapter.tags.iter().collect();
+    tags.sort();
+    for (key, value) in tags {
+        output.push_str(&format!("       {}: {}\n", key, value));
+    }
+    output
+}
+
+/// Broadcast Wave情報のテキスト出力
+fn format_broadcast(broadcast: &formats::bwf::BroadcastWave) -> String {
+    let mut output = String::from("   Broadcast Wave:\n");
+    let mut line = |label: &str, value: &str| {
+        if !value.is_empty() {
+            output.push_str(&format!("     {}: {}\n", label, value));
+        }
+    };
+
+    if let Some(bext) = &broadcast.bext {
+        line("説明", &bext.description);
+        line("作成者", &bext.originator);
+        line("作成者参照", &bext.originator_reference);
+        line(
+            "作成日時",
+            format!("{} {}", bext.origination_date, bext.origination_time).trim(),
+        );
+        if let Some(umid) = &bext.umid {
+            line("UMID", umid);
+        }
+        if let Some(loudness) = &bext.loudness {
+            line(
+                "ラウドネス",
+                &format!(
+                    "{:.1} LUFS (LRA {:.1} LU, True Peak {:.1} dBTP)",
+                    loudness.integrated, loudness.range, loudness.max_true_peak
+                ),
+            );
+        }
+    }
+    if let Some(timecode) = &broadcast.timecode_start {
+        line("開始タイムコード", timecode);
+    }
+    if let Some(ixml) = &broadcast.ixml {
+        for (label, value) in [
+            ("プロジェクト", &ixml.project),
+            ("シーン", &ixml.scene),
+            ("テイク", &ixml.take),
+            ("テープ", &ixml.tape),
+            ("ノート", &ixml.note),
+        ] {
+            if let Some(value) = value {
+                line(label, value);
+            }
+        }
+        let tracks: Vec<&str> = ixml.tracks.iter().map(|t| t.name.as_str()).collect();
+        line("トラック名", &tracks.join(", "));
+    }
+    if let Some(adm) = &broadcast.adm {
+        line("ADMトラック数", &adm.track_count.to_string());
+        line("ADMプログラム", &adm.programmes.join(", "));
+        line("ADMオブジェクト", &adm.objects.join(", "));
+        line("ADMパック種別", &adm.pack_types.join(", "));
+    }
+    for marker in &broadcast.markers {
+        line(
+            &format!("マーカー{}", marker.id),
+            format!(
+                "{:.3}秒 {}",
+                marker.time_seconds,
+                marker.label.as_deref().unwrap_or_default()
+            )
+            .trim(),
+        );
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -706,6 +1520,70 @@ mod tests {
         assert!(matches!(result, Err(AudioProbeError::FileNotFound { .. })));
     }
 
+    #[tokio::test]
+    async fn test_native_wav_analysis() {
+        let probe = AudioProbe::new(1).await.unwrap();
+        let info = probe
+            .analyze_file(PathBuf::from("data/bird.wav"))
+            .await
+            .unwrap();
+        assert_eq!(info.codec_name, "pcm_s16le");
+        assert_eq!(info.sample_rate, 44100);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.bits_per_sample, 16);
+        assert!((info.duration_seconds - 5.0736).abs() < 0.001);
+        assert_eq!(info.streams.len(), 1);
+        assert_eq!(info.streams[0].codec_name, "pcm_s16le");
+        assert_eq!(info.sample_fmt.as_deref(), Some("s16"));
+        assert_eq!(info.streams[0].sample_fmt.as_deref(), Some("s16"));
+        assert_eq!(info.nb_streams, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_placeholder_metadata_is_opt_in() {
+        let probe = AudioProbe::new(1).await.unwrap();
+        let info = probe
+            .analyze_file(PathBuf::from("data/bird.wav"))
+            .await
+            .unwrap();
+        assert!(!info.metadata.contains_key("artist"));
+        assert!(info.provenance.is_empty());
+
+        let probe = probe.with_placeholder_metadata(true);
+        let info = probe
+            .analyze_file(PathBuf::from("data/bird.wav"))
+            .await
+            .unwrap();
+        assert_eq!(info.metadata["title"], "bird");
+        assert_eq!(info.metadata["artist"], "Unknown Artist");
+        assert_eq!(info.provenance["metadata.title"], FieldSource::Filename);
+        assert_eq!(info.provenance["metadata.artist"], FieldSource::Default);
+        assert!(!info.estimated);
+    }
+
+    #[tokio::test]
+    async fn test_content_sniffing_overrides_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let renamed = dir.path().join("bird.mp3");
+        let extensionless = dir.path().join("bird");
+        std::fs::copy("data/bird.wav", &renamed).unwrap();
+        std::fs::copy("data/bird.wav", &extensionless).unwrap();
+
+        let probe = AudioProbe::new(1).await.unwrap();
+        let info = probe.analyze_file(renamed).await.unwrap();
+        assert_eq!(info.codec_name, "pcm_s16le");
+        assert_eq!(info.format_name, "wav");
+        assert_eq!(
+            info.warnings,
+            vec!["File extension '.mp3' does not match detected content (RIFF/WAVE)"]
+        );
+
+        assert!(!probe.is_audio_candidate(&extensionless));
+        let probe = probe.with_content_detection(true);
+        assert!(probe.is_audio_candidate(&extensionless));
+        assert_eq!(probe.collect_audio_files(dir.path()).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_audio_info_creation() {
         let path = PathBuf::from("test.mp3");
@@ -729,6 +1607,278 @@ mod tests {
         assert_eq!(format_duration(3661.0), "1時間1分1秒");
     }
 
+    #[test]
+    fn test_ffprobe_stream_conversion() {
+        let json = r#"{
+            "format": {"filename": "a.mkv", "format_name": "matroska,webm",
+                       "format_long_name": "Matroska / WebM", "start_time": "0.007000",
+                       "nb_streams": 2, "probe_score": 100},
+            "streams": [
+                {"index": 1, "codec_name": "aac", "codec_type": "audio", "profile": "LC",
+                 "sample_rate": "48000", "channels": 6, "channel_layout": "5.1",
+                 "bit_rate": "384000", "disposition": {"default": 1, "forced": 0, "comment": 1},
+                 "tags": {"language": "jpn", "title": "Director", "HANDLER_NAME": "Sound"}},
+                {"index": 2, "codec_name": "flac", "codec_type": "audio",
+                 "sample_rate": "96000", "channels": 2, "bits_per_sample": 0,
+                 "bits_per_raw_sample": "24", "sample_fmt": "s32", "time_base": "1/96000",
+                 "start_time": "0.000000", "duration_ts": 960000, "nb_frames": "235",
+                 "tags": {"language": "und"}}
+            ]
+        }"#;
+        let output: FFProbeOutput = serde_json::from_str(json).unwrap();
+        let format = output.format.unwrap();
+        assert_eq!(format.probe_score, Some(100));
+        assert_eq!(format.nb_streams, Some(2));
+        assert_eq!(format.start_time.as_deref(), Some("0.007000"));
+        let streams: Vec<StreamInfo> = output
+            .streams
+            .into_iter()
+            .map(FFProbeStream::into_stream_info)
+            .collect();
+
+        assert_eq!(streams[0].profile.as_deref(), Some("LC"));
+        assert_eq!(streams[0].language.as_deref(), Some("jpn"));
+        assert_eq!(streams[0].title.as_deref(), Some("Director"));
+        assert!(streams[0].disposition.default && streams[0].disposition.commentary);
+        assert_eq!(streams[0].tags["handler_name"], "Sound");
+        assert_eq!(
+            format_stream(&streams[0]),
+            "     #1 audio: aac (LC) 48000 Hz 5.1 384 kbps [jpn] \"Director\" \
+             (default, commentary)\n       handler_name: Sound\n"
+        );
+        assert_eq!(streams[1].bits_per_sample, 24);
+        assert_eq!(streams[1].bits_per_raw_sample, Some(24));
+        assert_eq!(streams[1].sample_fmt.as_deref(), Some("s32"));
+        assert_eq!(streams[1].time_base.as_deref(), Some("1/96000"));
+        assert_eq!(streams[1].start_time, Some(0.0));
+        assert_eq!(streams[1].duration_ts, Some(960_000));
+        assert_eq!(streams[1].nb_frames, Some(235));
+        assert_eq!(streams[1].language, None);
+        assert_eq!(
+            format_stream(&streams[1]),
+            "     #2 audio: flac 96000 Hz 2ch 24 bit s32\n"
+        );
+    }
+
+    #[test]
+    fn test_ffprobe_attached_picture() {
+        let json = r#"{
+            "streams": [
+                {"index": 0, "codec_name": "mp3", "codec_type": "audio",
+                 "sample_rate": "44100", "channels": 2, "disposition": {"default": 1}},
+                {"index": 1, "codec_name": "mjpeg", "codec_type": "video",
+                 "width": 600, "height": 600,
+                 "disposition": {"default": 0, "attached_pic": 1},
+                 "tags": {"comment": "Cover (front)"}}
+            ]
+        }"#;
+        let output: FFProbeOutput = serde_json::from_str(json).unwrap();
+        let artwork: Vec<Artwork> = output
+            .streams
+            .iter()
+            .filter_map(FFProbeStream::attached_picture)
+            .collect();
+        assert_eq!(artwork.len(), 1);
+        assert_eq!(artwork[0].picture_type, "Cover (front)");
+        assert_eq!(artwork[0].mime_type, "image/jpeg");
+        assert_eq!(
+            (artwork[0].width, artwork[0].height),
+            (Some(600), Some(600))
+        );
+        assert_eq!(
+            format_artwork(0, &artwork[0]),
+            "     #0 Cover (front) image/jpeg 600x600 [ffprobe]\n"
+        );
+
+        let streams: Vec<StreamInfo> = output
+            .streams
+            .into_iter()
+            .map(FFProbeStream::into_stream_info)
+            .collect();
+        assert!(streams[1].disposition.attached_pic);
+    }
+
+    #[test]
+    fn test_ffprobe_chapter_conversion() {
+        let json = r#"{
+            "streams": [],
+            "chapters": [
+                {"id": 0, "time_base": "1/1000", "start": 0, "start_time": "0.000000",
+                 "end": 65500, "end_time": "65.500000", "tags": {"title": "Intro"}},
+                {"id": 1, "time_base": "1/1000", "start": 65500, "start_time": "65.500000",
+                 "end": 3725000, "end_time": "3725.000000",
+                 "tags": {"title": "Main", "URL": "https://example.com/"}}
+            ]
+        }"#;
+        let output: FFProbeOutput = serde_json::from_str(json).unwrap();
+        let chapters: Vec<Chapter> = output
+            .chapters
+            .into_iter()
+            .map(FFProbeChapter::into_chapter)
+            .collect();
+
+        assert_eq!(chapters[0].title.as_deref(), Some("Intro"));
+        assert_eq!(chapters[1].start_time, 65.5);
+        assert_eq!(chapters[1].tags["url"], "https://example.com/");
+        assert_eq!(
+            format_chapter(&chapters[0]),
+            "     #0 00:00:00.000 - 00:01:05.500 Intro\n"
+        );
+        assert_eq!(
+            format_chapter(&chapters[1]),
+            "     #1 00:01:05.500 - 01:02:05.000 Main\n       url: https://example.com/\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backend_chain_attribution() {
+        let dir = tempfile::tempdir().unwrap();
+        let unknown = dir.path().join("noise.xyz");
+        std::fs::write(&unknown, vec![0u8; 1000]).unwrap();
+
+        let probe = AudioProbe::new(1)
+            .await
+            .unwrap()
+            .with_backends(&[BackendKind::Native, BackendKind::Estimate]);
+        let info = probe
+            .analyze_file(PathBuf::from("data/bird.wav"))
+            .await
+            .unwrap();
+        assert_eq!(info.backend, Some(BackendKind::Native));
+        assert!(info.failed_attempts.is_empty());
+
+        let info = probe.analyze_file(unknown.clone()).await.unwrap();
+        assert_eq!(info.backend, Some(BackendKind::Estimate));
+        assert!(info.estimated);
+        assert_eq!(info.failed_attempts.len(), 1);
+        assert_eq!(info.failed_attempts[0].backend, BackendKind::Native);
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["backend"], "estimate");
+        assert_eq!(json["failed_attempts"][0]["backend"], "native");
+        assert_eq!(json["failed_attempts"][0]["kind"], "invalid_audio_file");
+
+        // 1つに固定したバックエンドが失敗した場合は推定で埋めない
+        let native_only = probe.clone().with_backends(&[BackendKind::Native]);
+        assert!(native_only.analyze_file(unknown).await.is_err());
+
+        let missing = AudioProbe::new_with_ffprobe(1, &dir.path().join("missing"))
+            .await
+            .unwrap()
+            .with_backends(&[BackendKind::Ffprobe]);
+        assert!(missing.backend_kinds().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_structured_errors() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let probe = AudioProbe::new(1).await.unwrap();
+        let error = probe
+            .analyze_file(PathBuf::from("missing.mp3"))
+            .await
+            .unwrap_err();
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "file_not_found");
+        assert_eq!(json["path"], "missing.mp3");
+        assert_eq!(json["message"], "File not found: missing.mp3");
+        assert!(json.get("backend").is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("ffprobe");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             if [ \"$1\" = -version ]; then echo 'ffprobe version 7.0'; exit 0; fi\n\
+             echo 'Invalid data found when processing input' >&2; exit 1\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let audio = dir.path().join("broken.xyz");
+        std::fs::write(&audio, b"broken").unwrap();
+
+        let probe = AudioProbe::new_with_ffprobe(1, &script)
+            .await
+            .unwrap()
+            .with_backends(&[BackendKind::Ffprobe]);
+        let error = probe.analyze_file(audio.clone()).await.unwrap_err();
+        assert_eq!(error.kind(), "ffprobe_failed");
+        assert_eq!(error.path(), audio);
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["backend"], "ffprobe");
+        assert_eq!(json["exit_code"], 1);
+        assert_eq!(json["stderr"], "Invalid data found when processing input");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_custom_ffprobe_path_and_old_version() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("ffprobe-2.1");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             if [ \"$1\" = -version ]; then echo 'ffprobe version 2.1.8'; exit 0; fi\n\
+             echo '{\"streams\": [{\"codec_type\": \"audio\", \"codec_name\": \"xyz\"}]}'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let audio = dir.path().join("unknown.xyz");
+        std::fs::write(&audio, b"not a known container").unwrap();
+
+        let probe = AudioProbe::new_with_ffprobe(1, &script).await.unwrap();
+        let ffprobe = probe.ffprobe.as_ref().unwrap();
+        assert_eq!(ffprobe.path, script);
+        assert_eq!((ffprobe.major, ffprobe.minor), (Some(2), Some(1)));
+
+        let info = probe.analyze_file(audio).await.unwrap();
+        assert_eq!(info.codec_name, "xyz");
+        // ffprobeが利用できる場合は既定でffprobeを最初に試す
+        assert_eq!(info.backend, Some(BackendKind::Ffprobe));
+        assert!(info.failed_attempts.is_empty());
+        assert_eq!(info.probe_score, None);
+        assert!(info
+            .warnings
+            .iter()
+            .any(|w| w == "ffprobe 2.1.8 does not report probe_score (requires 2.2 or newer)"));
+
+        let missing = AudioProbe::new_with_ffprobe(1, &dir.path().join("missing"))
+            .await
+            .unwrap();
+        assert!(missing.ffprobe.is_none());
+    }
+
+    #[test]
+    fn test_format_broadcast() {
+        use formats::bwf::{BextChunk, BroadcastWave, CueMarker};
+
+        let broadcast = BroadcastWave {
+            bext: Some(BextChunk {
+                description: "Scene 12".to_string(),
+                origination_date: "2024-05-01".to_string(),
+                origination_time: "14:30:00".to_string(),
+                ..Default::default()
+            }),
+            timecode_start: Some("01:00:00:00".to_string()),
+            ixml: None,
+            adm: None,
+            markers: vec![CueMarker {
+                id: 1,
+                time_seconds: 1.5,
+                label: Some("Cut".to_string()),
+                ..Default::default()
+            }],
+        };
+        let text = format_broadcast(&broadcast);
+        assert!(text.contains("説明: Scene 12\n"));
+        assert!(text.contains("作成日時: 2024-05-01 14:30:00\n"));
+        assert!(text.contains("開始タイムコード: 01:00:00:00\n"));
+        assert!(text.contains("マーカー1: 1.500秒 Cut\n"));
+        assert!(!text.contains("作成者"));
+    }
+
     #[test]
     fn test_format_bitrate() {
         assert_eq!(format_bitrate(128), "128 bps");
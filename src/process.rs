@@ -0,0 +1,227 @@
+// 外部プロセス（ffprobe）の実行
+//
+// 壊れたファイルでffprobeが終了しないと同時実行数の枠を占有し続けるため、ファイルごとの
+// タイムアウトを過ぎた子プロセスはプロセスグループごと強制終了する。
+// UnixではRLIMIT_CPU・RLIMIT_ASでCPU時間とメモリの上限も設定できる。
+
+use crate::backend::BackendKind;
+use crate::AudioProbeError;
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+/// 子プロセスに課すリソース制限（Unix以外では無視する）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// CPU時間の上限（秒, RLIMIT_CPU）
+    pub cpu_seconds: Option<u64>,
+    /// アドレス空間の上限（バイト, RLIMIT_AS）
+    pub address_space_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// exec直前の子プロセスで制限を設定する
+    #[cfg(unix)]
+    fn apply(self, command: &mut Command) {
+        if self == Self::default() {
+            return;
+        }
+        // SAFETY: fork後の子プロセスではasync-signal-safeなgetrlimit/setrlimitのみを呼ぶ
+        unsafe {
+            command.pre_exec(move || {
+                let set_limit = |resource, soft: u64, hard: u64| {
+                    let mut current = libc::rlimit {
+                        rlim_cur: 0,
+                        rlim_max: 0,
+                    };
+                    if libc::getrlimit(resource, &mut current) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    // 特権のないプロセスはハード制限を引き上げられない
+                    let hard = (hard as libc::rlim_t).min(current.rlim_max);
+                    let limit = libc::rlimit {
+                        rlim_cur: (soft as libc::rlim_t).min(hard),
+                        rlim_max: hard,
+                    };
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                };
+                if let Some(seconds) = self.cpu_seconds {
+                    // ソフト制限でSIGXCPU、それでも終了しなければハード制限でSIGKILL
+                    set_limit(libc::RLIMIT_CPU, seconds, seconds.saturating_add(1))?;
+                }
+                if let Some(bytes) = self.address_space_bytes {
+                    set_limit(libc::RLIMIT_AS, bytes, bytes)?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn apply(self, _command: &mut Command) {}
+}
+
+/// 子プロセスのプロセスグループ全体にSIGKILLを送る
+#[cfg(unix)]
+fn kill_process_group(pid: Option<u32>) {
+    if let Some(pid) = pid {
+        // process_group(0)で起動しているため、子プロセスのPIDがプロセスグループIDになる
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+}
+
+/// Unix以外ではkill_on_dropによる子プロセス自身の終了のみ
+#[cfg(not(unix))]
+fn kill_process_group(_pid: Option<u32>) {}
+
+/// `command`を実行して出力を集める
+///
+/// `timeout`を過ぎた場合は子プロセスをプロセスグループごと強制終了し、
+/// `path`（解析対象のファイル）と経過時間を持つ`AudioProbeError::Timeout`を返す
+pub async fn output_with_timeout(
+    mut command: Command,
+    path: &Path,
+    timeout: Option<Duration>,
+    limits: ResourceLimits,
+) -> Result<Output, AudioProbeError> {
+    let program = command
+        .as_std()
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    limits.apply(&mut command);
+
+    let start = Instant::now();
+    let failed = |message: String| AudioProbeError::FFprobeError {
+        path: path.to_path_buf(),
+        message,
+        exit_code: None,
+        stderr: None,
+    };
+    let child = command
+        .spawn()
+        .map_err(|e| failed(format!("Failed to execute {}: {}", program, e)))?;
+    let pid = child.id();
+    let output = child.wait_with_output();
+    tokio::pin!(output);
+
+    let sleep = async {
+        match timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        biased;
+        result = &mut output => {
+            result.map_err(|e| failed(format!("Failed to wait for {}: {}", program, e)))
+        }
+        _ = sleep => {
+            // 子プロセスの回収前（PIDが再利用されない間）にグループへ送る
+            kill_process_group(pid);
+            Err(AudioProbeError::Timeout {
+                path: path.to_path_buf(),
+                elapsed: start.elapsed(),
+                backend: BackendKind::Ffprobe,
+            })
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[tokio::test]
+    async fn test_output_within_timeout() {
+        let output = output_with_timeout(
+            shell("echo ok"),
+            Path::new("a.mp3"),
+            Some(Duration::from_secs(10)),
+            ResourceLimits::default(),
+        )
+        .await
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"ok\n");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_timeout_kills_process_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        // 孫プロセスが標準出力を開いたまま残っても待ち続けないこと
+        let script = format!("sleep 30 & echo $! > {:?}; wait", pid_file);
+
+        let start = Instant::now();
+        let result = output_with_timeout(
+            shell(&script),
+            Path::new("stuck.mp3"),
+            Some(Duration::from_millis(300)),
+            ResourceLimits::default(),
+        )
+        .await;
+        match result {
+            Err(AudioProbeError::Timeout { path, elapsed, .. }) => {
+                assert_eq!(path, Path::new("stuck.mp3"));
+                assert!(elapsed >= Duration::from_millis(300));
+            }
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        // 孫プロセス（sleep）も終了している（ゾンビは終了済みとみなす）
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat_path = format!("/proc/{}/stat", pid.trim());
+        let mut alive = true;
+        for _ in 0..50 {
+            alive = std::fs::read_to_string(&stat_path)
+                .map(|stat| !stat.contains(") Z "))
+                .unwrap_or(false);
+            if !alive {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!alive, "grandchild process survived the timeout");
+    }
+
+    #[tokio::test]
+    async fn test_resource_limits_applied() {
+        let limits = ResourceLimits {
+            cpu_seconds: Some(7),
+            address_space_bytes: Some(512 * 1024 * 1024),
+        };
+        let output = output_with_timeout(
+            shell("ulimit -t; ulimit -v"),
+            Path::new("a.mp3"),
+            None,
+            limits,
+        )
+        .await
+        .unwrap();
+        // ulimit -vはKiB単位
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "7\n524288\n");
+    }
+}